@@ -0,0 +1,220 @@
+use std::thread;
+
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use super::{AlarmFired, snooze};
+
+const ACTION_SNOOZE: &str = "snooze";
+const ACTION_DISMISS: &str = "dismiss";
+const ACTION_REMIND: &str = "remind";
+/// Delay of the "Remind in 10 min" button of a missed alarm.
+const REMIND_MINUTES: u32 = 10;
+
+/// A button on a notification: the id reported back when it is pressed and
+/// its caption.
+#[derive(Debug, Clone, Copy)]
+struct Action {
+    id: &'static str,
+    label: &'static str,
+}
+
+type OnAction = Box<dyn FnOnce(&str) + Send>;
+
+/// Shows the alarm's notification with Snooze (while snoozes are left) and
+/// Dismiss buttons, which act on the alarm directly so nothing needs the
+/// window. Falls back to a plain notification where buttons are not
+/// available.
+pub fn notify(app: &AppHandle, alarm: &AlarmFired) {
+    let title = if alarm.label.is_empty() {
+        "Alarm".to_string()
+    } else {
+        alarm.label.clone()
+    };
+    let body = alarm.time.clone();
+    let mut actions = Vec::with_capacity(2);
+    if alarm.snoozes_left > 0 {
+        actions.push(Action {
+            id: ACTION_SNOOZE,
+            label: "Snooze",
+        });
+    }
+    actions.push(Action {
+        id: ACTION_DISMISS,
+        label: "Dismiss",
+    });
+    show(app, alarm.id, title, body, actions);
+}
+
+/// Tells about an alarm that should have rung while the machine slept or
+/// the app was closed, offering to ring it in a few minutes instead.
+pub fn notify_missed(app: &AppHandle, alarm: &AlarmFired) {
+    let title = if alarm.label.is_empty() {
+        "Missed alarm".to_string()
+    } else {
+        format!("Missed alarm: {}", alarm.label)
+    };
+    let body = format!("Was due at {} while the clock was not running.", alarm.time);
+    let actions = vec![
+        Action {
+            id: ACTION_REMIND,
+            label: "Remind in 10 min",
+        },
+        Action {
+            id: ACTION_DISMISS,
+            label: "Dismiss",
+        },
+    ];
+    show(app, alarm.id, title, body, actions);
+}
+
+fn show(app: &AppHandle, id: u64, title: String, body: String, actions: Vec<Action>) {
+    let app = app.clone();
+    // The platform APIs block until the notification is answered.
+    let spawned = thread::Builder::new()
+        .name("alarm-notification".into())
+        .spawn(move || {
+            let handler = app.clone();
+            let on_action: OnAction = Box::new(move |action| handle(&handler, id, action));
+            if let Err(err) = platform::show(&app, &title, &body, &actions, on_action) {
+                eprintln!("failed to show actionable alarm notification: {err}");
+                show_plain(&app, &title, &body);
+            }
+        });
+    if let Err(err) = spawned {
+        eprintln!("failed to start alarm notification thread: {err}");
+    }
+}
+
+fn handle(app: &AppHandle, id: u64, action: &str) {
+    let result = match action {
+        ACTION_SNOOZE => snooze::snooze(app, id, None),
+        ACTION_DISMISS => snooze::dismiss(app, id),
+        ACTION_REMIND => snooze::remind(app, id, REMIND_MINUTES),
+        _ => return,
+    };
+    if let Err(err) = result {
+        eprintln!("failed to {action} alarm {id}: {err}");
+    }
+}
+
+fn show_plain(app: &AppHandle, title: &str, body: &str) {
+    if let Err(err) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("failed to show alarm notification: {err}");
+    }
+}
+
+/// Windows toasts carry buttons whose activation calls back into the
+/// process.
+#[cfg(windows)]
+mod platform {
+    use tauri::AppHandle;
+    use tauri_winrt_notification::Toast;
+
+    use super::{Action, OnAction};
+
+    pub fn show(
+        app: &AppHandle,
+        title: &str,
+        body: &str,
+        actions: &[Action],
+        on_action: OnAction,
+    ) -> Result<(), String> {
+        let mut on_action = Some(on_action);
+        let mut toast = Toast::new(&app.config().identifier)
+            .title(title)
+            .text1(body);
+        for action in actions {
+            toast = toast.add_button(action.label, action.id);
+        }
+        toast
+            .on_activated(move |action| {
+                if let (Some(action), Some(on_action)) = (action, on_action.take()) {
+                    on_action(&action);
+                }
+                Ok(())
+            })
+            .show()
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// macOS notifications have one action button and a close button; the
+/// response arrives once the user picks one.
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::Once;
+
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse, set_application};
+    use tauri::AppHandle;
+
+    use super::{Action, OnAction};
+
+    static SET_APPLICATION: Once = Once::new();
+
+    pub fn show(
+        app: &AppHandle,
+        title: &str,
+        body: &str,
+        actions: &[Action],
+        on_action: OnAction,
+    ) -> Result<(), String> {
+        SET_APPLICATION.call_once(|| {
+            if let Err(err) = set_application(&app.config().identifier) {
+                eprintln!("failed to set notification bundle: {err}");
+            }
+        });
+        let (main, close) = match actions {
+            [main, close] => (Some(main), close),
+            [close] => (None, close),
+            _ => return Err("unsupported number of notification actions".to_string()),
+        };
+        let mut notification = Notification::new();
+        notification
+            .title(title)
+            .message(body)
+            .close_button(close.label)
+            .wait_for_click(true);
+        if let Some(main) = main {
+            notification.main_button(MainButton::SingleAction(main.label));
+        }
+        let label = match notification.send().map_err(|err| err.to_string())? {
+            NotificationResponse::ActionButton(label)
+            | NotificationResponse::CloseButton(label) => label,
+            _ => return Ok(()),
+        };
+        if let Some(action) = actions.iter().find(|action| action.label == label) {
+            on_action(action.id);
+        }
+        Ok(())
+    }
+}
+
+/// Freedesktop notification servers report the pressed action over D-Bus.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use notify_rust::Notification;
+    use tauri::AppHandle;
+
+    use super::{Action, OnAction};
+
+    pub fn show(
+        app: &AppHandle,
+        title: &str,
+        body: &str,
+        actions: &[Action],
+        on_action: OnAction,
+    ) -> Result<(), String> {
+        let mut notification = Notification::new();
+        notification
+            .appname(&app.package_info().name)
+            .summary(title)
+            .body(body);
+        for action in actions {
+            notification.action(action.id, action.label);
+        }
+        let handle = notification.show().map_err(|err| err.to_string())?;
+        handle.wait_for_action(|action| on_action(action));
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use std::{fs, path::PathBuf};
+
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    ical::{self, Component},
+    store::StoreError,
+    sync::state::TimeState,
+    zone::active::active_tz,
+};
+
+use super::{
+    Alarm, AlarmError, AlarmSpec, AlarmStore, default_sound, full_volume, next_fire, publish,
+    recurrence::Recurrence, schedule,
+};
+
+/// An alarm `import_ics` creates, or would create on a dry run.
+#[derive(Debug, Clone, Serialize)]
+pub struct IcsAlarm {
+    /// Id of the created alarm; `None` on a dry run.
+    id: Option<u64>,
+    /// The event's SUMMARY.
+    summary: String,
+    alarm: AlarmSpec,
+    next_fire_epoch_millis: Option<i64>,
+}
+
+/// An event or VALARM left out of the import, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct IcsSkipped {
+    summary: String,
+    reason: String,
+}
+
+/// Result of `import_ics`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IcsImport {
+    /// Whether the alarms were created; `false` for a dry run.
+    imported: bool,
+    alarms: Vec<IcsAlarm>,
+    skipped: Vec<IcsSkipped>,
+}
+
+/// Reads the iCalendar file at `path` and turns its events into alarms on
+/// the main clock: one per VALARM, ringing at its TRIGGER, or one at the
+/// event's start when it has none. AUDIO reminders ring with the default
+/// sound; DISPLAY and other reminders only show the notification. An RRULE
+/// becomes the alarm's recurrence; EXDATE and RDATE are not read.
+///
+/// With `dry_run` nothing is created, so the frontend can show what the
+/// file holds and call again to confirm.
+#[tauri::command]
+pub fn import_ics(app: AppHandle, path: PathBuf, dry_run: bool) -> Result<IcsImport, AlarmError> {
+    let text = fs::read_to_string(&path).map_err(StoreError::from)?;
+    let components = ical::parse(&text).map_err(AlarmError::InvalidCalendar)?;
+    let zone = active_tz(&app)?;
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+
+    let mut alarms = Vec::new();
+    let mut skipped = Vec::new();
+    for event in ical::events(&components) {
+        let summary = event
+            .property("SUMMARY")
+            .map(|summary| ical::unescape(&summary.value))
+            .unwrap_or_default();
+        let mut skip = |reason: String| {
+            skipped.push(IcsSkipped {
+                summary: summary.clone(),
+                reason,
+            })
+        };
+        let specs = match event_alarms(event, &summary, zone) {
+            Ok(specs) => specs,
+            Err(reason) => {
+                skip(reason);
+                continue;
+            }
+        };
+        for spec in specs {
+            let alarm = match spec {
+                Ok(alarm) => alarm,
+                Err(reason) => {
+                    skip(reason);
+                    continue;
+                }
+            };
+            match next_fire(&alarm, zone, now_millis) {
+                Ok(Some(next)) => alarms.push(IcsAlarm {
+                    id: None,
+                    summary: summary.clone(),
+                    alarm,
+                    next_fire_epoch_millis: Some(next),
+                }),
+                _ => skip("no occurrences left".to_string()),
+            }
+        }
+    }
+
+    if !dry_run && !alarms.is_empty() {
+        let mut scheduled = Vec::with_capacity(alarms.len());
+        for imported in &alarms {
+            scheduled.push(schedule(&app, &imported.alarm)?);
+        }
+        app.state::<AlarmStore>().update(|existing| {
+            let mut id = existing.iter().map(|alarm| alarm.id).max().unwrap_or(0);
+            for (imported, next_fire_epoch_millis) in alarms.iter_mut().zip(scheduled) {
+                id += 1;
+                imported.id = Some(id);
+                imported.next_fire_epoch_millis = next_fire_epoch_millis;
+                existing.push(Alarm {
+                    id,
+                    spec: imported.alarm.clone(),
+                    next_fire_epoch_millis,
+                    snoozed_until_epoch_millis: None,
+                    snooze_count: 0,
+                });
+            }
+            Ok(())
+        })?;
+        publish(&app);
+    }
+    Ok(IcsImport {
+        imported: !dry_run,
+        alarms,
+        skipped,
+    })
+}
+
+/// The alarms for one VEVENT. The outer error skips the whole event; an
+/// inner one skips only that VALARM.
+fn event_alarms(
+    event: &Component,
+    summary: &str,
+    zone: Tz,
+) -> Result<Vec<Result<AlarmSpec, String>>, String> {
+    if event
+        .property("STATUS")
+        .is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED"))
+    {
+        return Err("event is cancelled".to_string());
+    }
+    let start = event
+        .property("DTSTART")
+        .ok_or("event has no DTSTART")?
+        .date_time(zone)?;
+    let end = match (event.property("DTEND"), event.property("DURATION")) {
+        (Some(end), _) => Some(end.date_time(zone)?),
+        (None, Some(duration)) => Some(
+            start
+                + ical::parse_duration(&duration.value)
+                    .ok_or_else(|| format!("invalid DURATION: {}", duration.value))?,
+        ),
+        (None, None) => None,
+    };
+    let rule = event.property("RRULE").map(|rule| rule.value.clone());
+
+    let reminders: Vec<&Component> = event
+        .children
+        .iter()
+        .filter(|child| child.name == "VALARM")
+        .collect();
+    if reminders.is_empty() {
+        return Ok(vec![alarm_spec(
+            summary,
+            start,
+            start,
+            rule.as_deref(),
+            default_sound(),
+            zone,
+        )]);
+    }
+    Ok(reminders
+        .into_iter()
+        .map(|reminder| {
+            let trigger = reminder
+                .property("TRIGGER")
+                .ok_or("VALARM has no TRIGGER")?;
+            let fire = if trigger
+                .param("VALUE")
+                .is_some_and(|value| value.eq_ignore_ascii_case("DATE-TIME"))
+            {
+                trigger.date_time(zone)?
+            } else {
+                let offset = ical::parse_duration(&trigger.value)
+                    .ok_or_else(|| format!("invalid TRIGGER: {}", trigger.value))?;
+                let related = match trigger.param("RELATED") {
+                    Some(related) if related.eq_ignore_ascii_case("END") => {
+                        end.ok_or("TRIGGER is relative to an end the event does not have")?
+                    }
+                    _ => start,
+                };
+                related + offset
+            };
+            let sound = match reminder.property("ACTION") {
+                Some(action) if action.value.eq_ignore_ascii_case("AUDIO") => default_sound(),
+                _ => None,
+            };
+            alarm_spec(summary, start, fire, rule.as_deref(), sound, zone)
+        })
+        .collect())
+}
+
+/// An alarm ringing at `fire` on the main clock, repeating with `rule` when
+/// the event recurs and on the day of `fire` alone when it does not. The
+/// alarm keeps the main clock's current zone, so the event's times stay put
+/// if the clock later shows another.
+fn alarm_spec(
+    summary: &str,
+    start: DateTime<Tz>,
+    fire: DateTime<Tz>,
+    rule: Option<&str>,
+    sound: Option<String>,
+    zone: Tz,
+) -> Result<AlarmSpec, String> {
+    let fire = fire.with_timezone(&zone);
+    let date = fire.date_naive();
+    let rule = match rule {
+        // The BY parts name the event's days, which a reminder the day
+        // before no longer falls on.
+        Some(rule)
+            if date != start.with_timezone(&zone).date_naive()
+                && rule.to_ascii_uppercase().contains("BY") =>
+        {
+            return Err("reminders on another day than a recurring event are not supported".into());
+        }
+        Some(rule) => rule.to_string(),
+        None => format!("FREQ=DAILY;UNTIL={}", date.format("%Y%m%d")),
+    };
+    let recurrence = Recurrence::Rrule {
+        rule,
+        start: Some(date.format("%Y-%m-%d").to_string()),
+    };
+    recurrence.validate()?;
+    Ok(AlarmSpec {
+        time: fire.format("%H:%M").to_string(),
+        time_zone: Some(zone.name().to_string()),
+        label: summary.to_string(),
+        enabled: true,
+        recurrence,
+        snooze: Default::default(),
+        sound,
+        volume: full_volume(),
+        ramp_secs: 0,
+        critical: false,
+    })
+}
@@ -0,0 +1,367 @@
+mod actions;
+pub mod ics;
+pub mod recurrence;
+pub mod scheduler;
+pub mod snooze;
+pub mod system_task;
+pub mod tray;
+
+use std::{path::PathBuf, sync::Mutex};
+
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use crate::{
+    audio::{AudioError, AudioPlayer, tones},
+    store::{self, StoreError},
+    sync::{TimeSyncError, state::TimeState},
+    zone::{active::active_tz, parse_zone},
+};
+
+use recurrence::Recurrence;
+use snooze::SnoozePolicy;
+
+pub const EVENT_ALARMS_CHANGED: &str = "alarms-changed";
+pub const EVENT_ALARM_FIRED: &str = "alarm-fired";
+pub const EVENT_ALARM_MISSED: &str = "alarm-missed";
+
+const ALARMS_FILE_NAME: &str = "alarms.json";
+
+#[derive(Debug, Error)]
+pub enum AlarmError {
+    #[error("invalid alarm time: {0}")]
+    InvalidTime(String),
+    #[error("invalid recurrence: {0}")]
+    InvalidRecurrence(String),
+    #[error("invalid calendar file: {0}")]
+    InvalidCalendar(String),
+    #[error(transparent)]
+    Sound(#[from] AudioError),
+    #[error("snoozed the maximum of {0} times")]
+    SnoozeLimit(u32),
+    #[error("no alarm with id {0}")]
+    NotFound(u64),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl AlarmError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidTime(_) => "invalid_alarm_time",
+            Self::InvalidRecurrence(_) => "invalid_recurrence",
+            Self::InvalidCalendar(_) => "invalid_calendar",
+            Self::Sound(err) => err.code(),
+            Self::SnoozeLimit(_) => "snooze_limit",
+            Self::NotFound(_) => "alarm_not_found",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for AlarmError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AlarmError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// What the user sets up for an alarm; `create_alarm` and `update_alarm`
+/// take it whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmSpec {
+    /// Wall-clock time, "HH:MM", in `time_zone`.
+    pub time: String,
+    /// IANA zone the time and days are read in; `None` follows the main
+    /// clock.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub recurrence: Recurrence,
+    #[serde(default)]
+    pub snooze: SnoozePolicy,
+    /// Id of the sound played while the alarm rings; `None` shows only the
+    /// notification.
+    #[serde(default = "default_sound")]
+    pub sound: Option<String>,
+    /// Sound volume from 0 to 1.
+    #[serde(default = "full_volume")]
+    pub volume: f32,
+    /// Seconds over which the sound rises from silence to `volume`; 0
+    /// starts at `volume`.
+    #[serde(default)]
+    pub ramp_secs: u32,
+    /// Rings and notifies even while the OS is in Do Not Disturb.
+    #[serde(default)]
+    pub critical: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn default_sound() -> Option<String> {
+    Some(tones::DEFAULT_TONE.to_string())
+}
+
+fn full_volume() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alarm {
+    pub id: u64,
+    #[serde(flatten)]
+    pub spec: AlarmSpec,
+    /// When the alarm rings next; `None` while it is disabled.
+    pub next_fire_epoch_millis: Option<i64>,
+    /// When a snoozed alarm rings again. Independent of `enabled`, so a
+    /// one-off alarm can be snoozed after it disabled itself.
+    #[serde(default)]
+    pub snoozed_until_epoch_millis: Option<i64>,
+    /// Snoozes since the alarm last rang on schedule.
+    #[serde(default)]
+    pub snooze_count: u32,
+}
+
+/// Payload of the `alarm-fired` and `alarm-missed` events.
+#[derive(Clone, Serialize)]
+pub struct AlarmFired {
+    pub id: u64,
+    pub label: String,
+    pub time: String,
+    pub time_zone: Option<String>,
+    pub scheduled_epoch_millis: i64,
+    /// Corrected time the scheduler noticed the alarm was due.
+    pub fired_epoch_millis: i64,
+    /// Whether this is a snoozed alarm ringing again.
+    pub snoozed: bool,
+    pub snoozes_left: u32,
+    pub sound: Option<String>,
+    pub volume: f32,
+    pub ramp_secs: u32,
+    pub critical: bool,
+}
+
+impl AlarmFired {
+    fn new(alarm: &Alarm, scheduled_epoch_millis: i64, fired_epoch_millis: i64) -> Self {
+        Self {
+            id: alarm.id,
+            label: alarm.spec.label.clone(),
+            time: alarm.spec.time.clone(),
+            time_zone: alarm.spec.time_zone.clone(),
+            scheduled_epoch_millis,
+            fired_epoch_millis,
+            snoozed: alarm.snooze_count > 0,
+            snoozes_left: alarm
+                .spec
+                .snooze
+                .max_snoozes
+                .saturating_sub(alarm.snooze_count),
+            sound: alarm.spec.sound.clone(),
+            volume: alarm.spec.volume,
+            ramp_secs: alarm.spec.ramp_secs,
+            critical: alarm.spec.critical,
+        }
+    }
+}
+
+/// All alarms, saved to `alarms.json` in the app data directory whenever
+/// one is added, edited, removed, or fires.
+pub struct AlarmStore {
+    alarms: Mutex<Vec<Alarm>>,
+    path: Option<PathBuf>,
+    system_task: Mutex<system_task::Registered>,
+}
+
+impl AlarmStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, ALARMS_FILE_NAME);
+        let alarms = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            alarms: Mutex::new(alarms),
+            path,
+            system_task: Mutex::default(),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Alarm> {
+        self.alarms
+            .lock()
+            .map(|alarms| alarms.clone())
+            .unwrap_or_default()
+    }
+
+    /// Applies `change` and saves the result. When `change` or the save
+    /// fails, the list is left untouched.
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Vec<Alarm>) -> Result<T, AlarmError>,
+    ) -> Result<T, AlarmError> {
+        store::update_locked(self.path.as_deref(), &self.alarms, change)
+    }
+}
+
+#[tauri::command]
+pub fn list_alarms(app: AppHandle) -> Vec<Alarm> {
+    app.state::<AlarmStore>().list()
+}
+
+#[tauri::command]
+pub fn create_alarm(app: AppHandle, alarm: AlarmSpec) -> Result<Alarm, AlarmError> {
+    let next_fire_epoch_millis = schedule(&app, &alarm)?;
+    let created = app.state::<AlarmStore>().update(|alarms| {
+        let id = alarms.iter().map(|alarm| alarm.id).max().unwrap_or(0) + 1;
+        let created = Alarm {
+            id,
+            spec: alarm,
+            next_fire_epoch_millis,
+            snoozed_until_epoch_millis: None,
+            snooze_count: 0,
+        };
+        alarms.push(created.clone());
+        Ok(created)
+    })?;
+    publish(&app);
+    Ok(created)
+}
+
+/// Replaces the alarm's settings and reschedules it from now, dropping any
+/// pending snooze.
+#[tauri::command]
+pub fn update_alarm(app: AppHandle, id: u64, alarm: AlarmSpec) -> Result<Alarm, AlarmError> {
+    let next_fire_epoch_millis = schedule(&app, &alarm)?;
+    let updated = app.state::<AlarmStore>().update(|alarms| {
+        let existing = find_mut(alarms, id)?;
+        existing.spec = alarm;
+        existing.next_fire_epoch_millis = next_fire_epoch_millis;
+        existing.snoozed_until_epoch_millis = None;
+        existing.snooze_count = 0;
+        Ok(existing.clone())
+    })?;
+    publish(&app);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn delete_alarm(app: AppHandle, id: u64) -> Result<(), AlarmError> {
+    app.state::<AlarmStore>().update(|alarms| {
+        let index = alarms
+            .iter()
+            .position(|alarm| alarm.id == id)
+            .ok_or(AlarmError::NotFound(id))?;
+        alarms.remove(index);
+        Ok(())
+    })?;
+    publish(&app);
+    Ok(())
+}
+
+/// Skips the alarm's next occurrence; a one-off alarm is disabled instead.
+/// Any pending snooze is dropped.
+#[tauri::command]
+pub fn skip_alarm(app: AppHandle, id: u64) -> Result<Alarm, AlarmError> {
+    skip(&app, id)
+}
+
+pub fn skip(app: &AppHandle, id: u64) -> Result<Alarm, AlarmError> {
+    let zone = active_tz(app)?;
+    let skipped = app.state::<AlarmStore>().update(|alarms| {
+        let alarm = find_mut(alarms, id)?;
+        alarm.next_fire_epoch_millis = match alarm.next_fire_epoch_millis {
+            Some(next) if !alarm.spec.recurrence.is_once() => next_fire(&alarm.spec, zone, next)?,
+            _ => None,
+        };
+        alarm.spec.enabled = alarm.next_fire_epoch_millis.is_some();
+        alarm.snoozed_until_epoch_millis = None;
+        alarm.snooze_count = 0;
+        Ok(alarm.clone())
+    })?;
+    publish(app);
+    Ok(skipped)
+}
+
+/// Moves alarms without a zone of their own on to their next time in the
+/// main clock's zone, after that zone changed.
+pub fn rezone(app: &AppHandle) -> Result<(), AlarmError> {
+    let follows_main_clock =
+        |alarm: &Alarm| alarm.spec.time_zone.is_none() && alarm.next_fire_epoch_millis.is_some();
+    let store = app.state::<AlarmStore>();
+    if !store.list().iter().any(follows_main_clock) {
+        return Ok(());
+    }
+
+    let zone = active_tz(app)?;
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    store.update(|alarms| {
+        for alarm in alarms.iter_mut().filter(|alarm| follows_main_clock(alarm)) {
+            alarm.next_fire_epoch_millis = next_fire(&alarm.spec, zone, now_millis)?;
+            alarm.spec.enabled = alarm.next_fire_epoch_millis.is_some();
+        }
+        Ok(())
+    })?;
+    publish(app);
+    Ok(())
+}
+
+fn find_mut(alarms: &mut [Alarm], id: u64) -> Result<&mut Alarm, AlarmError> {
+    alarms
+        .iter_mut()
+        .find(|alarm| alarm.id == id)
+        .ok_or(AlarmError::NotFound(id))
+}
+
+/// Next time `alarm` rings, or `None` if it is disabled.
+fn schedule(app: &AppHandle, alarm: &AlarmSpec) -> Result<Option<i64>, AlarmError> {
+    if let Some(sound) = &alarm.sound {
+        app.state::<AudioPlayer>().source(sound)?;
+    }
+    alarm
+        .recurrence
+        .validate()
+        .map_err(AlarmError::InvalidRecurrence)?;
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    next_fire(alarm, active_tz(app)?, now_millis)
+}
+
+/// First time after `after_millis` that `alarm` rings, or `None` if it is
+/// disabled or its recurrence has ended. `zone` is the main clock's, used
+/// when the alarm has no zone of its own.
+fn next_fire(alarm: &AlarmSpec, zone: Tz, after_millis: i64) -> Result<Option<i64>, AlarmError> {
+    let time = NaiveTime::parse_from_str(alarm.time.trim(), "%H:%M")
+        .map_err(|_| AlarmError::InvalidTime(alarm.time.clone()))?;
+    let zone = match &alarm.time_zone {
+        Some(name) => parse_zone(name)?,
+        None => zone,
+    };
+    if !alarm.enabled {
+        return Ok(None);
+    }
+    Ok(alarm.recurrence.next_fire(zone, time, after_millis))
+}
+
+/// Announces a change to the alarm list to the window and the tray, and
+/// moves the OS task on to the next alarm.
+fn publish(app: &AppHandle) {
+    let alarms = app.state::<AlarmStore>().list();
+    if let Err(err) = app.emit(EVENT_ALARMS_CHANGED, alarms) {
+        eprintln!("failed to emit {EVENT_ALARMS_CHANGED}: {err}");
+    }
+    crate::refresh_tray_menu(app);
+    system_task::sync(app);
+}
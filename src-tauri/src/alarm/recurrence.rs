@@ -0,0 +1,298 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::zone::datemath;
+
+/// How far ahead to look for the next matching day: past every leap day of
+/// a four-year cycle, so `FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29` is found.
+const SEARCH_DAYS: u64 = 4 * 366 + 1;
+const WEEKDAY_CODES: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+/// When an alarm rings again after ringing once. Days are matched in the
+/// alarm's zone and the alarm rings at its wall-clock time on each, so a
+/// 07:00 alarm stays at 07:00 across DST changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Rings the next time the clock shows the alarm's time, then disables
+    /// itself.
+    #[default]
+    Once,
+    /// Bit 0 is Monday through bit 6 for Sunday, so weekdays are `0b0011111`.
+    Weekly { weekdays: u8 },
+    /// Every `interval` days counting from `start` ("YYYY-MM-DD").
+    EveryNDays { interval: u32, start: String },
+    /// On `day` of each month; months too short for it ring on their last
+    /// day.
+    Monthly { day: u32 },
+    /// An iCalendar RRULE such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`.
+    /// Supports FREQ (DAILY, WEEKLY, MONTHLY, YEARLY), INTERVAL, BYDAY
+    /// without ordinals, BYMONTHDAY (negative counts from the month's end),
+    /// BYMONTH, and UNTIL. `start` ("YYYY-MM-DD") plays the part of DTSTART
+    /// and is needed for an INTERVAL above 1 or a rule without BY parts.
+    Rrule { rule: String, start: Option<String> },
+}
+
+impl Recurrence {
+    pub fn is_once(&self) -> bool {
+        *self == Self::Once
+    }
+
+    /// Checks the recurrence can be evaluated, describing what is wrong.
+    pub fn validate(&self) -> Result<(), String> {
+        self.rule().map(|_| ())
+    }
+
+    /// First instant after `after_millis` that falls on a matching day of
+    /// `zone` at `time`. Times skipped by a DST gap ring right after it.
+    pub fn next_fire(&self, zone: Tz, time: NaiveTime, after_millis: i64) -> Option<i64> {
+        let rule = self.rule().ok()?;
+        let today = DateTime::from_timestamp_millis(after_millis)?
+            .with_timezone(&zone)
+            .date_naive();
+        (0..=SEARCH_DAYS)
+            .map_while(|offset| today.checked_add_days(Days::new(offset)))
+            .take_while(|date| rule.until.is_none_or(|until| *date <= until))
+            .filter(|date| rule.matches(*date))
+            .filter_map(|date| datemath::resolve(zone, date.and_time(time)))
+            .map(|local| local.timestamp_millis())
+            .find(|epoch_millis| *epoch_millis > after_millis)
+    }
+
+    fn rule(&self) -> Result<Rule, String> {
+        match self {
+            Self::Once => Ok(Rule::new(Frequency::Daily)),
+            Self::Weekly { weekdays } => {
+                if *weekdays == 0 || *weekdays >= 1 << 7 {
+                    return Err(format!("weekday mask out of range: {weekdays:#b}"));
+                }
+                Ok(Rule {
+                    weekdays: *weekdays,
+                    ..Rule::new(Frequency::Weekly)
+                })
+            }
+            Self::EveryNDays { interval, start } => {
+                if *interval == 0 {
+                    return Err("interval must be at least 1".to_string());
+                }
+                Ok(Rule {
+                    interval: *interval,
+                    start: Some(parse_date(start)?),
+                    ..Rule::new(Frequency::Daily)
+                })
+            }
+            Self::Monthly { day } => {
+                if !(1..=31).contains(day) {
+                    return Err(format!("day of month out of range: {day}"));
+                }
+                Ok(Rule {
+                    month_days: vec![*day as i32],
+                    clamp_month_day: true,
+                    ..Rule::new(Frequency::Monthly)
+                })
+            }
+            Self::Rrule { rule, start } => {
+                let start = start.as_deref().map(parse_date).transpose()?;
+                Rule::parse(rule, start)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A recurrence reduced to the RRULE parts it uses. Empty BY parts match
+/// every day.
+#[derive(Debug, Clone)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    start: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    /// Same bit layout as `Recurrence::Weekly`; 0 matches every day.
+    weekdays: u8,
+    month_days: Vec<i32>,
+    months: Vec<u32>,
+    /// Whether a month day past the month's end matches its last day
+    /// instead of nothing.
+    clamp_month_day: bool,
+}
+
+impl Rule {
+    fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            start: None,
+            until: None,
+            weekdays: 0,
+            month_days: Vec::new(),
+            months: Vec::new(),
+            clamp_month_day: false,
+        }
+    }
+
+    fn parse(text: &str, start: Option<NaiveDate>) -> Result<Self, String> {
+        let mut frequency = None;
+        let mut rule = Self::new(Frequency::Daily);
+        let text = text.trim();
+        let text = text.strip_prefix("RRULE:").unwrap_or(text);
+        for part in text.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("malformed rule part: {part}"))?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("unsupported FREQ: {value}")),
+                    });
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("invalid INTERVAL: {value}"))?;
+                }
+                "BYDAY" => {
+                    for code in value.split(',') {
+                        let index = WEEKDAY_CODES
+                            .iter()
+                            .position(|known| known.eq_ignore_ascii_case(code.trim()))
+                            .ok_or_else(|| format!("unsupported BYDAY value: {code}"))?;
+                        rule.weekdays |= 1 << index;
+                    }
+                }
+                "BYMONTHDAY" => {
+                    rule.month_days = parse_list(value, |day: &i32| (1..=31).contains(&day.abs()))
+                        .ok_or_else(|| format!("invalid BYMONTHDAY: {value}"))?;
+                }
+                "BYMONTH" => {
+                    rule.months = parse_list(value, |month: &u32| (1..=12).contains(month))
+                        .ok_or_else(|| format!("invalid BYMONTH: {value}"))?;
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    rule.until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| format!("invalid UNTIL: {value}"))?,
+                    );
+                }
+                "WKST" => {}
+                other => return Err(format!("unsupported rule part: {other}")),
+            }
+        }
+        rule.frequency = frequency.ok_or("rule has no FREQ")?;
+        rule.start = start;
+
+        let has_by_parts =
+            rule.weekdays != 0 || !rule.month_days.is_empty() || !rule.months.is_empty();
+        if rule.start.is_none() {
+            if rule.interval > 1 {
+                return Err("INTERVAL above 1 needs a start date".to_string());
+            }
+            if !has_by_parts && rule.frequency != Frequency::Daily {
+                return Err("a rule without BY parts needs a start date".to_string());
+            }
+        }
+        // Like DTSTART, the start date supplies whatever the frequency
+        // leaves open.
+        if let Some(start) = rule.start.filter(|_| !has_by_parts) {
+            match rule.frequency {
+                Frequency::Daily => {}
+                Frequency::Weekly => rule.weekdays = 1 << start.weekday().num_days_from_monday(),
+                Frequency::Monthly => rule.month_days = vec![start.day() as i32],
+                Frequency::Yearly => {
+                    rule.months = vec![start.month()];
+                    rule.month_days = vec![start.day() as i32];
+                }
+            }
+        }
+        Ok(rule)
+    }
+
+    fn matches(&self, date: NaiveDate) -> bool {
+        if self.start.is_some_and(|start| date < start) {
+            return false;
+        }
+        if !self.months.is_empty() && !self.months.contains(&date.month()) {
+            return false;
+        }
+        if self.weekdays != 0 && self.weekdays & (1 << date.weekday().num_days_from_monday()) == 0 {
+            return false;
+        }
+        if !self.month_days.is_empty() && !self.matches_month_day(date) {
+            return false;
+        }
+        self.in_interval(date)
+    }
+
+    fn matches_month_day(&self, date: NaiveDate) -> bool {
+        let Some(length) = days_in_month(date) else {
+            return false;
+        };
+        let day = date.day() as i32;
+        self.month_days.iter().any(|wanted| {
+            let wanted = if *wanted < 0 {
+                length + 1 + wanted
+            } else if self.clamp_month_day {
+                (*wanted).min(length)
+            } else {
+                *wanted
+            };
+            wanted == day
+        })
+    }
+
+    /// Whether `date` falls in a period that is a multiple of `interval`
+    /// periods after the start.
+    fn in_interval(&self, date: NaiveDate) -> bool {
+        let Some(start) = self.start.filter(|_| self.interval > 1) else {
+            return true;
+        };
+        let periods = match self.frequency {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => {
+                let week_start = |day: NaiveDate| {
+                    day - chrono::Duration::days(i64::from(day.weekday().num_days_from_monday()))
+                };
+                (week_start(date) - week_start(start)).num_days() / 7
+            }
+            Frequency::Monthly => {
+                i64::from(date.year() - start.year()) * 12 + i64::from(date.month())
+                    - i64::from(start.month())
+            }
+            Frequency::Yearly => i64::from(date.year() - start.year()),
+        };
+        periods % i64::from(self.interval) == 0
+    }
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("invalid date, expected YYYY-MM-DD: {text}"))
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, valid: impl Fn(&T) -> bool) -> Option<Vec<T>> {
+    value
+        .split(',')
+        .map(|item| item.trim().parse().ok().filter(&valid))
+        .collect()
+}
+
+fn days_in_month(date: NaiveDate) -> Option<i32> {
+    let first = date.with_day(1)?;
+    let next = first.checked_add_months(Months::new(1))?;
+    i32::try_from((next - first).num_days()).ok()
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    audio::{AudioPlayer, Playback},
+    dnd,
+    settings::WebhookEvent,
+    sync::state::TimeState,
+    webhook,
+    zone::active::active_tz,
+};
+
+use super::{
+    AlarmError, AlarmFired, AlarmStore, EVENT_ALARM_FIRED, EVENT_ALARM_MISSED, actions, next_fire,
+    publish,
+};
+
+/// How often alarms are checked against the corrected clock, and so how late
+/// one may ring at most.
+const CHECK_PERIOD: Duration = Duration::from_secs(1);
+/// An alarm noticed later than this, because the machine slept or the app
+/// was closed at its time, is reported as missed instead of ringing.
+const MISSED_AFTER_MILLIS: i64 = 2 * 60 * 1000;
+
+/// Rings alarms whose time has come. Alarms found long overdue, at startup
+/// or after the machine wakes, are reported as missed instead, and move on
+/// to their next occurrence like the others.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match fire_due(&app) {
+                Ok(fired) if !fired.is_empty() => {
+                    publish(&app);
+                    for alarm in fired {
+                        if alarm.fired_epoch_millis - alarm.scheduled_epoch_millis
+                            > MISSED_AFTER_MILLIS
+                        {
+                            missed(&app, alarm);
+                        } else {
+                            ring(&app, alarm);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("failed to update fired alarms: {err}"),
+            }
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+/// Marks due alarms and snoozes as fired and returns them. Recurring
+/// alarms move on to their next occurrence; the others, and those whose
+/// recurrence has ended, are disabled. Ringing on schedule drops a pending
+/// snooze and resets the snooze count.
+fn fire_due(app: &AppHandle) -> Result<Vec<AlarmFired>, AlarmError> {
+    let store = app.state::<AlarmStore>();
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    let is_due = |at: Option<i64>| at.filter(|at| *at <= now_millis);
+    if !store.list().iter().any(|alarm| {
+        is_due(alarm.next_fire_epoch_millis).is_some()
+            || is_due(alarm.snoozed_until_epoch_millis).is_some()
+    }) {
+        return Ok(Vec::new());
+    }
+
+    let zone = active_tz(app)?;
+    store.update(|alarms| {
+        let mut fired = Vec::new();
+        for alarm in alarms.iter_mut() {
+            if let Some(scheduled) = is_due(alarm.next_fire_epoch_millis) {
+                alarm.next_fire_epoch_millis = if alarm.spec.recurrence.is_once() {
+                    None
+                } else {
+                    next_fire(&alarm.spec, zone, now_millis.max(scheduled))?
+                };
+                alarm.spec.enabled = alarm.next_fire_epoch_millis.is_some();
+                alarm.snoozed_until_epoch_millis = None;
+                alarm.snooze_count = 0;
+                fired.push(AlarmFired::new(alarm, scheduled, now_millis));
+            } else if let Some(snoozed_until) = is_due(alarm.snoozed_until_epoch_millis) {
+                alarm.snoozed_until_epoch_millis = None;
+                fired.push(AlarmFired::new(alarm, snoozed_until, now_millis));
+            }
+        }
+        Ok(fired)
+    })
+}
+
+/// Plays the alarm's sound and shows its notification, except for a
+/// non-critical alarm during Do Not Disturb, which only emits the event and
+/// calls the webhooks.
+fn ring(app: &AppHandle, alarm: AlarmFired) {
+    if alarm.critical || !dnd::suppresses(app) {
+        play_sound(app, &alarm);
+        actions::notify(app, &alarm);
+        crate::alert_main_window(app);
+    }
+    webhook::fire(
+        app,
+        WebhookEvent::AlarmFired,
+        &alarm.label,
+        vec![("alarm_time", alarm.time.clone())],
+    );
+    if let Err(err) = app.emit(EVENT_ALARM_FIRED, alarm) {
+        eprintln!("failed to emit {EVENT_ALARM_FIRED}: {err}");
+    }
+}
+
+/// Reports an alarm that was due long ago, silently, with a notification
+/// offering to ring it shortly.
+fn missed(app: &AppHandle, alarm: AlarmFired) {
+    if alarm.critical || !dnd::suppresses(app) {
+        actions::notify_missed(app, &alarm);
+    }
+    if let Err(err) = app.emit(EVENT_ALARM_MISSED, alarm) {
+        eprintln!("failed to emit {EVENT_ALARM_MISSED}: {err}");
+    }
+}
+
+fn play_sound(app: &AppHandle, alarm: &AlarmFired) {
+    if let Some(sound) = &alarm.sound {
+        let playback = Playback {
+            repeat: true,
+            volume: alarm.volume,
+            ramp: Duration::from_secs(u64::from(alarm.ramp_secs)),
+        };
+        if let Err(err) = app.state::<AudioPlayer>().play(sound, &playback) {
+            eprintln!("failed to play alarm sound {sound}: {err}");
+        }
+    }
+}
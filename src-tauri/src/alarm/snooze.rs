@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{audio::AudioPlayer, sync::state::TimeState};
+
+use super::{Alarm, AlarmError, AlarmStore, find_mut, publish};
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+
+/// How an alarm may be snoozed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnoozePolicy {
+    /// Used when `snooze_alarm` is called without a duration.
+    pub minutes: u32,
+    /// Snoozes allowed per ring; 0 disables snoozing.
+    pub max_snoozes: u32,
+}
+
+impl Default for SnoozePolicy {
+    fn default() -> Self {
+        Self {
+            minutes: 9,
+            max_snoozes: 3,
+        }
+    }
+}
+
+/// Rings the alarm again after `minutes`, or its policy's duration. The
+/// scheduler keeps the snooze, so it rings with the window closed and
+/// after a restart.
+#[tauri::command]
+pub fn snooze_alarm(app: AppHandle, id: u64, minutes: Option<u32>) -> Result<Alarm, AlarmError> {
+    snooze(&app, id, minutes)
+}
+
+/// Stops a ringing alarm and drops any pending snooze.
+#[tauri::command]
+pub fn dismiss_alarm(app: AppHandle, id: u64) -> Result<Alarm, AlarmError> {
+    dismiss(&app, id)
+}
+
+/// Rings the alarm once more after `minutes` without counting a snooze,
+/// for an alarm that was missed.
+#[tauri::command]
+pub fn remind_alarm(app: AppHandle, id: u64, minutes: u32) -> Result<Alarm, AlarmError> {
+    remind(&app, id, minutes)
+}
+
+pub fn snooze(app: &AppHandle, id: u64, minutes: Option<u32>) -> Result<Alarm, AlarmError> {
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    let snoozed = app.state::<AlarmStore>().update(|alarms| {
+        let alarm = find_mut(alarms, id)?;
+        let policy = &alarm.spec.snooze;
+        if alarm.snooze_count >= policy.max_snoozes {
+            return Err(AlarmError::SnoozeLimit(policy.max_snoozes));
+        }
+        let minutes = minutes.unwrap_or(policy.minutes).max(1);
+        alarm.snooze_count += 1;
+        alarm.snoozed_until_epoch_millis =
+            Some(now_millis + i64::from(minutes) * MILLIS_PER_MINUTE);
+        Ok(alarm.clone())
+    })?;
+    app.state::<AudioPlayer>().stop();
+    publish(app);
+    Ok(snoozed)
+}
+
+pub fn remind(app: &AppHandle, id: u64, minutes: u32) -> Result<Alarm, AlarmError> {
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    let reminded = app.state::<AlarmStore>().update(|alarms| {
+        let alarm = find_mut(alarms, id)?;
+        alarm.snoozed_until_epoch_millis =
+            Some(now_millis + i64::from(minutes.max(1)) * MILLIS_PER_MINUTE);
+        Ok(alarm.clone())
+    })?;
+    publish(app);
+    Ok(reminded)
+}
+
+pub fn dismiss(app: &AppHandle, id: u64) -> Result<Alarm, AlarmError> {
+    let dismissed = app.state::<AlarmStore>().update(|alarms| {
+        let alarm = find_mut(alarms, id)?;
+        alarm.snoozed_until_epoch_millis = None;
+        alarm.snooze_count = 0;
+        Ok(alarm.clone())
+    })?;
+    app.state::<AudioPlayer>().stop();
+    publish(app);
+    Ok(dismissed)
+}
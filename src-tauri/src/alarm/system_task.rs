@@ -0,0 +1,337 @@
+use std::sync::PoisonError;
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager};
+
+use crate::{settings::SettingsStore, store::StoreError, sync::state::TimeState};
+
+use super::{AlarmStore, tray::next_ring};
+
+/// How long after the next alarm the OS starts the app. A running app has
+/// rung the alarm and moved the task on by then, so only a closed app is
+/// started, early enough for the alarm to still ring rather than count as
+/// missed.
+const LAUNCH_DELAY_MILLIS: i64 = 30 * 1000;
+
+/// What this process last set the OS task to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Registered {
+    /// Nothing yet; a task from an earlier run may exist.
+    #[default]
+    Unknown,
+    Nothing,
+    /// Starts the app at this system-clock time.
+    At(i64),
+}
+
+/// Chooses whether the OS starts the app for alarms while it is closed.
+#[tauri::command]
+pub fn set_alarm_system_task(app: AppHandle, enabled: bool) -> Result<(), StoreError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.alarm_task.enabled = enabled)?;
+    sync(&app);
+    Ok(())
+}
+
+/// Points the OS task at the next alarm or snooze, or removes it when
+/// none is pending or `alarm_task.enabled` is off. Only the next ring needs
+/// a task: once started, the app rings it and registers the one after.
+///
+/// The task is a Task Scheduler task set to wake the computer on Windows, a
+/// launchd agent on macOS, and a systemd user timer with `WakeSystem` on
+/// Linux. macOS does not wake from sleep for it; the agent runs on waking.
+pub fn sync(app: &AppHandle) {
+    let app = app.clone();
+    let identifier = app.config().identifier.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = app.state::<AlarmStore>();
+        // Held throughout, so overlapping syncs apply in order.
+        let mut registered = store
+            .system_task
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // The OS schedules by its own clock, not the corrected one.
+        let system_offset =
+            app.state::<TimeState>().schedule_now_millis() - Utc::now().timestamp_millis();
+        let next = store.list().iter().filter_map(next_ring).min();
+        let wanted = match next {
+            Some(at) if app.state::<SettingsStore>().get().alarm_task.enabled => {
+                Registered::At(at + LAUNCH_DELAY_MILLIS - system_offset)
+            }
+            _ => Registered::Nothing,
+        };
+        if *registered == wanted {
+            return;
+        }
+        let result = match wanted {
+            Registered::At(at) => platform::register(&identifier, at),
+            _ => platform::remove(&identifier),
+        };
+        match result {
+            Ok(()) => *registered = wanted,
+            Err(err) => eprintln!("failed to update the system alarm task: {err}"),
+        }
+    });
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{fs, os::windows::process::CommandExt, process::Command};
+
+    use chrono::{DateTime, SecondsFormat};
+
+    const TASK_FOLDER: &str = "Adaptive Clock";
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    pub fn register(identifier: &str, at_millis: i64) -> Result<(), String> {
+        let at = DateTime::from_timestamp_millis(at_millis).ok_or("alarm time out of range")?;
+        let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <Triggers>
+    <TimeTrigger>
+      <StartBoundary>{start}</StartBoundary>
+      <Enabled>true</Enabled>
+    </TimeTrigger>
+  </Triggers>
+  <Principals>
+    <Principal>
+      <LogonType>InteractiveToken</LogonType>
+    </Principal>
+  </Principals>
+  <Settings>
+    <WakeToRun>true</WakeToRun>
+    <StartWhenAvailable>true</StartWhenAvailable>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+  </Settings>
+  <Actions>
+    <Exec>
+      <Command>{command}</Command>
+    </Exec>
+  </Actions>
+</Task>
+"#,
+            start = at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            command = escape_xml(&exe.to_string_lossy()),
+        );
+        // schtasks reads task XML as UTF-16.
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+        let path = std::env::temp_dir().join(format!("{identifier}.next-alarm.xml"));
+        fs::write(&path, bytes).map_err(|err| err.to_string())?;
+        let result = schtasks(&[
+            "/Create",
+            "/TN",
+            &task_name(identifier),
+            "/XML",
+            &path.to_string_lossy(),
+            "/F",
+        ]);
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    /// Deleting fails when there is no task, which is what is wanted.
+    pub fn remove(identifier: &str) -> Result<(), String> {
+        let _ = schtasks(&["/Delete", "/TN", &task_name(identifier), "/F"]);
+        Ok(())
+    }
+
+    fn task_name(identifier: &str) -> String {
+        format!("{TASK_FOLDER}\\{identifier}.next-alarm")
+    }
+
+    fn schtasks(args: &[&str]) -> Result<(), String> {
+        let output = Command::new("schtasks")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use chrono::{DateTime, Datelike, Local, Timelike};
+
+    pub fn register(identifier: &str, at_millis: i64) -> Result<(), String> {
+        // launchd matches calendar intervals to the minute, so round up.
+        let at = DateTime::from_timestamp_millis(at_millis + 59_999)
+            .ok_or("alarm time out of range")?
+            .with_timezone(&Local);
+        let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+        let label = label(identifier);
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{label}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>{program}</string>
+  </array>
+  <key>StartCalendarInterval</key>
+  <dict>
+    <key>Month</key>
+    <integer>{month}</integer>
+    <key>Day</key>
+    <integer>{day}</integer>
+    <key>Hour</key>
+    <integer>{hour}</integer>
+    <key>Minute</key>
+    <integer>{minute}</integer>
+  </dict>
+</dict>
+</plist>
+"#,
+            program = escape_xml(&exe.to_string_lossy()),
+            month = at.month(),
+            day = at.day(),
+            hour = at.hour(),
+            minute = at.minute(),
+        );
+        remove(identifier)?;
+        let path = plist_path(identifier)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(&path, plist).map_err(|err| err.to_string())?;
+        launchctl(&["load", "-w", &path.to_string_lossy()])
+    }
+
+    pub fn remove(identifier: &str) -> Result<(), String> {
+        let path = plist_path(identifier)?;
+        if !path.exists() {
+            return Ok(());
+        }
+        // Fails harmlessly when the agent is not loaded.
+        let _ = launchctl(&["unload", "-w", &path.to_string_lossy()]);
+        fs::remove_file(&path).map_err(|err| err.to_string())
+    }
+
+    fn label(identifier: &str) -> String {
+        format!("{identifier}.next-alarm")
+    }
+
+    fn plist_path(identifier: &str) -> Result<PathBuf, String> {
+        let home = std::env::var_os("HOME").ok_or("HOME is not set")?;
+        Ok(PathBuf::from(home)
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label(identifier))))
+    }
+
+    fn launchctl(args: &[&str]) -> Result<(), String> {
+        let output = Command::new("launchctl")
+            .args(args)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+/// The service starts the app inside the user's session, so it reaches the
+/// display as long as the desktop exported its environment to the user
+/// manager, as the common ones do.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use chrono::DateTime;
+
+    pub fn register(identifier: &str, at_millis: i64) -> Result<(), String> {
+        let at = DateTime::from_timestamp_millis(at_millis).ok_or("alarm time out of range")?;
+        let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+        let unit = unit(identifier);
+        let dir = unit_dir()?;
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        fs::write(
+            dir.join(format!("{unit}.service")),
+            format!(
+                "[Unit]\nDescription=Adaptive Clock alarm\n\n[Service]\nExecStart=\"{}\"\n",
+                exe.to_string_lossy().replace('"', "\\\"")
+            ),
+        )
+        .map_err(|err| err.to_string())?;
+        fs::write(
+            dir.join(format!("{unit}.timer")),
+            format!(
+                "[Unit]\nDescription=Start Adaptive Clock for the next alarm\n\n[Timer]\nOnCalendar={}\nAccuracySec=1s\nWakeSystem=true\n\n[Install]\nWantedBy=timers.target\n",
+                at.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+        )
+        .map_err(|err| err.to_string())?;
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", &format!("{unit}.timer")])?;
+        systemctl(&["restart", &format!("{unit}.timer")])
+    }
+
+    pub fn remove(identifier: &str) -> Result<(), String> {
+        let unit = unit(identifier);
+        let dir = unit_dir()?;
+        let timer = dir.join(format!("{unit}.timer"));
+        if !timer.exists() {
+            return Ok(());
+        }
+        // Fails harmlessly when the timer is not enabled.
+        let _ = systemctl(&["disable", "--now", &format!("{unit}.timer")]);
+        fs::remove_file(timer).map_err(|err| err.to_string())?;
+        let _ = fs::remove_file(dir.join(format!("{unit}.service")));
+        systemctl(&["daemon-reload"])
+    }
+
+    fn unit(identifier: &str) -> String {
+        format!("{identifier}.next-alarm")
+    }
+
+    fn unit_dir() -> Result<PathBuf, String> {
+        let config = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => PathBuf::from(config),
+            None => {
+                PathBuf::from(std::env::var_os("HOME").ok_or("HOME is not set")?).join(".config")
+            }
+        };
+        Ok(config.join("systemd/user"))
+    }
+
+    fn systemctl(args: &[&str]) -> Result<(), String> {
+        let output = Command::new("systemctl")
+            .arg("--user")
+            .args(args)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
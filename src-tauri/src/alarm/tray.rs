@@ -0,0 +1,98 @@
+use chrono::DateTime;
+use tauri::{
+    AppHandle, Manager, Wry,
+    menu::{MenuItemBuilder, Submenu, SubmenuBuilder},
+};
+
+use crate::zone::active::active_tz;
+
+use super::{Alarm, AlarmStore, skip, snooze};
+
+/// How many upcoming alarms the tray lists.
+const TRAY_ALARM_COUNT: usize = 3;
+const MENU_ID_ALARM_PREFIX: &str = "tray-alarm:";
+
+/// When the alarm rings next, counting a pending snooze.
+pub(super) fn next_ring(alarm: &Alarm) -> Option<i64> {
+    match (
+        alarm.snoozed_until_epoch_millis,
+        alarm.next_fire_epoch_millis,
+    ) {
+        (Some(snoozed), Some(next)) => Some(snoozed.min(next)),
+        (snoozed, next) => snoozed.or(next),
+    }
+}
+
+/// The next `count` alarms to ring, soonest first, each with the day and
+/// time it rings on the main clock, such as "Wed 07:00".
+pub fn upcoming(app: &AppHandle, count: usize) -> Vec<(String, Alarm)> {
+    let zone = active_tz(app).ok();
+    let mut alarms: Vec<(i64, Alarm)> = app
+        .state::<AlarmStore>()
+        .list()
+        .into_iter()
+        .filter_map(|alarm| Some((next_ring(&alarm)?, alarm)))
+        .collect();
+    alarms.sort_by_key(|(at, _)| *at);
+    alarms
+        .into_iter()
+        .take(count)
+        .map(|(at, alarm)| {
+            let when = zone
+                .zip(DateTime::from_timestamp_millis(at))
+                .map(|(zone, at)| at.with_timezone(&zone).format("%a %H:%M").to_string())
+                .unwrap_or_else(|| alarm.spec.time.clone());
+            (when, alarm)
+        })
+        .collect()
+}
+
+/// A submenu for each of the next alarms, titled with its day, time, and
+/// label, holding an entry that dismisses a snooze or skips the next
+/// occurrence.
+pub fn tray_submenus(app: &AppHandle) -> tauri::Result<Vec<Submenu<Wry>>> {
+    upcoming(app, TRAY_ALARM_COUNT)
+        .into_iter()
+        .map(|(when, alarm)| {
+            let title = if alarm.spec.label.is_empty() {
+                format!("Alarm {when}")
+            } else {
+                format!("Alarm {when} · {}", alarm.spec.label)
+            };
+            let action = if alarm.snoozed_until_epoch_millis.is_some() {
+                "Dismiss snooze"
+            } else if alarm.spec.recurrence.is_once() {
+                "Turn off"
+            } else {
+                "Skip next"
+            };
+            let item =
+                MenuItemBuilder::with_id(format!("{MENU_ID_ALARM_PREFIX}{}", alarm.id), action)
+                    .build(app)?;
+            SubmenuBuilder::new(app, title).item(&item).build()
+        })
+        .collect()
+}
+
+/// Handles clicks on the entries of `tray_submenus`; other ids are ignored.
+pub fn handle_menu_event(app: &AppHandle, id: &str) {
+    let Some(id) = id
+        .strip_prefix(MENU_ID_ALARM_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        return;
+    };
+    let snoozed = app
+        .state::<AlarmStore>()
+        .list()
+        .iter()
+        .any(|alarm| alarm.id == id && alarm.snoozed_until_epoch_millis.is_some());
+    let result = if snoozed {
+        snooze::dismiss(app, id)
+    } else {
+        skip(app, id)
+    };
+    if let Err(err) = result {
+        eprintln!("failed to update alarm {id} from the tray: {err}");
+    }
+}
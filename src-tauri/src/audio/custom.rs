@@ -0,0 +1,106 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use rodio::{Decoder, Source};
+use tauri::{AppHandle, State};
+
+use crate::store;
+
+use super::{AudioError, AudioPlayer, BoxedSource, Playback};
+
+const SOUNDS_DIR_NAME: &str = "sounds";
+/// Ids of imported sounds are this prefix and the file name in the cache.
+pub const CUSTOM_PREFIX: &str = "file:";
+const SUPPORTED_EXTENSIONS: [&str; 3] = ["mp3", "ogg", "wav"];
+/// Alarm sounds are short; anything bigger is most likely the wrong file.
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Where imported sounds are copied, so alarms keep working when the
+/// original file moves.
+pub fn sounds_dir(app: &AppHandle) -> Option<PathBuf> {
+    store::data_file(app, SOUNDS_DIR_NAME)
+}
+
+/// Ids of the imported sounds, by file name.
+pub fn list(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| has_supported_extension(Path::new(name)))
+        .map(|name| format!("{CUSTOM_PREFIX}{name}"))
+        .collect();
+    names.sort();
+    names
+}
+
+/// The imported sound `name` (an id without its prefix) from `dir`.
+pub fn source(dir: &Path, name: &str) -> Result<BoxedSource, AudioError> {
+    // Ids come from the frontend; keep them inside the cache directory.
+    if Path::new(name).file_name() != Some(OsStr::new(name)) {
+        return Err(AudioError::UnknownSound(format!("{CUSTOM_PREFIX}{name}")));
+    }
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Err(AudioError::UnknownSound(format!("{CUSTOM_PREFIX}{name}")));
+    }
+    decode(&path)
+}
+
+/// Checks that `path` is a supported audio file of reasonable size that
+/// decodes, and returns it ready to play.
+fn decode(path: &Path) -> Result<BoxedSource, AudioError> {
+    if !has_supported_extension(path) {
+        return Err(AudioError::UnsupportedFormat(path.display().to_string()));
+    }
+    let file = File::open(path)?;
+    if file.metadata()?.len() > MAX_FILE_BYTES {
+        return Err(AudioError::TooLarge(MAX_FILE_BYTES));
+    }
+    let decoder = Decoder::new(BufReader::new(file))
+        .map_err(|err| AudioError::Decode(format!("{}: {err}", path.display())))?;
+    Ok(Box::new(decoder.convert_samples::<f32>()))
+}
+
+fn has_supported_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Validates the audio file at `path` and copies it into the sound cache.
+/// Returns the id to use as an alarm's `sound`; importing a file with the
+/// same name again replaces the cached copy.
+#[tauri::command]
+pub fn import_sound(app: AppHandle, path: PathBuf) -> Result<String, AudioError> {
+    decode(&path)?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| AudioError::UnsupportedFormat(path.display().to_string()))?;
+    let dir = sounds_dir(&app).ok_or(AudioError::Unavailable)?;
+    fs::create_dir_all(&dir)?;
+    fs::copy(&path, dir.join(name))?;
+    Ok(format!("{CUSTOM_PREFIX}{name}"))
+}
+
+/// Plays the audio file at `path` once without importing it, for trying
+/// files out in the settings. Plays even while muted.
+#[tauri::command]
+pub fn preview_sound(
+    player: State<'_, AudioPlayer>,
+    path: PathBuf,
+    volume: Option<f32>,
+) -> Result<(), AudioError> {
+    let source = decode(&path)?;
+    player.play_source(source, &Playback::once(volume.unwrap_or(1.0)))
+}
@@ -0,0 +1,287 @@
+pub mod custom;
+pub mod tones;
+
+use std::{
+    io,
+    path::PathBuf,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Manager, State};
+use thiserror::Error;
+
+use crate::{settings::SettingsStore, store::StoreError};
+
+/// A looping sound stops by itself after this long, so an alarm nobody
+/// dismisses does not ring forever.
+const MAX_LOOP_DURATION: Duration = Duration::from_secs(10 * 60);
+
+pub type BoxedSource = Box<dyn Source<Item = f32> + Send>;
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("unknown sound: {0}")]
+    UnknownSound(String),
+    #[error("unsupported sound file, expected mp3, ogg, or wav: {0}")]
+    UnsupportedFormat(String),
+    #[error("sound file is larger than {0} bytes")]
+    TooLarge(u64),
+    #[error("failed to decode sound file {0}")]
+    Decode(String),
+    #[error("failed to read sound file: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("no audio output available: {0}")]
+    Device(String),
+    #[error("audio thread is not running")]
+    Unavailable,
+}
+
+impl AudioError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownSound(_) => "unknown_sound",
+            Self::UnsupportedFormat(_) => "unsupported_sound_format",
+            Self::TooLarge(_) => "sound_too_large",
+            Self::Decode(_) => "invalid_sound_file",
+            Self::Io(_) => "io",
+            Self::Store(err) => err.code(),
+            Self::Device(_) => "audio_device",
+            Self::Unavailable => "audio_unavailable",
+        }
+    }
+}
+
+impl Serialize for AudioError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AudioError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// How a sound is played.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    /// Loop until stopped, for at most `MAX_LOOP_DURATION`.
+    pub repeat: bool,
+    /// From 0 to 1.
+    pub volume: f32,
+    /// Time over which the volume rises from silence to `volume`, for a
+    /// gentle wake; zero starts at full volume.
+    pub ramp: Duration,
+}
+
+impl Playback {
+    /// A single pass at `volume`.
+    pub fn once(volume: f32) -> Self {
+        Self {
+            repeat: false,
+            volume,
+            ramp: Duration::ZERO,
+        }
+    }
+}
+
+enum Command {
+    Play {
+        source: BoxedSource,
+        reply: Sender<Result<(), AudioError>>,
+    },
+    Stop,
+}
+
+/// Plays sounds from the backend, so alarms are heard while the window is
+/// hidden and the webview throttled. rodio's output stream cannot leave the
+/// thread that opened it, so a dedicated thread owns it and takes commands
+/// over a channel. Only one sound plays at a time.
+pub struct AudioPlayer {
+    commands: Mutex<Sender<Command>>,
+    sounds_dir: Option<PathBuf>,
+    muted: AtomicBool,
+}
+
+impl AudioPlayer {
+    pub fn start(app: &AppHandle) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        if let Err(err) = thread::Builder::new()
+            .name("audio".into())
+            .spawn(move || run(receiver))
+        {
+            eprintln!("failed to start audio thread: {err}");
+        }
+        Self {
+            commands: Mutex::new(commands),
+            sounds_dir: custom::sounds_dir(app),
+            muted: AtomicBool::new(app.state::<SettingsStore>().get().audio.muted),
+        }
+    }
+
+    /// A built-in tone or, for ids starting with `file:`, an imported sound.
+    pub fn source(&self, id: &str) -> Result<BoxedSource, AudioError> {
+        match id.strip_prefix(custom::CUSTOM_PREFIX) {
+            Some(name) => {
+                let dir = self.sounds_dir.as_deref().ok_or(StoreError::NoDirectory)?;
+                custom::source(dir, name)
+            }
+            None => tones::source(id).ok_or_else(|| AudioError::UnknownSound(id.to_string())),
+        }
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    /// Plays the sound `id`, replacing whatever is playing. Does nothing
+    /// while muted.
+    pub fn play(&self, id: &str, playback: &Playback) -> Result<(), AudioError> {
+        let source = self.source(id)?;
+        if self.is_muted() {
+            return Ok(());
+        }
+        self.play_source(source, playback)
+    }
+
+    /// Plays `source` whether muted or not.
+    pub fn play_source(&self, source: BoxedSource, playback: &Playback) -> Result<(), AudioError> {
+        let source: BoxedSource = Box::new(source.amplify(playback.volume.clamp(0.0, 1.0)));
+        let source: BoxedSource = if playback.repeat {
+            Box::new(source.repeat_infinite().take_duration(MAX_LOOP_DURATION))
+        } else {
+            source
+        };
+        // Ramped after looping, so the rise spans the repetitions rather
+        // than restarting with each.
+        let source: BoxedSource = if playback.ramp.is_zero() {
+            source
+        } else {
+            Box::new(source.fade_in(playback.ramp))
+        };
+        let (reply, result) = mpsc::channel();
+        self.send(Command::Play { source, reply })?;
+        result.recv().map_err(|_| AudioError::Unavailable)?
+    }
+
+    pub fn stop(&self) {
+        if let Err(err) = self.send(Command::Stop) {
+            eprintln!("failed to stop sound: {err}");
+        }
+    }
+
+    fn send(&self, command: Command) -> Result<(), AudioError> {
+        self.commands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send(command)
+            .map_err(|_| AudioError::Unavailable)
+    }
+}
+
+/// Owns the output stream, opening it on first use and again after a
+/// failure, so a headset plugged in later still gets sound.
+fn run(commands: Receiver<Command>) {
+    let mut output: Option<(OutputStream, OutputStreamHandle)> = None;
+    let mut sink: Option<Sink> = None;
+    for command in commands {
+        match command {
+            Command::Play { source, reply } => {
+                if let Some(previous) = sink.take() {
+                    previous.stop();
+                }
+                let result = open(&mut output).and_then(|handle| {
+                    Sink::try_new(handle).map_err(|err| AudioError::Device(err.to_string()))
+                });
+                let result = match result {
+                    Ok(new_sink) => {
+                        new_sink.append(source);
+                        sink = Some(new_sink);
+                        Ok(())
+                    }
+                    Err(err) => {
+                        output = None;
+                        Err(err)
+                    }
+                };
+                // The caller may have given up waiting; nothing to do then.
+                let _ = reply.send(result);
+            }
+            Command::Stop => {
+                if let Some(previous) = sink.take() {
+                    previous.stop();
+                }
+            }
+        }
+    }
+}
+
+fn open(
+    output: &mut Option<(OutputStream, OutputStreamHandle)>,
+) -> Result<&OutputStreamHandle, AudioError> {
+    if output.is_none() {
+        *output =
+            Some(OutputStream::try_default().map_err(|err| AudioError::Device(err.to_string()))?);
+    }
+    output
+        .as_ref()
+        .map(|(_, handle)| handle)
+        .ok_or(AudioError::Unavailable)
+}
+
+/// Ids of the built-in tones followed by the imported sounds, for a sound
+/// picker.
+#[tauri::command]
+pub fn list_sounds(player: State<'_, AudioPlayer>) -> Vec<String> {
+    let custom = player.sounds_dir.as_deref().map(custom::list);
+    tones::TONES
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .chain(custom.into_iter().flatten())
+        .collect()
+}
+
+/// Plays the sound `id` once, or looped when `repeat` is set.
+#[tauri::command]
+pub fn play_sound(
+    player: State<'_, AudioPlayer>,
+    id: String,
+    repeat: Option<bool>,
+    volume: Option<f32>,
+) -> Result<(), AudioError> {
+    let playback = Playback {
+        repeat: repeat.unwrap_or(false),
+        ..Playback::once(volume.unwrap_or(1.0))
+    };
+    player.play(&id, &playback)
+}
+
+/// Silences or restores all alarm and timer sounds, stopping the one that
+/// is playing, and remembers the choice.
+#[tauri::command]
+pub fn set_muted(
+    app: AppHandle,
+    player: State<'_, AudioPlayer>,
+    muted: bool,
+) -> Result<(), AudioError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.audio.muted = muted)?;
+    player.muted.store(muted, Ordering::Relaxed);
+    if muted {
+        player.stop();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_sound(player: State<'_, AudioPlayer>) {
+    player.stop();
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use rodio::{
+    Source,
+    source::{SineWave, Zero},
+};
+
+use super::BoxedSource;
+
+const SAMPLE_RATE: u32 = 48_000;
+/// Pure sine waves at full scale are piercing; this keeps them at a level
+/// comparable to typical alarm recordings.
+const TONE_AMPLITUDE: f32 = 0.3;
+
+/// Built-in tones as (frequency in Hz, milliseconds) steps; a frequency of
+/// 0 is silence. Each pattern loops while an alarm rings.
+#[rustfmt::skip]
+pub const TONES: &[(&str, &[(f32, u64)])] = &[
+    ("beep", &[(880.0, 200), (0.0, 200), (880.0, 200), (0.0, 600)]),
+    ("chime", &[(659.3, 300), (830.6, 300), (987.8, 600), (0.0, 800)]),
+    ("digital", &[
+        (1000.0, 80), (0.0, 80), (1000.0, 80), (0.0, 80),
+        (1000.0, 80), (0.0, 80), (1000.0, 80), (0.0, 500),
+    ]),
+    ("pulse", &[(440.0, 500), (0.0, 500)]),
+];
+
+pub const DEFAULT_TONE: &str = "beep";
+
+/// One pass of the tone named `id`, or `None` if there is no such tone.
+pub fn source(id: &str) -> Option<BoxedSource> {
+    let (_, steps) = TONES.iter().find(|(name, _)| *name == id)?;
+    let parts: Vec<BoxedSource> = steps
+        .iter()
+        .map(|&(frequency, millis)| {
+            let length = Duration::from_millis(millis);
+            if frequency > 0.0 {
+                Box::new(
+                    SineWave::new(frequency)
+                        .amplify(TONE_AMPLITUDE)
+                        .take_duration(length),
+                ) as BoxedSource
+            } else {
+                Box::new(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(length))
+            }
+        })
+        .collect();
+    Some(Box::new(rodio::source::from_iter(parts)))
+}
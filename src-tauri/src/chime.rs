@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveTime, Timelike};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Manager, State};
+use thiserror::Error;
+
+use crate::{
+    audio::{AudioError, AudioPlayer, Playback},
+    dnd,
+    settings::{ChimeSettings, QuietHours, SettingsStore},
+    store::StoreError,
+    sync::state::TimeState,
+    zone::{active::active_tz, datemath},
+};
+
+/// How often the corrected clock is checked for a new hour or half hour.
+const CHECK_PERIOD: Duration = Duration::from_secs(1);
+/// A chime found later than this, such as right after the machine wakes
+/// up, is skipped rather than played out of time.
+const MAX_LATE_MILLIS: i64 = 10_000;
+
+#[derive(Debug, Error)]
+pub enum ChimeError {
+    #[error("invalid quiet hours, expected HH:MM: {0}")]
+    InvalidQuietHours(String),
+    #[error(transparent)]
+    Sound(#[from] AudioError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl ChimeError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidQuietHours(_) => "invalid_quiet_hours",
+            Self::Sound(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for ChimeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ChimeError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[tauri::command]
+pub fn get_chime_settings(app: AppHandle) -> ChimeSettings {
+    app.state::<SettingsStore>().get().chime
+}
+
+#[tauri::command]
+pub fn set_chime_settings(
+    app: AppHandle,
+    player: State<'_, AudioPlayer>,
+    settings: ChimeSettings,
+) -> Result<ChimeSettings, ChimeError> {
+    player.source(&settings.sound)?;
+    if let Some(quiet_hours) = &settings.quiet_hours {
+        parse_quiet_hours(quiet_hours)?;
+    }
+    let updated = app
+        .state::<SettingsStore>()
+        .update(|current| current.chime = settings)?;
+    Ok(updated.chime)
+}
+
+/// Plays the chime on the hour, and on the half hour if enabled, of the
+/// main clock's zone, timed against the corrected clock.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_chime = None;
+        loop {
+            check(&app, &mut last_chime);
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+/// Chimes if the latest hour or half hour has just passed and has not
+/// chimed yet. `last_chime` holds the instant of the last one.
+fn check(app: &AppHandle, last_chime: &mut Option<i64>) {
+    let chime = app.state::<SettingsStore>().get().chime;
+    if !chime.enabled {
+        return;
+    }
+    let zone = match active_tz(app) {
+        Ok(zone) => zone,
+        Err(err) => {
+            eprintln!("failed to resolve chime zone: {err}");
+            return;
+        }
+    };
+    let now = app.state::<TimeState>().schedule_now_millis();
+    let Some((slot, time)) = latest_slot(zone, now, chime.half_hour) else {
+        return;
+    };
+    if now - slot > MAX_LATE_MILLIS {
+        return;
+    }
+    if *last_chime == Some(slot) {
+        return;
+    }
+    *last_chime = Some(slot);
+    if chime
+        .quiet_hours
+        .as_ref()
+        .is_some_and(|quiet_hours| is_quiet(quiet_hours, time))
+        || dnd::suppresses(app)
+    {
+        return;
+    }
+    if let Err(err) = app
+        .state::<AudioPlayer>()
+        .play(&chime.sound, &Playback::once(chime.volume))
+    {
+        eprintln!("failed to play chime: {err}");
+    }
+}
+
+/// The latest hour (or half hour) at or before `now_millis` on the wall
+/// clock of `zone`, as an instant and as the local time it names.
+fn latest_slot(zone: Tz, now_millis: i64, half_hour: bool) -> Option<(i64, NaiveTime)> {
+    let local = DateTime::from_timestamp_millis(now_millis)?.with_timezone(&zone);
+    let minute = if half_hour && local.minute() >= 30 {
+        30
+    } else {
+        0
+    };
+    let time = NaiveTime::from_hms_opt(local.hour(), minute, 0)?;
+    let slot = datemath::resolve(zone, local.date_naive().and_time(time))?;
+    Some((slot.timestamp_millis(), time))
+}
+
+fn is_quiet(quiet_hours: &QuietHours, time: NaiveTime) -> bool {
+    let Ok((start, end)) = parse_quiet_hours(quiet_hours) else {
+        return false;
+    };
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+fn parse_quiet_hours(quiet_hours: &QuietHours) -> Result<(NaiveTime, NaiveTime), ChimeError> {
+    let parse = |text: &str| {
+        NaiveTime::parse_from_str(text.trim(), "%H:%M")
+            .map_err(|_| ChimeError::InvalidQuietHours(text.to_string()))
+    };
+    Ok((parse(&quiet_hours.start)?, parse(&quiet_hours.end)?))
+}
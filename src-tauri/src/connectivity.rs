@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{net::SharedClient, settings::SettingsStore, sync};
+
+pub const EVENT_NETWORK_ONLINE: &str = "network-online";
+pub const EVENT_NETWORK_OFFLINE: &str = "network-offline";
+
+/// Polls `network.connectivity_check_url` and reports transitions between
+/// online and offline. Regaining connectivity triggers an immediate resync.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut online: Option<bool> = None;
+        loop {
+            let settings = app.state::<SettingsStore>().get().network;
+            let reachable = probe(
+                &app.state::<SharedClient>().get(),
+                &settings.connectivity_check_url,
+            )
+            .await;
+
+            if online != Some(reachable) {
+                let event = if reachable {
+                    EVENT_NETWORK_ONLINE
+                } else {
+                    EVENT_NETWORK_OFFLINE
+                };
+                if let Err(err) = app.emit(event, ()) {
+                    eprintln!("failed to emit {event}: {err}");
+                }
+                // The startup sync already covers the first observation.
+                if reachable && online.is_some() {
+                    sync::resync(&app).await;
+                }
+                online = Some(reachable);
+            }
+
+            tokio::time::sleep(Duration::from_secs(settings.connectivity_check_secs.max(1))).await;
+        }
+    });
+}
+
+/// A captive portal answers the check URL with a redirect or its own page,
+/// so only the expected `204 No Content` counts as being online.
+async fn probe(client: &reqwest::Client, url: &str) -> bool {
+    match client.get(url).send().await {
+        Ok(response) => response.status() == reqwest::StatusCode::NO_CONTENT,
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,427 @@
+use std::{path::PathBuf, sync::Mutex, time::Duration};
+
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use thiserror::Error;
+
+use crate::{
+    dnd,
+    store::{self, StoreError},
+    sync::{TimeSyncError, state::TimeState},
+    zone::{active::active_tz, datemath, parse_zone},
+};
+
+pub const EVENT_COUNTDOWNS_CHANGED: &str = "countdowns-changed";
+pub const EVENT_COUNTDOWN_MILESTONE: &str = "countdown-milestone";
+
+const COUNTDOWNS_FILE_NAME: &str = "countdowns.json";
+/// How often milestones are checked against the corrected clock.
+const CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Error)]
+pub enum CountdownError {
+    #[error("invalid countdown target: {0}")]
+    InvalidTarget(String),
+    #[error("no countdown with id {0}")]
+    NotFound(u64),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl CountdownError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidTarget(_) => "invalid_countdown_target",
+            Self::NotFound(_) => "countdown_not_found",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for CountdownError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CountdownError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+/// How long before the target a notification is shown. Days and weeks
+/// count on the wall clock of the countdown's zone, so "1 day" before
+/// 09:00 is 09:00 the day before even across a DST change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub amount: u32,
+    pub unit: MilestoneUnit,
+}
+
+impl Milestone {
+    const fn new(amount: u32, unit: MilestoneUnit) -> Self {
+        Self { amount, unit }
+    }
+
+    /// The instant this milestone falls on for a target at `local` in
+    /// `zone`.
+    fn epoch_millis(&self, zone: Tz, local: NaiveDateTime) -> Option<i64> {
+        let amount = i64::from(self.amount);
+        let by_days = |days: u64| {
+            let date = local.date().checked_sub_days(Days::new(days))?;
+            datemath::resolve(zone, date.and_time(local.time()))
+        };
+        let at = match self.unit {
+            MilestoneUnit::Week => by_days(u64::from(self.amount) * 7)?,
+            MilestoneUnit::Day => by_days(u64::from(self.amount))?,
+            MilestoneUnit::Hour => datemath::resolve(zone, local)? - TimeDelta::hours(amount),
+            MilestoneUnit::Minute => datemath::resolve(zone, local)? - TimeDelta::minutes(amount),
+        };
+        Some(at.timestamp_millis())
+    }
+
+    /// "1 week", "3 days", "30 minutes".
+    fn describe(&self) -> String {
+        let unit = match self.unit {
+            MilestoneUnit::Minute => "minute",
+            MilestoneUnit::Hour => "hour",
+            MilestoneUnit::Day => "day",
+            MilestoneUnit::Week => "week",
+        };
+        let plural = if self.amount == 1 { "" } else { "s" };
+        format!("{} {unit}{plural}", self.amount)
+    }
+}
+
+fn default_milestones() -> Vec<Milestone> {
+    vec![
+        Milestone::new(1, MilestoneUnit::Week),
+        Milestone::new(1, MilestoneUnit::Day),
+        Milestone::new(1, MilestoneUnit::Hour),
+    ]
+}
+
+/// A countdown as the frontend defines it, such as "Launch" at 2025-06-01
+/// 09:00 in Asia/Tokyo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountdownSpec {
+    pub label: String,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    /// "HH:MM"; midnight when left out.
+    #[serde(default)]
+    pub time: Option<String>,
+    /// IANA zone the date and time are read in; `None` takes the main
+    /// clock's zone when the countdown is saved, so the target stays put.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default = "default_milestones")]
+    pub milestones: Vec<Milestone>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Countdown {
+    pub id: u64,
+    #[serde(flatten)]
+    pub spec: CountdownSpec,
+    pub target_epoch_millis: i64,
+    /// Milestones already notified, or passed when the countdown was set.
+    #[serde(default)]
+    pub notified: Vec<Milestone>,
+    /// Whether the target has been reached and announced.
+    #[serde(default)]
+    pub reached: bool,
+}
+
+/// Payload of the `countdown-milestone` event. `milestone` is `None` when
+/// the target itself is reached.
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownMilestone {
+    id: u64,
+    label: String,
+    milestone: Option<Milestone>,
+    target_epoch_millis: i64,
+}
+
+/// All countdowns, saved to `countdowns.json` in the app data directory
+/// whenever one is added, edited, removed, or passes a milestone.
+pub struct CountdownStore {
+    countdowns: Mutex<Vec<Countdown>>,
+    path: Option<PathBuf>,
+}
+
+impl CountdownStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, COUNTDOWNS_FILE_NAME);
+        let countdowns = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            countdowns: Mutex::new(countdowns),
+            path,
+        }
+    }
+
+    pub fn list(&self) -> Vec<Countdown> {
+        self.countdowns
+            .lock()
+            .map(|countdowns| countdowns.clone())
+            .unwrap_or_default()
+    }
+
+    /// Applies `change` and saves the result. When `change` or the save
+    /// fails, the list is left untouched.
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Vec<Countdown>) -> Result<T, CountdownError>,
+    ) -> Result<T, CountdownError> {
+        store::update_locked(self.path.as_deref(), &self.countdowns, change)
+    }
+}
+
+/// All countdowns, soonest target first, including those already reached.
+#[tauri::command]
+pub fn list_countdowns(countdowns: State<'_, CountdownStore>) -> Vec<Countdown> {
+    let mut countdowns = countdowns.list();
+    countdowns.sort_by_key(|countdown| countdown.target_epoch_millis);
+    countdowns
+}
+
+/// Adds a countdown. Milestones that have already passed are not notified.
+#[tauri::command]
+pub fn create_countdown(
+    app: AppHandle,
+    countdown: CountdownSpec,
+) -> Result<Countdown, CountdownError> {
+    let created = app.state::<CountdownStore>().update(|countdowns| {
+        let id = countdowns
+            .iter()
+            .map(|countdown| countdown.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let created = prepare(&app, id, countdown)?;
+        countdowns.push(created.clone());
+        Ok(created)
+    })?;
+    publish(&app);
+    Ok(created)
+}
+
+/// Replaces a countdown's settings; milestones are tracked afresh against
+/// the new target.
+#[tauri::command]
+pub fn update_countdown(
+    app: AppHandle,
+    id: u64,
+    countdown: CountdownSpec,
+) -> Result<Countdown, CountdownError> {
+    let updated = app.state::<CountdownStore>().update(|countdowns| {
+        let existing = countdowns
+            .iter_mut()
+            .find(|existing| existing.id == id)
+            .ok_or(CountdownError::NotFound(id))?;
+        *existing = prepare(&app, id, countdown)?;
+        Ok(existing.clone())
+    })?;
+    publish(&app);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn delete_countdown(app: AppHandle, id: u64) -> Result<(), CountdownError> {
+    app.state::<CountdownStore>().update(|countdowns| {
+        let index = countdowns
+            .iter()
+            .position(|countdown| countdown.id == id)
+            .ok_or(CountdownError::NotFound(id))?;
+        countdowns.remove(index);
+        Ok(())
+    })?;
+    publish(&app);
+    Ok(())
+}
+
+/// Resolves the target of `spec` and marks the milestones already behind
+/// the corrected clock as notified.
+fn prepare(app: &AppHandle, id: u64, mut spec: CountdownSpec) -> Result<Countdown, CountdownError> {
+    let (zone, local) = target(app, &spec)?;
+    spec.time_zone = Some(zone.name().to_string());
+    let target_epoch_millis = datemath::resolve(zone, local)
+        .ok_or_else(|| CountdownError::InvalidTarget(spec.date.clone()))?
+        .timestamp_millis();
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    let notified = spec
+        .milestones
+        .iter()
+        .filter(|milestone| {
+            milestone
+                .epoch_millis(zone, local)
+                .is_none_or(|at| at <= now_millis)
+        })
+        .copied()
+        .collect();
+    Ok(Countdown {
+        id,
+        spec,
+        target_epoch_millis,
+        notified,
+        reached: target_epoch_millis <= now_millis,
+    })
+}
+
+/// Moves the targets of countdowns saved without a zone, which read their
+/// date and time on the main clock, into its zone after that zone changed.
+pub fn rezone(app: &AppHandle) -> Result<(), CountdownError> {
+    let follows_main_clock = |countdown: &Countdown| countdown.spec.time_zone.is_none();
+    let store = app.state::<CountdownStore>();
+    if !store.list().iter().any(follows_main_clock) {
+        return Ok(());
+    }
+
+    store.update(|countdowns| {
+        for countdown in countdowns
+            .iter_mut()
+            .filter(|countdown| follows_main_clock(countdown))
+        {
+            let (zone, local) = target(app, &countdown.spec)?;
+            countdown.target_epoch_millis = datemath::resolve(zone, local)
+                .ok_or_else(|| CountdownError::InvalidTarget(countdown.spec.date.clone()))?
+                .timestamp_millis();
+        }
+        Ok(())
+    })?;
+    publish(app);
+    Ok(())
+}
+
+/// The zone of `spec` and its target on that zone's wall clock.
+fn target(app: &AppHandle, spec: &CountdownSpec) -> Result<(Tz, NaiveDateTime), CountdownError> {
+    let zone = match spec.time_zone.as_deref() {
+        Some(name) => parse_zone(name)?,
+        None => active_tz(app)?,
+    };
+    let date = NaiveDate::parse_from_str(spec.date.trim(), "%Y-%m-%d")
+        .map_err(|_| CountdownError::InvalidTarget(spec.date.clone()))?;
+    let time = match spec.time.as_deref() {
+        Some(time) => NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| CountdownError::InvalidTarget(time.to_string()))?,
+        None => NaiveTime::MIN,
+    };
+    Ok((zone, date.and_time(time)))
+}
+
+fn publish(app: &AppHandle) {
+    let countdowns = app.state::<CountdownStore>().list();
+    if let Err(err) = app.emit(EVENT_COUNTDOWNS_CHANGED, countdowns) {
+        eprintln!("failed to emit {EVENT_COUNTDOWNS_CHANGED}: {err}");
+    }
+}
+
+/// Notifies milestones and targets as the corrected clock passes them.
+/// When several passed while the app was closed, only the latest is
+/// notified.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match pass_due(&app) {
+                Ok(passed) if !passed.is_empty() => {
+                    publish(&app);
+                    for milestone in passed {
+                        notify(&app, milestone);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("failed to update countdown milestones: {err}"),
+            }
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+fn pass_due(app: &AppHandle) -> Result<Vec<CountdownMilestone>, CountdownError> {
+    let store = app.state::<CountdownStore>();
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    // Milestones whose instant has come, latest first, with the target
+    // itself as `None`.
+    let due = |countdown: &Countdown| -> Vec<(i64, Option<Milestone>)> {
+        let Ok((zone, local)) = target(app, &countdown.spec) else {
+            return Vec::new();
+        };
+        let mut due: Vec<(i64, Option<Milestone>)> = countdown
+            .spec
+            .milestones
+            .iter()
+            .filter(|milestone| !countdown.notified.contains(milestone))
+            .filter_map(|milestone| Some((milestone.epoch_millis(zone, local)?, Some(*milestone))))
+            .chain((!countdown.reached).then_some((countdown.target_epoch_millis, None)))
+            .filter(|(at, _)| *at <= now_millis)
+            .collect();
+        due.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+        due
+    };
+    if store
+        .list()
+        .iter()
+        .all(|countdown| due(countdown).is_empty())
+    {
+        return Ok(Vec::new());
+    }
+    store.update(|countdowns| {
+        let mut passed = Vec::new();
+        for countdown in countdowns.iter_mut() {
+            let due = due(countdown);
+            let Some((_, latest)) = due.first() else {
+                continue;
+            };
+            for (_, milestone) in &due {
+                match milestone {
+                    Some(milestone) => countdown.notified.push(*milestone),
+                    None => countdown.reached = true,
+                }
+            }
+            passed.push(CountdownMilestone {
+                id: countdown.id,
+                label: countdown.spec.label.clone(),
+                milestone: *latest,
+                target_epoch_millis: countdown.target_epoch_millis,
+            });
+        }
+        Ok(passed)
+    })
+}
+
+fn notify(app: &AppHandle, passed: CountdownMilestone) {
+    let body = match &passed.milestone {
+        Some(milestone) => format!("{} in {}", passed.label, milestone.describe()),
+        None => format!("{} is here", passed.label),
+    };
+    if !dnd::suppresses(app)
+        && let Err(err) = app
+            .notification()
+            .builder()
+            .title("Countdown")
+            .body(&body)
+            .show()
+    {
+        eprintln!("failed to show countdown notification: {err}");
+    }
+    if let Err(err) = app.emit(EVENT_COUNTDOWN_MILESTONE, passed) {
+        eprintln!("failed to emit {EVENT_COUNTDOWN_MILESTONE}: {err}");
+    }
+}
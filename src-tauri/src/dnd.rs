@@ -0,0 +1,234 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{settings::SettingsStore, store::StoreError};
+
+pub const EVENT_DND_CHANGED: &str = "dnd-changed";
+
+/// None of the platforms announce changes in a way the app can subscribe
+/// to without extra system libraries, so the state is polled.
+const DND_POLL_PERIOD: Duration = Duration::from_secs(15);
+
+/// Payload of the `dnd-changed` event.
+#[derive(Clone, Serialize)]
+struct DndChanged {
+    active: bool,
+}
+
+/// Last observed Do Not Disturb state of the OS (Focus Assist on Windows,
+/// Focus on macOS, the notification server's DND on Linux desktops).
+///
+/// While it is on, and unless the user turned `dnd.respect` off, chimes and
+/// Pomodoro sounds are skipped and timers, Pomodoro phases, DST notices and
+/// clock warnings show no notification. Alarms stay silent too unless
+/// marked `critical`; every alarm still emits `alarm-fired`.
+#[derive(Debug, Default)]
+pub struct DndState {
+    active: AtomicBool,
+}
+
+impl DndState {
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether non-critical sounds and notifications are to be held back now.
+pub fn suppresses(app: &AppHandle) -> bool {
+    app.state::<SettingsStore>().get().dnd.respect && app.state::<DndState>().is_active()
+}
+
+#[tauri::command]
+pub fn get_dnd_state(dnd: State<'_, DndState>) -> bool {
+    dnd.is_active()
+}
+
+/// Chooses whether the app holds back chimes and notifications while the
+/// OS is in Do Not Disturb.
+#[tauri::command]
+pub fn set_respect_dnd(app: AppHandle, respect: bool) -> Result<(), StoreError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.dnd.respect = respect)?;
+    Ok(())
+}
+
+/// Polls the OS state into `DndState` and emits `dnd-changed` when it
+/// changes. A state that cannot be read counts as off.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let active = tauri::async_runtime::spawn_blocking(platform::is_active)
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or(false);
+            let previous = app
+                .state::<DndState>()
+                .active
+                .swap(active, Ordering::Relaxed);
+            if previous != active
+                && let Err(err) = app.emit(EVENT_DND_CHANGED, DndChanged { active })
+            {
+                eprintln!("failed to emit {EVENT_DND_CHANGED}: {err}");
+            }
+            tokio::time::sleep(DND_POLL_PERIOD).await;
+        }
+    });
+}
+
+/// `Some(true)` if any source reports DND on, `None` if none could be read.
+fn any(observations: [Option<bool>; 2]) -> Option<bool> {
+    observations.into_iter().flatten().reduce(|a, b| a || b)
+}
+
+/// Focus Assist keeps its profile in a WNF state that the shell publishes
+/// but no public API exposes; the notification state covers presentation
+/// mode and the quiet time after setup.
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+
+    use windows_sys::Win32::UI::Shell::{
+        QUERY_USER_NOTIFICATION_STATE, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+        SHQueryUserNotificationState,
+    };
+
+    /// WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED: 0 is off, 1 priority
+    /// only, 2 alarms only.
+    const FOCUS_ASSIST_STATE_NAME: u64 = 0x0D83_063E_A3BF_1C75;
+
+    #[link(name = "ntdll")]
+    unsafe extern "system" {
+        fn ZwQueryWnfStateData(
+            state_name: *const u64,
+            type_id: *const c_void,
+            explicit_scope: *const c_void,
+            change_stamp: *mut u32,
+            buffer: *mut c_void,
+            buffer_size: *mut u32,
+        ) -> i32;
+    }
+
+    pub fn is_active() -> Option<bool> {
+        super::any([
+            focus_assist_profile().map(|profile| profile != 0),
+            notification_state()
+                .map(|state| state == QUNS_QUIET_TIME || state == QUNS_PRESENTATION_MODE),
+        ])
+    }
+
+    fn focus_assist_profile() -> Option<u32> {
+        let mut change_stamp = 0u32;
+        let mut profile = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            ZwQueryWnfStateData(
+                &FOCUS_ASSIST_STATE_NAME,
+                std::ptr::null(),
+                std::ptr::null(),
+                &mut change_stamp,
+                (&raw mut profile).cast(),
+                &mut size,
+            )
+        };
+        (status >= 0).then_some(profile)
+    }
+
+    fn notification_state() -> Option<QUERY_USER_NOTIFICATION_STATE> {
+        let mut state = 0;
+        let status = unsafe { SHQueryUserNotificationState(&mut state) };
+        (status >= 0).then_some(state)
+    }
+}
+
+/// A manually enabled Focus is recorded as an assertion in the DND
+/// database; older releases keep a `doNotDisturb` preference instead.
+/// Focus turned on by a schedule alone is not detected.
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    const ASSERTIONS_PATH: &str = "Library/DoNotDisturb/DB/Assertions.json";
+
+    pub fn is_active() -> Option<bool> {
+        assertions().or_else(legacy_preference)
+    }
+
+    fn assertions() -> Option<bool> {
+        let path = PathBuf::from(std::env::var_os("HOME")?).join(ASSERTIONS_PATH);
+        let raw = fs::read_to_string(path).ok()?;
+        let document: serde_json::Value = serde_json::from_str(&raw).ok()?;
+        let entries = document.get("data")?.as_array()?;
+        Some(entries.iter().any(|entry| {
+            entry
+                .get("storeAssertionRecords")
+                .and_then(|records| records.as_array())
+                .is_some_and(|records| !records.is_empty())
+        }))
+    }
+
+    fn legacy_preference() -> Option<bool> {
+        let output = Command::new("defaults")
+            .args([
+                "-currentHost",
+                "read",
+                "com.apple.notificationcenterui",
+                "doNotDisturb",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim() == "1")
+    }
+}
+
+/// KDE and other notification servers expose an `Inhibited` property;
+/// GNOME turns off banners instead.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use std::process::Command;
+
+    pub fn is_active() -> Option<bool> {
+        super::any([notifications_inhibited(), gnome_banners_hidden()])
+    }
+
+    fn notifications_inhibited() -> Option<bool> {
+        let stdout = run(
+            "busctl",
+            &[
+                "--user",
+                "get-property",
+                "org.freedesktop.Notifications",
+                "/org/freedesktop/Notifications",
+                "org.freedesktop.Notifications",
+                "Inhibited",
+            ],
+        )?;
+        // Printed as "b true".
+        Some(stdout.split_whitespace().nth(1)? == "true")
+    }
+
+    fn gnome_banners_hidden() -> Option<bool> {
+        let stdout = run(
+            "gsettings",
+            &["get", "org.gnome.desktop.notifications", "show-banners"],
+        )?;
+        Some(stdout == "false")
+    }
+
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
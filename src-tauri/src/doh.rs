@@ -0,0 +1,105 @@
+use std::net::{IpAddr, SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::settings::DohServer;
+
+/// DNS record types requested from the resolver.
+const RECORD_A: u16 = 1;
+const RECORD_AAAA: u16 = 28;
+
+#[derive(Debug, Error)]
+pub enum DohError {
+    #[error("DNS-over-HTTPS request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("DNS-over-HTTPS returned no address for {0}")]
+    NoAddress(String),
+}
+
+/// Resolves hostnames through a public DNS-over-HTTPS JSON API so requests
+/// survive networks that hijack plain DNS.
+pub struct DohResolver {
+    /// Client used for the lookups themselves; the endpoints are addressed
+    /// by IP so it never needs DNS.
+    client: reqwest::Client,
+    endpoint: &'static str,
+}
+
+impl DohResolver {
+    pub fn new(client: reqwest::Client, server: DohServer) -> Self {
+        let endpoint = match server {
+            DohServer::Cloudflare => "https://1.1.1.1/dns-query",
+            DohServer::Google => "https://8.8.8.8/resolve",
+        };
+        Self { client, endpoint }
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let client = self.client.clone();
+        let endpoint = self.endpoint;
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let (v4, v6) = futures::join!(
+                lookup(&client, endpoint, &host, RECORD_A),
+                lookup(&client, endpoint, &host, RECORD_AAAA)
+            );
+            // Either family is enough; only fail when both came up empty.
+            let mut addresses = v4.unwrap_or_default();
+            addresses.extend(v6.unwrap_or_default());
+            if addresses.is_empty() {
+                return Err(DohError::NoAddress(host).into());
+            }
+
+            // Port 0 is replaced with the URL's port by reqwest.
+            let addrs: Addrs = Box::new(
+                addresses
+                    .into_iter()
+                    .map(|address| SocketAddr::new(address, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+async fn lookup(
+    client: &reqwest::Client,
+    endpoint: &str,
+    host: &str,
+    record_type: u16,
+) -> Result<Vec<IpAddr>, DohError> {
+    let response: DohResponse = client
+        .get(endpoint)
+        .query(&[("name", host), ("type", &record_type.to_string())])
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // CNAME answers are followed by the resolver and listed alongside the
+    // final records, so keeping only the requested type is enough.
+    Ok(response
+        .answer
+        .into_iter()
+        .filter(|answer| answer.record_type == record_type)
+        .filter_map(|answer| answer.data.parse().ok())
+        .collect())
+}
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use icu::{
+    datetime::{NoCalendarFormatter, fieldsets::T, options::TimePrecision},
+    locale::Locale,
+    time::Time,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub const EVENT_HOUR_CYCLE_CHANGED: &str = "hour-cycle-changed";
+
+/// OS settings offer no change notification the app can use without extra
+/// system libraries, so they are polled.
+const HOUR_CYCLE_POLL_PERIOD: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HourCycle {
+    /// 1–12 with AM/PM.
+    H12,
+    /// 0–23.
+    H23,
+}
+
+/// Payload of the `hour-cycle-changed` event.
+#[derive(Clone, Serialize)]
+struct HourCycleChanged {
+    hour_cycle: HourCycle,
+}
+
+/// Whether the OS shows times on a 12- or 24-hour clock.
+#[tauri::command]
+pub fn get_hour_cycle() -> HourCycle {
+    current()
+}
+
+/// Polls the OS preference and emits `hour-cycle-changed` when it changes.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Reading the preference may run a subprocess, so it stays off the
+        // async workers.
+        let mut previous = tauri::async_runtime::spawn_blocking(current)
+            .await
+            .unwrap_or(HourCycle::H23);
+        loop {
+            tokio::time::sleep(HOUR_CYCLE_POLL_PERIOD).await;
+            let Ok(observed) = tauri::async_runtime::spawn_blocking(current).await else {
+                continue;
+            };
+            if observed == previous {
+                continue;
+            }
+            previous = observed;
+            let payload = HourCycleChanged {
+                hour_cycle: observed,
+            };
+            if let Err(err) = app.emit(EVENT_HOUR_CYCLE_CHANGED, payload) {
+                eprintln!("failed to emit {EVENT_HOUR_CYCLE_CHANGED}: {err}");
+            }
+        }
+    });
+}
+
+/// The explicit OS setting if there is one, else the convention of the
+/// locale used for times.
+pub fn current() -> HourCycle {
+    platform::hour_cycle()
+        .or_else(|| platform::time_locale().and_then(|locale| of_locale(&locale)))
+        .unwrap_or(HourCycle::H23)
+}
+
+/// Formats 13:00 in `locale` and checks whether it reads "13".
+fn of_locale(locale: &Locale) -> Option<HourCycle> {
+    let formatter = NoCalendarFormatter::try_new(
+        locale.into(),
+        T::medium().with_time_precision(TimePrecision::Hour),
+    )
+    .ok()?;
+    let afternoon = Time::try_new(13, 0, 0, 0).ok()?;
+    let rendered = formatter.format(&afternoon).to_string();
+    Some(if rendered.contains("13") {
+        HourCycle::H23
+    } else {
+        HourCycle::H12
+    })
+}
+
+/// Windows stores the short time pattern in the registry; `H` means a
+/// 24-hour clock and `h` a 12-hour one.
+#[cfg(windows)]
+mod platform {
+    use icu::locale::Locale;
+    use windows_sys::Win32::System::Registry::{HKEY_CURRENT_USER, RRF_RT_REG_SZ, RegGetValueW};
+
+    use super::HourCycle;
+
+    pub fn hour_cycle() -> Option<HourCycle> {
+        let key: Vec<u16> = "Control Panel\\International\0".encode_utf16().collect();
+        let value: Vec<u16> = "sTimeFormat\0".encode_utf16().collect();
+        let mut buffer = [0u16; 80];
+        let mut size = std::mem::size_of_val(&buffer) as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        if status != 0 {
+            return None;
+        }
+        let len = (size as usize / 2).saturating_sub(1).min(buffer.len());
+        let pattern = String::from_utf16_lossy(&buffer[..len]);
+        Some(if pattern.contains('H') {
+            HourCycle::H23
+        } else {
+            HourCycle::H12
+        })
+    }
+
+    pub fn time_locale() -> Option<Locale> {
+        crate::locale::system_locale()
+    }
+}
+
+/// macOS keeps the "24-hour time" switch as a global default that is only
+/// present once the user has changed it.
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+
+    use icu::locale::Locale;
+
+    use super::HourCycle;
+
+    pub fn hour_cycle() -> Option<HourCycle> {
+        let read = |key: &str| {
+            Command::new("defaults")
+                .args(["read", "-g", key])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .is_some_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+        };
+        if read("AppleICUForce24HourTime") {
+            Some(HourCycle::H23)
+        } else if read("AppleICUForce12HourTime") {
+            Some(HourCycle::H12)
+        } else {
+            None
+        }
+    }
+
+    pub fn time_locale() -> Option<Locale> {
+        crate::locale::system_locale()
+    }
+}
+
+/// Linux has no separate switch; `LC_TIME` decides, as it does for `date`.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use icu::locale::Locale;
+
+    use super::HourCycle;
+
+    pub fn hour_cycle() -> Option<HourCycle> {
+        None
+    }
+
+    pub fn time_locale() -> Option<Locale> {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .filter(|value| value != "C" && value != "POSIX")
+            .and_then(|value| crate::locale::parse_posix(&value))
+            .or_else(crate::locale::system_locale)
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::zone::{datemath, parse_zone};
+
+/// A BEGIN/END block with its properties and nested blocks.
+#[derive(Debug, Default)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub children: Vec<Component>,
+}
+
+impl Component {
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+}
+
+/// One content line, `NAME;PARAM=VALUE:value`.
+#[derive(Debug)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// A DATE or DATE-TIME value as an instant. UTC times end in `Z`, a
+    /// TZID names the zone, and floating times and all-day dates are read
+    /// in `floating`, with dates starting at midnight.
+    pub fn date_time(&self, floating: Tz) -> Result<DateTime<Tz>, String> {
+        let invalid = || format!("invalid {}: {}", self.name, self.value);
+        let value = self.value.trim();
+        let (value, zone) = match value.strip_suffix('Z') {
+            Some(utc) => (utc, Tz::UTC),
+            None => match self.param("TZID") {
+                Some(tzid) => (
+                    value,
+                    parse_zone(tzid.trim_start_matches('/'))
+                        .map_err(|_| format!("unknown TZID: {tzid}"))?,
+                ),
+                None => (value, floating),
+            },
+        };
+        let local = if value.contains('T') {
+            NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?
+        } else {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map_err(|_| invalid())?
+                .and_time(NaiveTime::MIN)
+        };
+        datemath::resolve(zone, local).ok_or_else(invalid)
+    }
+}
+
+/// Splits an iCalendar (RFC 5545) file into its top-level components after
+/// unfolding long lines. Properties outside any component are dropped.
+pub fn parse(text: &str) -> Result<Vec<Component>, String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(continued) if !lines.is_empty() => {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(continued);
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut roots = Vec::new();
+    let mut open: Vec<Component> = Vec::new();
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let property = parse_property(line).ok_or_else(|| format!("malformed line: {line}"))?;
+        match property.name.as_str() {
+            "BEGIN" => open.push(Component {
+                name: property.value.trim().to_ascii_uppercase(),
+                ..Default::default()
+            }),
+            "END" => {
+                let component = open
+                    .pop()
+                    .filter(|component| component.name.eq_ignore_ascii_case(property.value.trim()))
+                    .ok_or_else(|| format!("unexpected END:{}", property.value))?;
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = open.last_mut() {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+    if let Some(unclosed) = open.last() {
+        return Err(format!("{} is never closed", unclosed.name));
+    }
+    Ok(roots)
+}
+
+/// The VEVENTs of parsed components, whether inside a VCALENDAR or not.
+pub fn events(components: &[Component]) -> impl Iterator<Item = &Component> {
+    components
+        .iter()
+        .flat_map(|component| match component.name.as_str() {
+            "VCALENDAR" => component.children.iter().collect(),
+            _ => vec![component],
+        })
+        .filter(|component| component.name == "VEVENT")
+}
+
+/// Parses `NAME;PARAM=VALUE;PARAM="QUOTED":value`. A colon inside a quoted
+/// parameter value does not end the parameters.
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let split = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..split], &line[split + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.trim().to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+/// Parses a DURATION value such as `-PT15M`, `P1D`, or `P1W`.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (negative, text) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let text = text.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    let mut any = false;
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() && !in_time => in_time = true,
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(amount),
+                    ('D', false) => Duration::days(amount),
+                    ('H', true) => Duration::hours(amount),
+                    ('M', true) => Duration::minutes(amount),
+                    ('S', true) => Duration::seconds(amount),
+                    _ => return None,
+                };
+                any = true;
+            }
+        }
+    }
+    if !any || !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Undoes TEXT escaping, putting escaped line breaks on one line for a
+/// label.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push(' '),
+            Some(escaped) => unescaped.push(escaped),
+            None => {}
+        }
+    }
+    unescaped.trim().to_string()
+}
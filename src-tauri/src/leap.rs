@@ -0,0 +1,182 @@
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::sync::MILLIS_PER_SECOND;
+
+/// UTC dates on which TAI − UTC changed, with the new value in seconds.
+/// Source: IERS Bulletin C; append new entries as they are announced.
+const LEAP_SECONDS: [(i32, u32, i64); 28] = [
+    (1972, 1, 10),
+    (1972, 7, 11),
+    (1973, 1, 12),
+    (1974, 1, 13),
+    (1975, 1, 14),
+    (1976, 1, 15),
+    (1977, 1, 16),
+    (1978, 1, 17),
+    (1979, 1, 18),
+    (1980, 1, 19),
+    (1981, 7, 20),
+    (1982, 7, 21),
+    (1983, 7, 22),
+    (1985, 7, 23),
+    (1988, 1, 24),
+    (1990, 1, 25),
+    (1991, 1, 26),
+    (1992, 7, 27),
+    (1993, 7, 28),
+    (1994, 7, 29),
+    (1996, 1, 30),
+    (1997, 7, 31),
+    (1999, 1, 32),
+    (2006, 1, 33),
+    (2009, 1, 34),
+    (2012, 7, 35),
+    (2015, 7, 36),
+    (2017, 1, 37),
+];
+
+/// Last day the table is known to be complete, per the latest Bulletin C.
+const TABLE_VALID_UNTIL: (i32, u32, u32) = (2026, 12, 28);
+
+/// TAI − GPS, fixed since the GPS epoch.
+const GPS_TAI_OFFSET_SECONDS: i64 = 19;
+/// 1980-01-06T00:00:00Z, the GPS epoch, in Unix milliseconds.
+const GPS_EPOCH_UNIX_MILLIS: i64 = 315_964_800_000;
+const MILLIS_PER_WEEK: i64 = 7 * 24 * 60 * 60 * MILLIS_PER_SECOND;
+
+/// Time scales `convert_time_scale` understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeScale {
+    /// Unix milliseconds.
+    Utc,
+    /// Milliseconds since 1970-01-01T00:00:00 TAI.
+    Tai,
+    /// Milliseconds since the GPS epoch, 1980-01-06T00:00:00 UTC.
+    Gps,
+}
+
+/// A leap second table entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeapSecond {
+    /// First UTC instant at which `tai_minus_utc_seconds` applies.
+    pub effective_unix_millis: i64,
+    pub tai_minus_utc_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeapSecondInfo {
+    pub tai_minus_utc_seconds: i64,
+    pub gps_minus_utc_seconds: i64,
+    /// Entries announced but not yet in effect.
+    pub upcoming: Vec<LeapSecond>,
+    /// Until when the table is known to be complete.
+    pub valid_until_unix_millis: i64,
+    pub table: Vec<LeapSecond>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertedTime {
+    pub scale: TimeScale,
+    pub millis: i64,
+    /// GPS week number and milliseconds into the week, for the GPS scale.
+    pub gps_week: Option<i64>,
+    pub gps_millis_of_week: Option<i64>,
+}
+
+fn unix_millis(year: i32, month: u32, day: u32) -> i64 {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map_or(0, |time| time.and_utc().timestamp_millis())
+}
+
+fn table() -> impl Iterator<Item = LeapSecond> {
+    LEAP_SECONDS
+        .iter()
+        .map(|&(year, month, offset)| LeapSecond {
+            effective_unix_millis: unix_millis(year, month, 1),
+            tai_minus_utc_seconds: offset,
+        })
+}
+
+/// TAI − UTC at a UTC instant; the 1972 value is used for earlier instants.
+fn tai_minus_utc_at(unix_millis: i64) -> i64 {
+    table()
+        .take_while(|entry| entry.effective_unix_millis <= unix_millis)
+        .last()
+        .map_or(LEAP_SECONDS[0].2, |entry| entry.tai_minus_utc_seconds)
+}
+
+fn utc_to_tai(unix_millis: i64) -> i64 {
+    unix_millis + tai_minus_utc_at(unix_millis) * MILLIS_PER_SECOND
+}
+
+fn tai_to_utc(tai_millis: i64) -> i64 {
+    let offset = table()
+        .take_while(|entry| {
+            entry.effective_unix_millis + entry.tai_minus_utc_seconds * MILLIS_PER_SECOND
+                <= tai_millis
+        })
+        .last()
+        .map_or(LEAP_SECONDS[0].2, |entry| entry.tai_minus_utc_seconds);
+    tai_millis - offset * MILLIS_PER_SECOND
+}
+
+/// TAI milliseconds of the GPS epoch; TAI − UTC equalled TAI − GPS then.
+fn gps_epoch_tai_millis() -> i64 {
+    GPS_EPOCH_UNIX_MILLIS + GPS_TAI_OFFSET_SECONDS * MILLIS_PER_SECOND
+}
+
+fn to_tai(millis: i64, scale: TimeScale) -> i64 {
+    match scale {
+        TimeScale::Utc => utc_to_tai(millis),
+        TimeScale::Tai => millis,
+        TimeScale::Gps => millis + gps_epoch_tai_millis(),
+    }
+}
+
+fn from_tai(tai_millis: i64, scale: TimeScale) -> i64 {
+    match scale {
+        TimeScale::Utc => tai_to_utc(tai_millis),
+        TimeScale::Tai => tai_millis,
+        TimeScale::Gps => tai_millis - gps_epoch_tai_millis(),
+    }
+}
+
+/// Converts `millis` on the `from` scale to the `to` scale.
+#[tauri::command]
+pub fn convert_time_scale(millis: i64, from: TimeScale, to: TimeScale) -> ConvertedTime {
+    let converted = from_tai(to_tai(millis, from), to);
+    let gps = (to == TimeScale::Gps).then(|| {
+        (
+            converted.div_euclid(MILLIS_PER_WEEK),
+            converted.rem_euclid(MILLIS_PER_WEEK),
+        )
+    });
+    ConvertedTime {
+        scale: to,
+        millis: converted,
+        gps_week: gps.map(|(week, _)| week),
+        gps_millis_of_week: gps.map(|(_, millis)| millis),
+    }
+}
+
+/// Returns the current TAI − UTC offset, the leap second table, and any
+/// announced leap seconds that have not happened yet.
+#[tauri::command]
+pub fn get_leap_seconds() -> LeapSecondInfo {
+    let now = Utc::now().timestamp_millis();
+    let tai_minus_utc_seconds = tai_minus_utc_at(now);
+    let (year, month, day) = TABLE_VALID_UNTIL;
+
+    LeapSecondInfo {
+        tai_minus_utc_seconds,
+        gps_minus_utc_seconds: tai_minus_utc_seconds - GPS_TAI_OFFSET_SECONDS,
+        upcoming: table()
+            .filter(|entry| entry.effective_unix_millis > now)
+            .collect(),
+        valid_until_unix_millis: unix_millis(year, month, day),
+        table: table().collect(),
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{Datelike, Timelike};
+use icu::{
+    calendar::Date,
+    datetime::{
+        DateTimeFormatter, DateTimeFormatterLoadError,
+        fieldsets::{T, YMD, YMDE, YMDT},
+        options::TimePrecision,
+    },
+    locale::{Locale, locale},
+    time::{DateTime, Time},
+};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::State;
+use thiserror::Error;
+
+use crate::{
+    sync::{TimeSyncError, state::TimeState},
+    zone::{default_zone_name, parse_zone},
+};
+
+#[derive(Debug, Error)]
+pub enum LocaleError {
+    #[error("unsupported locale: {0}")]
+    Invalid(String),
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+}
+
+impl LocaleError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid_locale",
+            Self::InvalidTime(_) => "invalid_time",
+            Self::Zone(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for LocaleError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Self::Zone(err) = self {
+            return err.serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("LocaleError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// How much of the instant `format_localized` shows.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalizedStyle {
+    /// Hours and minutes, e.g. "14:05" or "2:05 PM".
+    #[default]
+    Time,
+    TimeWithSeconds,
+    /// Numeric date, e.g. "2026/10/15".
+    ShortDate,
+    /// Date with month name and weekday.
+    LongDate,
+    ShortDateTime,
+}
+
+/// The OS locale as a BCP 47 tag, e.g. `ja-JP`; `en-US` if it cannot be read.
+#[tauri::command]
+pub fn get_system_locale() -> String {
+    system_locale().unwrap_or(locale!("en-US")).to_string()
+}
+
+/// Formats `epoch_millis` (the displayed time by default) in `time_zone` the
+/// way `locale` (the OS locale by default) writes it, including its hour
+/// cycle, month names, and digits.
+#[tauri::command]
+pub fn format_localized(
+    state: State<'_, TimeState>,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+    style: Option<LocalizedStyle>,
+    locale: Option<String>,
+) -> Result<String, LocaleError> {
+    let locale = match locale {
+        Some(tag) => tag
+            .parse::<Locale>()
+            .map_err(|_| LocaleError::Invalid(tag.clone()))?,
+        None => system_locale().unwrap_or(locale!("en-US")),
+    };
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let epoch_millis = epoch_millis.unwrap_or_else(|| state.display_now_millis());
+    let local = chrono::DateTime::from_timestamp_millis(epoch_millis)
+        .ok_or_else(|| LocaleError::InvalidTime(format!("out of range: {epoch_millis}")))?
+        .with_timezone(&zone);
+
+    let invalid_time = |err: icu::calendar::RangeError| LocaleError::InvalidTime(err.to_string());
+    let date = Date::try_new_iso(local.year(), local.month() as u8, local.day() as u8)
+        .map_err(invalid_time)?;
+    let time = Time::try_new(
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second().min(59) as u8,
+        0,
+    )
+    .map_err(invalid_time)?;
+    let input = DateTime { date, time };
+
+    let prefs = (&locale).into();
+    let minutes = TimePrecision::Minute;
+    let unsupported =
+        |err: DateTimeFormatterLoadError| LocaleError::Invalid(format!("{locale}: {err}"));
+    let text = match style.unwrap_or_default() {
+        LocalizedStyle::Time => {
+            DateTimeFormatter::try_new(prefs, T::medium().with_time_precision(minutes))
+                .map(|formatter| formatter.format(&input).to_string())
+        }
+        LocalizedStyle::TimeWithSeconds => DateTimeFormatter::try_new(prefs, T::medium())
+            .map(|formatter| formatter.format(&input).to_string()),
+        LocalizedStyle::ShortDate => DateTimeFormatter::try_new(prefs, YMD::short())
+            .map(|formatter| formatter.format(&input).to_string()),
+        LocalizedStyle::LongDate => DateTimeFormatter::try_new(prefs, YMDE::long())
+            .map(|formatter| formatter.format(&input).to_string()),
+        LocalizedStyle::ShortDateTime => {
+            DateTimeFormatter::try_new(prefs, YMDT::short().with_time_precision(minutes))
+                .map(|formatter| formatter.format(&input).to_string())
+        }
+    }
+    .map_err(unsupported)?;
+    Ok(text)
+}
+
+/// Reads the OS locale.
+pub fn system_locale() -> Option<Locale> {
+    parse_posix(&sys_locale::get_locale()?)
+}
+
+/// Parses a locale name, turning POSIX forms such as `ja_JP.UTF-8` into
+/// BCP 47.
+pub fn parse_posix(raw: &str) -> Option<Locale> {
+    let tag = raw
+        .split(['.', '@'])
+        .next()
+        .unwrap_or(raw)
+        .replace('_', "-");
+    tag.parse()
+        .inspect_err(|err| eprintln!("ignoring unparseable locale {raw}: {err}"))
+        .ok()
+}
@@ -1,159 +1,72 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::time::Duration;
+mod alarm;
+mod audio;
+mod chime;
+mod connectivity;
+mod countdown;
+mod dnd;
+mod doh;
+mod hour_cycle;
+mod ical;
+mod leap;
+mod locale;
+mod net;
+mod pinning;
+mod placement;
+mod pomodoro;
+mod power;
+mod reminder;
+mod settings;
+mod shortcuts;
+mod stopwatch;
+mod store;
+mod subscription;
+mod sync;
+mod sysclock;
+mod time;
+mod timer;
+mod tooltip;
+mod wakelock;
+mod webhook;
+mod zone;
+mod zone_windows;
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use serde::Serialize;
-use serde_json::Value;
 use tauri::{
-    AppHandle, LogicalSize, Manager, PhysicalPosition, Size,
-    menu::{MenuBuilder, MenuItemBuilder},
+    AppHandle, Manager, Wry,
+    menu::{Menu, MenuBuilder, MenuItemBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconEvent},
+    window::UserAttentionType,
 };
-use thiserror::Error;
 
-const MILLIS_PER_SECOND: i64 = 1000;
-
-const MAIN_WINDOW_LABEL: &str = "main";
-const TRAY_ID_MAIN: &str = "main";
+pub(crate) const MAIN_WINDOW_LABEL: &str = "main";
+pub(crate) const TRAY_ID_MAIN: &str = "main";
 const MENU_ID_SHOW: &str = "tray-show";
 const MENU_ID_HIDE: &str = "tray-hide";
 const MENU_ID_QUIT: &str = "tray-quit";
 
-#[derive(Debug, Error)]
-enum TimeSyncError {
-    #[error("network request failed: {0}")]
-    Request(String),
-    #[error("failed to parse response")]
-    Parse,
-}
-
-#[derive(Serialize)]
-struct TimeSyncResult {
-    epoch_millis: i64,
-}
-
-#[tauri::command]
-async fn sync_time(time_zone: Option<String>) -> Result<TimeSyncResult, String> {
-    let zone = time_zone.unwrap_or_else(|| "Etc/UTC".to_string());
-    fetch_remote_time(&zone)
-        .await
-        .or_else(|err| {
-            eprintln!("time sync fallback triggered: {err}");
-            Ok(TimeSyncResult {
-                epoch_millis: Utc::now().timestamp_millis(),
-            })
-        })
-        .map_err(|err: TimeSyncError| err.to_string())
-}
-
-async fn fetch_remote_time(zone: &str) -> Result<TimeSyncResult, TimeSyncError> {
-    let encoded_zone = urlencoding::encode(zone);
-    let url = format!(
-        "https://timeapi.io/api/Time/current/zone?timeZone={}",
-        encoded_zone
-    );
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|err| TimeSyncError::Request(err.to_string()))?;
-
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|err| TimeSyncError::Request(err.to_string()))?;
-
-    if !response.status().is_success() {
-        return Err(TimeSyncError::Request(format!(
-            "unexpected status: {}",
-            response.status()
-        )));
-    }
-
-    let payload: Value = response.json().await.map_err(|_| TimeSyncError::Parse)?;
-    let epoch_millis = extract_epoch_millis(&payload).ok_or(TimeSyncError::Parse)?;
-
-    Ok(TimeSyncResult { epoch_millis })
-}
-
-fn extract_epoch_millis(payload: &Value) -> Option<i64> {
-    if let Some(unix_seconds) = payload.get("unixTime").and_then(value_to_i64) {
-        return Some(unix_seconds * MILLIS_PER_SECOND);
-    }
-
-    for key in [
-        "dateTime",
-        "dateTimeUtc",
-        "currentLocalTime",
-        "currentUtcTime",
-    ] {
-        if let Some(candidate) = payload.get(key).and_then(Value::as_str) {
-            if let Some(parsed) = parse_iso_candidate(candidate) {
-                return Some(parsed);
-            }
-        }
-    }
-
-    let year = payload.get("year").and_then(value_to_i64)?;
-    let month = payload.get("month").and_then(value_to_i64)?;
-    let day = payload.get("day").and_then(value_to_i64)?;
-    let hour = payload.get("hour").and_then(value_to_i64)?;
-    let minute = payload.get("minute").and_then(value_to_i64)?;
-    let seconds = payload.get("seconds").and_then(value_to_i64)?;
-    let millis = payload
-        .get("milliSeconds")
-        .and_then(value_to_i64)
-        .unwrap_or(0);
-
-    let year_i32 = i32::try_from(year).ok()?;
-    let month_u32 = u32::try_from(month).ok()?;
-    let day_u32 = u32::try_from(day).ok()?;
-    let hour_u32 = u32::try_from(hour).ok()?;
-    let minute_u32 = u32::try_from(minute).ok()?;
-    let second_u32 = u32::try_from(seconds).ok()?;
-    let millis_u32 = u32::try_from(millis).ok()?;
-
-    let date = NaiveDate::from_ymd_opt(year_i32, month_u32, day_u32)?;
-    let time = NaiveTime::from_hms_milli_opt(hour_u32, minute_u32, second_u32, millis_u32)?;
-
-    Some(NaiveDateTime::new(date, time).and_utc().timestamp_millis())
-}
-
-fn parse_iso_candidate(value: &str) -> Option<i64> {
-    if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(value) {
-        return Some(parsed.timestamp_millis());
-    }
-
-    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
-        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
-            return Some(naive.and_utc().timestamp_millis());
-        }
-    }
-
-    None
-}
-
-fn value_to_i64(value: &Value) -> Option<i64> {
-    match value {
-        Value::Number(number) => number
-            .as_i64()
-            .or_else(|| number.as_f64().map(|float| float.round() as i64)),
-        Value::String(text) => text
-            .trim()
-            .parse::<f64>()
-            .ok()
-            .map(|float| float.round() as i64),
-        _ => None,
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
     }
 }
 
-fn show_main_window(app: &AppHandle) {
-    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+/// Brings the main window up for a ringing alarm or a finished timer, as
+/// `alerts` in the settings asks.
+pub(crate) fn alert_main_window(app: &AppHandle) {
+    let alerts = app.state::<settings::SettingsStore>().get().alerts;
+    let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return;
+    };
+    if alerts.show_window {
+        let _ = window.unminimize();
         let _ = window.show();
         let _ = window.set_focus();
     }
+    if alerts.request_attention {
+        let _ = window.request_user_attention(Some(UserAttentionType::Critical));
+    }
 }
 
 fn hide_main_window(app: &AppHandle) {
@@ -177,8 +90,51 @@ fn toggle_main_window(app: &AppHandle) {
     }
 }
 
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let show_item = MenuItemBuilder::with_id(MENU_ID_SHOW, "Show").build(app)?;
+    let hide_item = MenuItemBuilder::with_id(MENU_ID_HIDE, "Hide").build(app)?;
+    let alarm_menus = alarm::tray::tray_submenus(app)?;
+    let timer_menus = timer::tray_submenus(app)?;
+    let zone_menu = zone::active::tray_submenu(app, &app.state::<settings::SettingsStore>().get())?;
+    let position_menu = placement::tray_submenu(app)?;
+    let monitor_menu = placement::monitor_submenu(app)?;
+    let quit_item = MenuItemBuilder::with_id(MENU_ID_QUIT, "Quit").build(app)?;
+
+    let mut menu = MenuBuilder::new(app)
+        .item(&show_item)
+        .item(&hide_item)
+        .separator();
+    if !alarm_menus.is_empty() || !timer_menus.is_empty() {
+        for submenu in alarm_menus.iter().chain(&timer_menus) {
+            menu = menu.item(submenu);
+        }
+        menu = menu.separator();
+    }
+    menu = menu.item(&zone_menu).item(&position_menu);
+    if let Some(monitor_menu) = &monitor_menu {
+        menu = menu.item(monitor_menu);
+    }
+    menu.separator().item(&quit_item).build()
+}
+
+/// Rebuilds the tray menu after the favorite or active zones, the alarms,
+/// the running timers, or the window's corner or monitor change.
+pub(crate) fn refresh_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID_MAIN) else {
+        return;
+    };
+    if let Err(err) = build_tray_menu(app).and_then(|menu| tray.set_menu(Some(menu))) {
+        eprintln!("failed to rebuild tray menu: {err}");
+    }
+}
+
 fn main() {
+    sync::clock::init();
+
     tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .on_tray_icon_event(|app, event| match event {
             TrayIconEvent::Click {
                 button,
@@ -193,52 +149,193 @@ fn main() {
             _ => {}
         })
         .setup(|app| {
+            let settings = settings::SettingsStore::load(app.handle());
+            app.manage(net::SharedClient::from_settings(&settings.get().network)?);
+            app.manage(settings);
+            let time_state = sync::state::TimeState::default();
+            time_state.set_simulation(sync::simulate::from_args(std::env::args().skip(1))?);
+            app.manage(time_state);
+            app.manage(power::PowerState::default());
+            app.manage(dnd::DndState::default());
+            app.manage(wakelock::WakeLockState::default());
+            app.manage(alarm::AlarmStore::load(app.handle()));
+            app.manage(audio::AudioPlayer::start(app.handle()));
+            app.manage(timer::TimerStore::load(app.handle()));
+            app.manage(stopwatch::StopwatchStore::load(app.handle()));
+            app.manage(pomodoro::PomodoroStore::load(app.handle()));
+            app.manage(reminder::ReminderStore::load(app.handle()));
+            app.manage(countdown::CountdownStore::load(app.handle()));
+            app.manage(subscription::SubscriptionStore::load(app.handle()));
+            app.manage(sync::history::HistoryStore::open(app.handle()));
+            app.manage(sync::nts::SessionCache::default());
+            app.manage(zone::detect::DetectionCache::default());
+            app.manage(zone_windows::ZoneWindowStore::load(app.handle()));
+            app.manage(placement::PlacementStore::load(app.handle()));
+            sync::restore(app.handle());
+            sync::task::spawn(app.handle().clone());
+
             let window = app
                 .get_webview_window(MAIN_WINDOW_LABEL)
                 .expect("main window unavailable");
             window.set_always_on_top(true)?;
             window.set_visible_on_all_workspaces(true)?;
             window.set_skip_taskbar(true)?;
-            let desired_size = LogicalSize::new(600.0, 600.0);
-            window.set_size(Size::Logical(desired_size))?;
-            let margin = 24.0;
-
-            if let Some(monitor) = window.current_monitor()? {
-                let monitor_size = monitor.size();
-                let outer_size = window.outer_size()?;
-                let x = (monitor_size.width as f64 - outer_size.width as f64 - margin).max(0.0);
-                let y = (monitor_size.height as f64 - outer_size.height as f64 - margin).max(0.0);
-                window.set_position(tauri::Position::Physical(PhysicalPosition {
-                    x: x.round() as i32,
-                    y: y.round() as i32,
-                }))?;
-            }
+            placement::restore(app.handle(), &window)?;
 
             if let Some(tray) = app.tray_by_id(TRAY_ID_MAIN) {
-                let show_item = MenuItemBuilder::with_id(MENU_ID_SHOW, "Show").build(app)?;
-                let hide_item = MenuItemBuilder::with_id(MENU_ID_HIDE, "Hide").build(app)?;
-                let quit_item = MenuItemBuilder::with_id(MENU_ID_QUIT, "Quit").build(app)?;
-
-                let tray_menu = MenuBuilder::new(app)
-                    .item(&show_item)
-                    .item(&hide_item)
-                    .separator()
-                    .item(&quit_item)
-                    .build()?;
-
-                tray.set_menu(Some(tray_menu))?;
+                tray.set_menu(Some(build_tray_menu(app.handle())?))?;
             }
 
+            power::watch(app.handle())?;
+            connectivity::watch(app.handle());
+            hour_cycle::watch(app.handle());
+            dnd::watch(app.handle());
+            alarm::scheduler::watch(app.handle());
+            alarm::system_task::sync(app.handle());
+            reminder::watch(app.handle());
+            countdown::watch(app.handle());
+            subscription::watch(app.handle());
+            timer::watch(app.handle());
+            pomodoro::watch(app.handle());
+            chime::watch(app.handle());
+            tooltip::watch(app.handle());
+            zone::watch(app.handle());
+            zone::dst::watch(app.handle());
+            zone_windows::restore(app.handle());
+            placement::watch(app.handle());
+            shortcuts::register(app.handle());
+
             app.on_menu_event(|app_handle, event| match event.id().as_ref() {
                 MENU_ID_SHOW => show_main_window(app_handle),
                 MENU_ID_HIDE => hide_main_window(app_handle),
                 MENU_ID_QUIT => app_handle.exit(0),
-                _ => {}
+                id => {
+                    zone::active::handle_menu_event(app_handle, id);
+                    alarm::tray::handle_menu_event(app_handle, id);
+                    timer::handle_menu_event(app_handle, id);
+                    placement::handle_menu_event(app_handle, id);
+                    placement::handle_monitor_menu_event(app_handle, id);
+                }
             });
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![sync_time])
+        .invoke_handler(tauri::generate_handler![
+            alarm::create_alarm,
+            alarm::update_alarm,
+            alarm::delete_alarm,
+            alarm::skip_alarm,
+            alarm::list_alarms,
+            alarm::ics::import_ics,
+            alarm::system_task::set_alarm_system_task,
+            alarm::snooze::snooze_alarm,
+            alarm::snooze::dismiss_alarm,
+            alarm::snooze::remind_alarm,
+            audio::list_sounds,
+            audio::play_sound,
+            audio::stop_sound,
+            audio::set_muted,
+            audio::custom::import_sound,
+            audio::custom::preview_sound,
+            timer::start_timer,
+            timer::start_timer_from_text,
+            timer::pause_timer,
+            timer::resume_timer,
+            timer::cancel_timer,
+            timer::list_timers,
+            stopwatch::get_stopwatch,
+            stopwatch::start_stopwatch,
+            stopwatch::stop_stopwatch,
+            stopwatch::lap_stopwatch,
+            stopwatch::reset_stopwatch,
+            stopwatch::export_laps,
+            shortcuts::get_shortcuts,
+            shortcuts::set_shortcuts,
+            pomodoro::get_pomodoro,
+            pomodoro::start_pomodoro,
+            pomodoro::pause_pomodoro,
+            pomodoro::resume_pomodoro,
+            pomodoro::skip_pomodoro_phase,
+            pomodoro::stop_pomodoro,
+            pomodoro::set_pomodoro_settings,
+            pomodoro::get_pomodoro_stats,
+            chime::get_chime_settings,
+            chime::set_chime_settings,
+            reminder::create_reminder,
+            reminder::update_reminder,
+            reminder::delete_reminder,
+            reminder::list_reminders,
+            reminder::validate_cron,
+            countdown::list_countdowns,
+            countdown::create_countdown,
+            countdown::update_countdown,
+            countdown::delete_countdown,
+            subscription::list_calendar_subscriptions,
+            subscription::add_calendar_subscription,
+            subscription::remove_calendar_subscription,
+            subscription::refresh_calendars,
+            subscription::get_next_events,
+            dnd::get_dnd_state,
+            dnd::set_respect_dnd,
+            wakelock::get_wake_lock,
+            wakelock::prevent_sleep,
+            wakelock::allow_sleep,
+            wakelock::set_wake_lock_for_timers,
+            wakelock::set_wake_lock_for_stopwatch,
+            webhook::list_webhooks,
+            webhook::set_webhooks,
+            webhook::test_webhook,
+            sync::sync_time,
+            sync::clock::monotonic_now,
+            sync::status::get_sync_status,
+            sync::history::get_sync_history,
+            sync::history::export_sync_history,
+            sync::benchmark::benchmark_time_sources,
+            sync::simulate::simulate_time,
+            sync::simulate::stop_simulation,
+            sync::debug::set_debug_offset,
+            sync::providers::list_time_providers,
+            sync::providers::set_provider_enabled,
+            sync::providers::set_provider_priority,
+            sysclock::set_system_clock_to_synced,
+            time::get_current_time,
+            hour_cycle::get_hour_cycle,
+            leap::convert_time_scale,
+            leap::get_leap_seconds,
+            locale::get_system_locale,
+            locale::format_localized,
+            zone::get_system_timezone,
+            zone::active::get_active_zone,
+            zone::active::set_active_zone,
+            zone::calendar::get_calendar_info,
+            zone::calendar::format_in_calendar,
+            zone::convert::convert_time,
+            zone::datemath::add_duration,
+            zone::datemath::add_business_days,
+            zone::datemath::next_occurrence_of_weekday,
+            zone::datemath::time_until,
+            zone::detect::detect_timezone,
+            zone::epoch::epoch_to_datetime,
+            zone::epoch::datetime_to_epoch,
+            zone::favorites::list_favorite_zones,
+            zone::favorites::add_favorite_zone,
+            zone::favorites::remove_favorite_zone,
+            zone::format::validate_format,
+            zone::format::render_format,
+            zone::info::get_timezone_info,
+            zone::info::zone_offset_at,
+            zone::meeting::find_meeting_windows,
+            zone::names::get_zone_display_names,
+            zone::natural::parse_natural,
+            zone::search::search_cities,
+            zone::search::search_timezones,
+            zone::world::world_clock,
+            zone_windows::open_zone_window,
+            placement::set_window_corner,
+            placement::list_monitors,
+            placement::set_window_monitor,
+            placement::set_follow_cursor
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
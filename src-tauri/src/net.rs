@@ -0,0 +1,172 @@
+use std::{collections::HashMap, fs, net::SocketAddr, sync::Arc, time::Duration};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use thiserror::Error;
+
+use crate::{
+    doh::DohResolver,
+    pinning,
+    settings::{IpPreference, NetworkSettings, ProxySettings},
+    sync::ProviderKind,
+};
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("invalid http client configuration: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("failed to read CA bundle: {0}")]
+    CaBundle(#[from] std::io::Error),
+    #[error("invalid certificate pinning configuration: {0}")]
+    Pinning(String),
+}
+
+/// The process-wide HTTP client, kept in managed state so every network
+/// command shares one connection pool and TLS session cache.
+#[derive(Clone)]
+pub struct SharedClient {
+    client: reqwest::Client,
+    /// Clients for providers that resolve hosts over DNS-over-HTTPS or pin
+    /// certificates; everything else uses `client`.
+    providers: HashMap<ProviderKind, reqwest::Client>,
+}
+
+impl SharedClient {
+    /// Builds the client from `settings`, falling back to a direct
+    /// connection with default timeouts if the configuration is unusable.
+    /// Certificate pins survive the fallback so it cannot bypass them.
+    pub fn from_settings(settings: &NetworkSettings) -> Result<Self, ClientError> {
+        let (settings, client) = match build_client(settings) {
+            Ok(client) => (settings.clone(), client),
+            Err(err) => {
+                eprintln!("{err}; using a direct connection instead");
+                let fallback = NetworkSettings {
+                    certificate_pins: settings.certificate_pins.clone(),
+                    ..NetworkSettings::default()
+                };
+                let client = build_client(&fallback)?;
+                (fallback, client)
+            }
+        };
+
+        let doh_resolver = Arc::new(OrderedResolver {
+            inner: Some(Arc::new(DohResolver::new(
+                client.clone(),
+                settings.doh.server,
+            ))),
+            preference: settings.ip_preference,
+        });
+        let mut providers = HashMap::new();
+        if !settings.doh.providers.is_empty() {
+            let doh_client = client_builder(&settings)?
+                .dns_resolver(doh_resolver.clone())
+                .build()?;
+            for provider in &settings.doh.providers {
+                providers.insert(*provider, doh_client.clone());
+            }
+        }
+        for pin in &settings.certificate_pins {
+            let config =
+                pinning::client_config(&pin.spki_sha256, settings.ca_bundle_path.as_deref())?;
+            let mut builder = client_builder(&settings)?.use_preconfigured_tls(config);
+            if settings.doh.providers.contains(&pin.provider) {
+                builder = builder.dns_resolver(doh_resolver.clone());
+            }
+            providers.insert(pin.provider, builder.build()?);
+        }
+        Ok(Self { client, providers })
+    }
+
+    /// Returns a handle to the shared client; clones share the same pool.
+    pub fn get(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    /// The client `provider` should use, honouring `network.doh.providers`
+    /// and `network.certificate_pins`.
+    pub fn for_provider(&self, provider: ProviderKind) -> reqwest::Client {
+        self.providers
+            .get(&provider)
+            .cloned()
+            .unwrap_or_else(|| self.get())
+    }
+}
+
+fn build_client(settings: &NetworkSettings) -> Result<reqwest::Client, ClientError> {
+    Ok(client_builder(settings)?.build()?)
+}
+
+fn client_builder(settings: &NetworkSettings) -> Result<reqwest::ClientBuilder, ClientError> {
+    let mut builder =
+        reqwest::Client::builder().timeout(Duration::from_secs(settings.timeout_secs.max(1)));
+
+    if settings.ip_preference != IpPreference::Any {
+        builder = builder.dns_resolver(Arc::new(OrderedResolver {
+            inner: None,
+            preference: settings.ip_preference,
+        }));
+    }
+
+    if let Some(address) = settings.local_address {
+        builder = builder.local_address(address);
+    }
+
+    if let Some(interface) = &settings.interface {
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            builder = builder.interface(interface);
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        eprintln!("binding to interface {interface} is not supported on this platform");
+    }
+
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(build_proxy(proxy)?);
+    }
+
+    if let Some(path) = &settings.ca_bundle_path {
+        let pem = fs::read(path)?;
+        for certificate in reqwest::Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Accepts `http://`, `https://`, `socks5://` and `socks5h://` proxy URLs.
+fn build_proxy(settings: &ProxySettings) -> Result<reqwest::Proxy, ClientError> {
+    let mut proxy = reqwest::Proxy::all(&settings.url)?;
+    if let Some(username) = &settings.username {
+        proxy = proxy.basic_auth(username, settings.password.as_deref().unwrap_or_default());
+    }
+    Ok(proxy)
+}
+
+/// Resolves through `inner`, or the system resolver, and lists addresses of
+/// the preferred family first. The connector races the other family only
+/// after a short delay, so a broken IPv6 route no longer stalls requests.
+struct OrderedResolver {
+    inner: Option<Arc<dyn Resolve>>,
+    preference: IpPreference,
+}
+
+impl Resolve for OrderedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let inner = self.inner.clone();
+        let preference = self.preference;
+        Box::pin(async move {
+            let mut addresses: Vec<SocketAddr> = match inner {
+                Some(inner) => inner.resolve(name).await?.collect(),
+                // Port 0 is replaced with the URL's port by reqwest.
+                None => tokio::net::lookup_host((name.as_str(), 0)).await?.collect(),
+            };
+            match preference {
+                IpPreference::Any => {}
+                IpPreference::Ipv4 => addresses.sort_by_key(|address| !address.is_ipv4()),
+                IpPreference::Ipv6 => addresses.sort_by_key(|address| !address.is_ipv6()),
+            }
+            let addrs: Addrs = Box::new(addresses.into_iter());
+            Ok(addrs)
+        })
+    }
+}
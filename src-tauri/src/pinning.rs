@@ -0,0 +1,179 @@
+use std::{error::Error as StdError, fmt, fs, io, path::Path, sync::Arc};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, Error as TlsError, OtherError,
+    RootCertStore, SignatureScheme,
+    client::{
+        WebPkiServerVerifier,
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    },
+    crypto::ring,
+    pki_types::{CertificateDer, ServerName, UnixTime, pem::PemObject},
+};
+use sha2::{Digest, Sha256};
+
+use crate::net::ClientError;
+
+/// Raised by `PinnedVerifier` when no certificate in the chain carries a
+/// pinned key; travels through reqwest's error chain to `TimeSyncError`.
+#[derive(Debug)]
+pub struct PinMismatch {
+    pub host: String,
+}
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no pinned key in the certificate chain of {}", self.host)
+    }
+}
+
+impl StdError for PinMismatch {}
+
+/// TLS configuration that validates the chain as usual and then requires one
+/// of its certificates to carry a key whose SHA-256 SPKI hash is in `pins`
+/// (base64, as used by HPKP). Unparseable pins are dropped, so a typo makes
+/// the provider fail closed rather than go unpinned.
+pub fn client_config(
+    pins: &[String],
+    ca_bundle_path: Option<&Path>,
+) -> Result<ClientConfig, ClientError> {
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    if let Some(path) = ca_bundle_path {
+        let pem = fs::read(path)?;
+        for certificate in CertificateDer::pem_slice_iter(&pem) {
+            let certificate = certificate
+                .map_err(|err| ClientError::Pinning(format!("invalid CA bundle: {err}")))?;
+            roots
+                .add(certificate)
+                .map_err(|err| ClientError::Pinning(err.to_string()))?;
+        }
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|err| ClientError::Pinning(err.to_string()))?;
+    let verifier = PinnedVerifier {
+        inner,
+        pins: pins.iter().filter_map(|pin| decode_pin(pin)).collect(),
+    };
+
+    Ok(ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| ClientError::Pinning(err.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+fn decode_pin(pin: &str) -> Option<[u8; 32]> {
+    let decoded = STANDARD
+        .decode(pin.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+    if decoded.is_none() {
+        eprintln!("ignoring invalid certificate pin {pin}");
+    }
+    decoded
+}
+
+/// Finds a `PinMismatch` anywhere in `err`'s source chain. The TLS layer
+/// wraps it in an `io::Error`, whose `source` skips the wrapped error, so
+/// those are unwrapped by hand.
+pub fn find_mismatch(err: &(dyn StdError + 'static)) -> Option<&PinMismatch> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        let wrapped = err
+            .downcast_ref::<io::Error>()
+            .and_then(io::Error::get_ref)
+            .map(|inner| inner as &(dyn StdError + 'static));
+        if let Some(mismatch) = [Some(err), wrapped]
+            .into_iter()
+            .flatten()
+            .find_map(as_mismatch)
+        {
+            return Some(mismatch);
+        }
+        current = err.source();
+    }
+    None
+}
+
+fn as_mismatch(err: &(dyn StdError + 'static)) -> Option<&PinMismatch> {
+    match err.downcast_ref::<TlsError>()? {
+        TlsError::InvalidCertificate(CertificateError::Other(OtherError(inner))) => {
+            inner.downcast_ref::<PinMismatch>()
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl PinnedVerifier {
+    fn is_pinned(&self, certificate: &CertificateDer<'_>) -> bool {
+        webpki::EndEntityCert::try_from(certificate).is_ok_and(|parsed| {
+            let hash: [u8; 32] = Sha256::digest(parsed.subject_public_key_info()).into();
+            self.pins.contains(&hash)
+        })
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        if std::iter::once(end_entity)
+            .chain(intermediates)
+            .any(|certificate| self.is_pinned(certificate))
+        {
+            return Ok(verified);
+        }
+        Err(TlsError::InvalidCertificate(CertificateError::Other(
+            OtherError(Arc::new(PinMismatch {
+                host: server_name.to_str().into_owned(),
+            })),
+        )))
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
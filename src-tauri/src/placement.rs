@@ -0,0 +1,774 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{
+    AppHandle, LogicalSize, Manager, Monitor, PhysicalPosition, PhysicalSize, Position, Size,
+    WebviewWindow, WindowEvent, Wry,
+    menu::{CheckMenuItemBuilder, Submenu, SubmenuBuilder},
+};
+use thiserror::Error;
+
+use crate::{
+    MAIN_WINDOW_LABEL,
+    settings::SettingsStore,
+    store::{self, StoreError},
+};
+
+const PLACEMENT_FILE_NAME: &str = "window-placement.json";
+/// Size of the main window on first run, before the user resizes it.
+const DEFAULT_SIZE: LogicalSize<f64> = LogicalSize::new(600.0, 600.0);
+/// Logical pixels between the window and the screen edges at a corner.
+const DEFAULT_MARGIN: f64 = 24.0;
+const MENU_ID_CORNER_PREFIX: &str = "tray-position:";
+const MENU_ID_CUSTOM: &str = "tray-position-custom";
+const MENU_ID_MONITOR_PREFIX: &str = "tray-monitor:";
+const MENU_ID_FOLLOW_CURSOR: &str = "tray-monitor-follow-cursor";
+/// How often the monitors, and in follow-cursor mode the cursor, are
+/// checked.
+const WATCH_PERIOD: Duration = Duration::from_millis(500);
+/// Quiet time after the last move or resize before the placement is saved,
+/// so a drag writes the file once rather than for every pixel.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum PlacementError {
+    #[error("the main window is not open")]
+    NoWindow,
+    #[error("no monitor to place the window on")]
+    NoMonitor,
+    #[error("no monitor {0}")]
+    MonitorNotFound(String),
+    #[error("failed to place window: {0}")]
+    Window(#[from] tauri::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl PlacementError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoWindow => "no_window",
+            Self::NoMonitor => "no_monitor",
+            Self::MonitorNotFound(_) => "monitor_not_found",
+            Self::Window(_) => "window",
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for PlacementError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PlacementError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl WindowCorner {
+    const ALL: [Self; 5] = [
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomLeft,
+        Self::BottomRight,
+        Self::Center,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::TopLeft => "top_left",
+            Self::TopRight => "top_right",
+            Self::BottomLeft => "bottom_left",
+            Self::BottomRight => "bottom_right",
+            Self::Center => "center",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::TopLeft => "Top left",
+            Self::TopRight => "Top right",
+            Self::BottomLeft => "Bottom left",
+            Self::BottomRight => "Bottom right",
+            Self::Center => "Center",
+        }
+    }
+
+    /// Top-left position of a window of `size` at this corner of `monitor`,
+    /// `margin` logical pixels from its edges.
+    fn position(
+        self,
+        monitor: &Monitor,
+        size: PhysicalSize<u32>,
+        margin: f64,
+    ) -> PhysicalPosition<i32> {
+        let margin = (margin * monitor.scale_factor()).round() as i32;
+        let origin = monitor.position();
+        let free_x = monitor.size().width as i32 - size.width as i32;
+        let free_y = monitor.size().height as i32 - size.height as i32;
+        let (x, y) = match self {
+            Self::TopLeft => (margin, margin),
+            Self::TopRight => (free_x - margin, margin),
+            Self::BottomLeft => (margin, free_y - margin),
+            Self::BottomRight => (free_x - margin, free_y - margin),
+            Self::Center => (free_x / 2, free_y / 2),
+        };
+        PhysicalPosition {
+            x: origin.x + x.max(0),
+            y: origin.y + y.max(0),
+        }
+    }
+}
+
+/// A connected monitor, as `list_monitors` reports it.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    /// Stable across launches; what `set_window_monitor` takes.
+    id: String,
+    name: Option<String>,
+    /// Position in the OS's monitor list.
+    index: usize,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    primary: bool,
+    /// Whether the main window is on this monitor.
+    current: bool,
+}
+
+/// What `watch` compares to notice a change of monitors.
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorLayout {
+    key: String,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+}
+
+impl MonitorLayout {
+    fn of(monitor: &Monitor) -> Self {
+        Self {
+            key: monitor_key(monitor),
+            position: *monitor.position(),
+            size: *monitor.size(),
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Where the main window sits on one monitor, in physical pixels, with the
+/// position relative to the monitor's top-left corner so it survives the
+/// monitors being rearranged.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Placement {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    /// Corner the window was put in with `set_window_corner`; `None` once
+    /// it is dragged elsewhere.
+    #[serde(default)]
+    corner: Option<WindowCorner>,
+    #[serde(default)]
+    margin: f64,
+    /// The monitor's scale factor when the size was saved, to keep the
+    /// window's logical size if it changes; 0 for placements saved before
+    /// it was recorded.
+    #[serde(default)]
+    scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedPlacement {
+    /// Monitor the window was last on.
+    monitor: Option<String>,
+    /// Last placement on each monitor the window has been on.
+    monitors: BTreeMap<String, Placement>,
+}
+
+impl SavedPlacement {
+    fn current(&self) -> Option<&Placement> {
+        self.monitors.get(self.monitor.as_ref()?)
+    }
+}
+
+/// The main window's placement, saved to `window-placement.json` in the app
+/// data directory shortly after the window stops moving or resizing.
+pub struct PlacementStore {
+    saved: Mutex<SavedPlacement>,
+    path: Option<PathBuf>,
+    /// Counts deferred changes, so only the last one's save runs.
+    pending: AtomicU64,
+}
+
+impl PlacementStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, PLACEMENT_FILE_NAME);
+        let saved = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            saved: Mutex::new(saved),
+            path,
+            pending: AtomicU64::new(0),
+        }
+    }
+
+    fn get(&self) -> SavedPlacement {
+        self.saved
+            .lock()
+            .map(|saved| saved.clone())
+            .unwrap_or_default()
+    }
+
+    fn update(&self, change: impl FnOnce(&mut SavedPlacement)) -> Result<(), StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let mut saved = self.saved.lock().unwrap_or_else(PoisonError::into_inner);
+        change(&mut saved);
+        store::write_json(path, &*saved)
+    }
+
+    /// Applies `change` in memory now and saves once `SAVE_DELAY` passes
+    /// without another deferred change.
+    fn update_later(&self, app: &AppHandle, change: impl FnOnce(&mut SavedPlacement)) {
+        change(&mut self.saved.lock().unwrap_or_else(PoisonError::into_inner));
+        let generation = self.pending.fetch_add(1, Ordering::Relaxed) + 1;
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            let store = app.state::<PlacementStore>();
+            if store.pending.load(Ordering::Relaxed) == generation
+                && let Err(err) = store.update(|_| {})
+            {
+                eprintln!("failed to save window placement: {err}");
+            }
+        });
+    }
+}
+
+/// Moves the main window to `corner` of its monitor, or its center,
+/// `margin` logical pixels (24 by default) from the edges. It stays there
+/// across launches and resizes until it is dragged away.
+#[tauri::command]
+pub fn set_window_corner(
+    app: AppHandle,
+    corner: WindowCorner,
+    margin: Option<f64>,
+) -> Result<(), PlacementError> {
+    let window = main_window(&app)?;
+    let monitor = window.current_monitor()?.ok_or(PlacementError::NoMonitor)?;
+    place_at_corner(
+        &app,
+        &window,
+        &monitor,
+        corner,
+        margin.unwrap_or(DEFAULT_MARGIN).max(0.0),
+    )?;
+    crate::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// A "Position" submenu with an entry per corner, checked for the one the
+/// window is in, and a disabled "Custom" entry checked when it was dragged.
+pub fn tray_submenu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let current = app
+        .state::<PlacementStore>()
+        .get()
+        .current()
+        .and_then(|placement| placement.corner);
+    let mut submenu = SubmenuBuilder::new(app, "Position");
+    for corner in WindowCorner::ALL {
+        let item = CheckMenuItemBuilder::with_id(
+            format!("{MENU_ID_CORNER_PREFIX}{}", corner.id()),
+            corner.title(),
+        )
+        .checked(current == Some(corner))
+        .build(app)?;
+        submenu = submenu.item(&item);
+    }
+    let custom = CheckMenuItemBuilder::with_id(MENU_ID_CUSTOM, "Custom")
+        .checked(current.is_none())
+        .enabled(false)
+        .build(app)?;
+    submenu.separator().item(&custom).build()
+}
+
+/// Handles clicks on the entries of `tray_submenu`; other ids are ignored.
+pub fn handle_menu_event(app: &AppHandle, id: &str) {
+    let Some(corner) = id.strip_prefix(MENU_ID_CORNER_PREFIX).and_then(|id| {
+        WindowCorner::ALL
+            .into_iter()
+            .find(|corner| corner.id() == id)
+    }) else {
+        return;
+    };
+    let margin = app
+        .state::<PlacementStore>()
+        .get()
+        .current()
+        .filter(|placement| placement.corner.is_some())
+        .map(|placement| placement.margin);
+    if let Err(err) = set_window_corner(app.clone(), corner, margin) {
+        eprintln!("failed to move the window from the tray: {err}");
+    }
+}
+
+#[tauri::command]
+pub fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, PlacementError> {
+    let window = main_window(&app)?;
+    let primary = window.primary_monitor()?.as_ref().map(monitor_key);
+    let current = app.state::<PlacementStore>().get().monitor;
+    Ok(window
+        .available_monitors()?
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let id = monitor_key(monitor);
+            MonitorInfo {
+                name: monitor.name().cloned(),
+                index,
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+                scale_factor: monitor.scale_factor(),
+                primary: primary.as_ref() == Some(&id),
+                current: current.as_ref() == Some(&id),
+                id,
+            }
+        })
+        .collect())
+}
+
+/// Moves the main window to the monitor with id `monitor` and keeps it
+/// there on later launches while that monitor is connected.
+#[tauri::command]
+pub fn set_window_monitor(app: AppHandle, monitor: String) -> Result<(), PlacementError> {
+    let window = main_window(&app)?;
+    let target = window
+        .available_monitors()?
+        .into_iter()
+        .find(|candidate| monitor_key(candidate) == monitor)
+        .ok_or(PlacementError::MonitorNotFound(monitor))?;
+    move_to(&app, &window, &target)?;
+    crate::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Turns the follow-cursor mode on or off.
+#[tauri::command]
+pub fn set_follow_cursor(app: AppHandle, enabled: bool) -> Result<(), PlacementError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.window.follow_cursor = enabled)?;
+    crate::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// A "Display" submenu listing the monitors, checked for the one the window
+/// is on, and a "Follow cursor" switch; `None` with a single monitor.
+pub fn monitor_submenu(app: &AppHandle) -> tauri::Result<Option<Submenu<Wry>>> {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return Ok(None);
+    };
+    let monitors = window.available_monitors()?;
+    if monitors.len() < 2 {
+        return Ok(None);
+    }
+    let current = app.state::<PlacementStore>().get().monitor;
+    let mut submenu = SubmenuBuilder::new(app, "Display");
+    for (index, monitor) in monitors.iter().enumerate() {
+        let key = monitor_key(monitor);
+        let name = match monitor.name() {
+            Some(name) => name.clone(),
+            None => format!("Display {}", index + 1),
+        };
+        let size = monitor.size();
+        let item = CheckMenuItemBuilder::with_id(
+            format!("{MENU_ID_MONITOR_PREFIX}{key}"),
+            format!("{name} ({}×{})", size.width, size.height),
+        )
+        .checked(current.as_ref() == Some(&key))
+        .build(app)?;
+        submenu = submenu.item(&item);
+    }
+    let follow = CheckMenuItemBuilder::with_id(MENU_ID_FOLLOW_CURSOR, "Follow cursor")
+        .checked(app.state::<SettingsStore>().get().window.follow_cursor)
+        .build(app)?;
+    submenu.separator().item(&follow).build().map(Some)
+}
+
+/// Handles clicks on the entries of `monitor_submenu`; other ids are
+/// ignored.
+pub fn handle_monitor_menu_event(app: &AppHandle, id: &str) {
+    if id == MENU_ID_FOLLOW_CURSOR {
+        let enabled = !app.state::<SettingsStore>().get().window.follow_cursor;
+        if let Err(err) = set_follow_cursor(app.clone(), enabled) {
+            eprintln!("failed to switch follow-cursor mode from the tray: {err}");
+        }
+        return;
+    }
+    let Some(monitor) = id.strip_prefix(MENU_ID_MONITOR_PREFIX) else {
+        return;
+    };
+    if let Err(err) = set_window_monitor(app.clone(), monitor.to_string()) {
+        eprintln!("failed to move the window to {monitor} from the tray: {err}");
+    }
+}
+
+/// Puts the main window back on screen at its logical size when monitors
+/// are connected, removed, rearranged, or rescaled, which Tauri has no
+/// event for. While `window.follow_cursor` is on in the settings, also
+/// moves the window to the monitor under the cursor whenever the cursor
+/// crosses to another one.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut layout = None;
+        loop {
+            tokio::time::sleep(WATCH_PERIOD).await;
+            if let Err(err) = check_monitors(&app, &mut layout) {
+                eprintln!("failed to refit the window to the monitors: {err}");
+            }
+            if app.state::<SettingsStore>().get().window.follow_cursor
+                && let Err(err) = follow_cursor(&app)
+            {
+                eprintln!("failed to move the window to the cursor's monitor: {err}");
+            }
+        }
+    });
+}
+
+/// Refits the window when the monitors differ from `known`, which is then
+/// updated.
+fn check_monitors(
+    app: &AppHandle,
+    known: &mut Option<Vec<MonitorLayout>>,
+) -> Result<(), PlacementError> {
+    let window = main_window(app)?;
+    let monitors = window.available_monitors()?;
+    let layout: Vec<MonitorLayout> = monitors.iter().map(MonitorLayout::of).collect();
+    if known
+        .replace(layout.clone())
+        .is_none_or(|known| known == layout)
+    {
+        return Ok(());
+    }
+    // The monitor the window was on, or, once it is gone, the one the OS
+    // moved the window to.
+    let saved = app.state::<PlacementStore>().get().monitor;
+    let target = match saved
+        .as_ref()
+        .and_then(|key| monitors.iter().find(|monitor| monitor_key(monitor) == *key))
+    {
+        Some(monitor) => Some(monitor.clone()),
+        None => window.current_monitor()?.or(window.primary_monitor()?),
+    };
+    if let Some(target) = target {
+        move_to(app, &window, &target)?;
+        clamp(&window, &target, true)?;
+    }
+    crate::refresh_tray_menu(app);
+    Ok(())
+}
+
+fn follow_cursor(app: &AppHandle) -> Result<(), PlacementError> {
+    let window = main_window(app)?;
+    if !window.is_visible()? {
+        return Ok(());
+    }
+    let cursor = app.cursor_position()?;
+    let monitors = window.available_monitors()?;
+    let point = PhysicalPosition {
+        x: cursor.x.floor() as i32,
+        y: cursor.y.floor() as i32,
+    };
+    let Some(target) = monitor_containing(&monitors, point) else {
+        return Ok(());
+    };
+    let current = app.state::<PlacementStore>().get().monitor;
+    if current.as_ref() == Some(&monitor_key(target)) {
+        return Ok(());
+    }
+    move_to(app, &window, target)?;
+    crate::refresh_tray_menu(app);
+    Ok(())
+}
+
+/// Puts the main window back where it was when the app last quit, or, on
+/// first run or when that monitor is no longer connected, in the
+/// bottom-right corner of the current monitor at the default size. Then
+/// keeps the saved placement up to date as the window moves.
+pub fn restore(app: &AppHandle, window: &WebviewWindow) -> Result<(), PlacementError> {
+    let saved = app.state::<PlacementStore>().get();
+    let monitors = window.available_monitors()?;
+    let restored = saved.monitor.as_ref().and_then(|key| {
+        let monitor = monitors
+            .iter()
+            .find(|monitor| monitor_key(monitor) == *key)?;
+        Some((monitor, saved.monitors.get(key)?))
+    });
+    match restored {
+        Some((monitor, placement)) => apply(window, monitor, placement)?,
+        None => {
+            window.set_size(Size::Logical(DEFAULT_SIZE))?;
+            if let Some(monitor) = window.current_monitor()? {
+                place_at_corner(
+                    app,
+                    window,
+                    &monitor,
+                    WindowCorner::BottomRight,
+                    DEFAULT_MARGIN,
+                )?;
+            }
+        }
+    }
+
+    let app = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let resized = match event {
+            WindowEvent::Moved(_) => false,
+            WindowEvent::Resized(_) => true,
+            // The window may be mid-drag to another monitor, so it is only
+            // kept from outgrowing the one it is on.
+            WindowEvent::ScaleFactorChanged { .. } => {
+                if let Ok(Some(monitor)) = tracked.current_monitor()
+                    && let Err(err) = clamp(&tracked, &monitor, false)
+                {
+                    eprintln!("failed to fit window to its monitor: {err}");
+                }
+                return;
+            }
+            _ => return,
+        };
+        if let Err(err) = remember(&app, &tracked, resized) {
+            eprintln!("failed to record window placement: {err}");
+        }
+    });
+    Ok(())
+}
+
+fn main_window(app: &AppHandle) -> Result<WebviewWindow, PlacementError> {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or(PlacementError::NoWindow)
+}
+
+/// Moves the window to `target`, where it last was on that monitor, or, if
+/// it has not been there, to the corner it is in now.
+fn move_to(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    target: &Monitor,
+) -> Result<(), PlacementError> {
+    let store = app.state::<PlacementStore>();
+    let saved = store.get();
+    let key = monitor_key(target);
+    match saved.monitors.get(&key) {
+        Some(placement) => {
+            apply(window, target, placement)?;
+            store.update(|saved| saved.monitor = Some(key))?;
+        }
+        None => {
+            let (corner, margin) = saved
+                .current()
+                .and_then(|placement| Some((placement.corner?, placement.margin)))
+                .unwrap_or((WindowCorner::BottomRight, DEFAULT_MARGIN));
+            place_at_corner(app, window, target, corner, margin)?;
+        }
+    }
+    Ok(())
+}
+
+/// Puts the window where `placement` says on `monitor`, scaling its size
+/// if the monitor's scale factor changed since, and keeping it on screen.
+fn apply(window: &WebviewWindow, monitor: &Monitor, placement: &Placement) -> tauri::Result<()> {
+    let scale = if placement.scale_factor > 0.0 {
+        monitor.scale_factor() / placement.scale_factor
+    } else {
+        1.0
+    };
+    window.set_size(Size::Physical(PhysicalSize {
+        width: (placement.width as f64 * scale).round() as u32,
+        height: (placement.height as f64 * scale).round() as u32,
+    }))?;
+    let position = match placement.corner {
+        Some(corner) => corner.position(monitor, window.outer_size()?, placement.margin),
+        None => {
+            let origin = monitor.position();
+            PhysicalPosition {
+                x: origin.x + placement.x,
+                y: origin.y + placement.y,
+            }
+        }
+    };
+    window.set_position(Position::Physical(position))?;
+    clamp(window, monitor, true)
+}
+
+/// Shrinks the window to fit on `monitor` and, with `move_onto`, moves it
+/// fully onto it.
+fn clamp(window: &WebviewWindow, monitor: &Monitor, move_onto: bool) -> tauri::Result<()> {
+    let (origin, bounds) = (monitor.position(), monitor.size());
+    let size = window.inner_size()?;
+    if size.width > bounds.width || size.height > bounds.height {
+        window.set_size(Size::Physical(PhysicalSize {
+            width: size.width.min(bounds.width),
+            height: size.height.min(bounds.height),
+        }))?;
+    }
+    if !move_onto {
+        return Ok(());
+    }
+    let size = window.outer_size()?;
+    let position = window.outer_position()?;
+    let clamped = PhysicalPosition {
+        x: position.x.clamp(
+            origin.x,
+            origin.x + (bounds.width as i32 - size.width as i32).max(0),
+        ),
+        y: position.y.clamp(
+            origin.y,
+            origin.y + (bounds.height as i32 - size.height as i32).max(0),
+        ),
+    };
+    if clamped != position {
+        window.set_position(Position::Physical(clamped))?;
+    }
+    Ok(())
+}
+
+fn place_at_corner(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    monitor: &Monitor,
+    corner: WindowCorner,
+    margin: f64,
+) -> Result<(), PlacementError> {
+    let position = corner.position(monitor, window.outer_size()?, margin);
+    window.set_position(Position::Physical(position))?;
+    let origin = monitor.position();
+    let size = window.inner_size()?;
+    let key = monitor_key(monitor);
+    app.state::<PlacementStore>().update(|saved| {
+        saved.monitors.insert(
+            key.clone(),
+            Placement {
+                x: position.x - origin.x,
+                y: position.y - origin.y,
+                width: size.width,
+                height: size.height,
+                corner: Some(corner),
+                margin,
+                scale_factor: monitor.scale_factor(),
+            },
+        );
+        saved.monitor = Some(key);
+    })?;
+    Ok(())
+}
+
+/// Records the window's placement on the monitor holding its center. A
+/// window in a corner keeps to it when resized, and leaves it when moved
+/// anywhere else.
+fn remember(app: &AppHandle, window: &WebviewWindow, resized: bool) -> Result<(), PlacementError> {
+    let mut position = window.outer_position()?;
+    let outer_size = window.outer_size()?;
+    let center = PhysicalPosition {
+        x: position.x + outer_size.width as i32 / 2,
+        y: position.y + outer_size.height as i32 / 2,
+    };
+    let Some(monitor) = monitor_at(window, center) else {
+        return Ok(());
+    };
+    let store = app.state::<PlacementStore>();
+    let saved = store.get();
+    let shown = saved.current().and_then(|placement| placement.corner);
+    let key = monitor_key(&monitor);
+    let mut anchor = saved
+        .monitors
+        .get(&key)
+        .and_then(|placement| Some((placement.corner?, placement.margin)));
+    if let Some((corner, margin)) = anchor {
+        let anchored = corner.position(&monitor, outer_size, margin);
+        if resized && anchored != position {
+            window.set_position(Position::Physical(anchored))?;
+            position = anchored;
+        } else if anchored != position {
+            anchor = None;
+        }
+    }
+    let origin = monitor.position();
+    let size = window.inner_size()?;
+    let changed_monitor = saved.monitor.as_ref() != Some(&key);
+    store.update_later(app, |saved| {
+        saved.monitors.insert(
+            key.clone(),
+            Placement {
+                x: position.x - origin.x,
+                y: position.y - origin.y,
+                width: size.width,
+                height: size.height,
+                corner: anchor.map(|(corner, _)| corner),
+                margin: anchor.map_or(DEFAULT_MARGIN, |(_, margin)| margin),
+                scale_factor: monitor.scale_factor(),
+            },
+        );
+        saved.monitor = Some(key);
+    });
+    if changed_monitor || shown != anchor.map(|(corner, _)| corner) {
+        crate::refresh_tray_menu(app);
+    }
+    Ok(())
+}
+
+/// The monitor containing `point`, or the window's current one when the
+/// point is between monitors.
+fn monitor_at(window: &WebviewWindow, point: PhysicalPosition<i32>) -> Option<Monitor> {
+    let monitors = window.available_monitors().unwrap_or_default();
+    monitor_containing(&monitors, point)
+        .cloned()
+        .or_else(|| window.current_monitor().ok().flatten())
+}
+
+fn monitor_containing(monitors: &[Monitor], point: PhysicalPosition<i32>) -> Option<&Monitor> {
+    monitors.iter().find(|monitor| {
+        let origin = monitor.position();
+        let size = monitor.size();
+        (origin.x..origin.x + size.width as i32).contains(&point.x)
+            && (origin.y..origin.y + size.height as i32).contains(&point.y)
+    })
+}
+
+/// Identifies a monitor across launches by its name, or by its size and
+/// position when the OS gives it none.
+fn monitor_key(monitor: &Monitor) -> String {
+    match monitor.name() {
+        Some(name) => name.clone(),
+        None => {
+            let (origin, size) = (monitor.position(), monitor.size());
+            format!("{}x{}@{},{}", size.width, size.height, origin.x, origin.y)
+        }
+    }
+}
@@ -0,0 +1,485 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Days, NaiveDate};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use thiserror::Error;
+
+use crate::{
+    audio::{AudioPlayer, Playback},
+    dnd,
+    settings::{PomodoroSettings, SettingsStore, WebhookEvent},
+    store::{self, StoreError},
+    sync::{TimeSyncError, clock, state::TimeState},
+    webhook,
+    zone::active::active_tz,
+};
+
+pub const EVENT_POMODORO_PHASE_CHANGED: &str = "pomodoro-phase-changed";
+
+const STATS_FILE_NAME: &str = "pomodoro_stats.json";
+/// How often the running phase is checked for its end, and so how late a
+/// phase change may come at most.
+const CHECK_PERIOD: Duration = Duration::from_secs(1);
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Error)]
+pub enum PomodoroError {
+    #[error("no Pomodoro session is running")]
+    NotStarted,
+    #[error("invalid Pomodoro settings: {0}")]
+    InvalidSettings(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl PomodoroError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotStarted => "pomodoro_not_started",
+            Self::InvalidSettings(_) => "invalid_pomodoro_settings",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for PomodoroError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PomodoroError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn minutes(self, settings: &PomodoroSettings) -> u32 {
+        match self {
+            Self::Work => settings.work_minutes,
+            Self::ShortBreak => settings.short_break_minutes,
+            Self::LongBreak => settings.long_break_minutes,
+        }
+    }
+
+    /// The phase as serialized, such as "short_break".
+    fn id(self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::ShortBreak => "short_break",
+            Self::LongBreak => "long_break",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Work => "Time to focus",
+            Self::ShortBreak => "Take a short break",
+            Self::LongBreak => "Take a long break",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Run {
+    Running { ends_at_monotonic_millis: i64 },
+    Paused { remaining_millis: i64 },
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    phase: Phase,
+    /// Work phases finished in the current cycle.
+    completed: u32,
+    phase_millis: i64,
+    run: Run,
+}
+
+impl Session {
+    /// The start of `phase`, running or waiting for `resume_pomodoro`.
+    fn enter(phase: Phase, completed: u32, settings: &PomodoroSettings, running: bool) -> Self {
+        let phase_millis = i64::from(phase.minutes(settings)) * 60_000;
+        let run = if running {
+            Run::Running {
+                ends_at_monotonic_millis: clock::monotonic_millis().saturating_add(phase_millis),
+            }
+        } else {
+            Run::Paused {
+                remaining_millis: phase_millis,
+            }
+        };
+        Self {
+            phase,
+            completed,
+            phase_millis,
+            run,
+        }
+    }
+
+    fn remaining_millis(&self, now_monotonic_millis: i64) -> i64 {
+        match self.run {
+            Run::Running {
+                ends_at_monotonic_millis,
+            } => (ends_at_monotonic_millis - now_monotonic_millis).max(0),
+            Run::Paused { remaining_millis } => remaining_millis,
+        }
+    }
+
+    fn is_over(&self, now_monotonic_millis: i64) -> bool {
+        matches!(self.run, Run::Running { .. }) && self.remaining_millis(now_monotonic_millis) == 0
+    }
+
+    /// The phase after this one. A finished work phase counts towards the
+    /// cycle; skipped ones do not.
+    fn next(&self, settings: &PomodoroSettings, finished: bool) -> Self {
+        let (phase, completed) = match self.phase {
+            Phase::Work => {
+                let completed = self.completed + u32::from(finished);
+                if completed >= settings.sessions_before_long_break {
+                    (Phase::LongBreak, completed)
+                } else {
+                    (Phase::ShortBreak, completed)
+                }
+            }
+            Phase::ShortBreak => (Phase::Work, self.completed),
+            Phase::LongBreak => (Phase::Work, 0),
+        };
+        Self::enter(phase, completed, settings, settings.auto_advance)
+    }
+
+    fn info(&self, settings: &PomodoroSettings) -> PomodoroInfo {
+        PomodoroInfo {
+            phase: self.phase,
+            running: matches!(self.run, Run::Running { .. }),
+            duration_millis: self.phase_millis,
+            remaining_millis: self.remaining_millis(clock::monotonic_millis()),
+            completed_sessions: self.completed,
+            sessions_before_long_break: settings.sessions_before_long_break,
+        }
+    }
+}
+
+/// The current phase as the frontend sees it. Also the payload of the
+/// `pomodoro-phase-changed` event, which carries `null` once stopped.
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroInfo {
+    phase: Phase,
+    running: bool,
+    duration_millis: i64,
+    remaining_millis: i64,
+    /// Work phases finished in the current cycle.
+    completed_sessions: u32,
+    sessions_before_long_break: u32,
+}
+
+/// Finished work phases on one day of the active zone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DayStats {
+    sessions: u32,
+    focus_millis: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRange {
+    /// Today.
+    Day,
+    /// The seven days ending today.
+    Week,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayTotal {
+    /// "YYYY-MM-DD" in the active zone.
+    date: String,
+    sessions: u32,
+    focus_millis: i64,
+}
+
+/// Answer to `get_pomodoro_stats`: totals over the range and per day,
+/// oldest first, including days without sessions.
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroStats {
+    sessions: u32,
+    focus_millis: i64,
+    days: Vec<DayTotal>,
+}
+
+/// The Pomodoro cycle, timed on the monotonic clock in the backend so a
+/// throttled, hidden webview does not delay phase changes. Finished work
+/// phases are counted per day in `pomodoro_stats.json` in the app data
+/// directory; the cycle itself starts over with the app.
+pub struct PomodoroStore {
+    session: Mutex<Option<Session>>,
+    stats: Mutex<BTreeMap<String, DayStats>>,
+    stats_path: Option<PathBuf>,
+}
+
+impl PomodoroStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let stats_path = store::data_file(app, STATS_FILE_NAME);
+        let stats = stats_path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            session: Mutex::new(None),
+            stats: Mutex::new(stats),
+            stats_path,
+        }
+    }
+
+    fn with<T>(&self, change: impl FnOnce(&mut Option<Session>) -> T) -> T {
+        change(&mut self.session.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn record(&self, date: NaiveDate, focus_millis: i64) -> Result<(), StoreError> {
+        let path = self.stats_path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        let day = stats
+            .entry(date.format(DATE_FORMAT).to_string())
+            .or_default();
+        day.sessions += 1;
+        day.focus_millis += focus_millis;
+        store::write_json(path, &*stats)
+    }
+
+    fn day(&self, date: NaiveDate) -> DayTotal {
+        let key = date.format(DATE_FORMAT).to_string();
+        let stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        let day = stats.get(&key).cloned().unwrap_or_default();
+        DayTotal {
+            date: key,
+            sessions: day.sessions,
+            focus_millis: day.focus_millis,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_pomodoro(app: AppHandle, pomodoro: State<'_, PomodoroStore>) -> Option<PomodoroInfo> {
+    let settings = app.state::<SettingsStore>().get().pomodoro;
+    pomodoro.with(|session| session.as_ref().map(|session| session.info(&settings)))
+}
+
+/// Starts a new cycle with a work phase, replacing any running one.
+#[tauri::command]
+pub fn start_pomodoro(app: AppHandle, pomodoro: State<'_, PomodoroStore>) -> PomodoroInfo {
+    let settings = app.state::<SettingsStore>().get().pomodoro;
+    let session = Session::enter(Phase::Work, 0, &settings, true);
+    let info = session.info(&settings);
+    pomodoro.with(|current| *current = Some(session));
+    emit(&app, Some(&info));
+    info
+}
+
+#[tauri::command]
+pub fn pause_pomodoro(
+    app: AppHandle,
+    pomodoro: State<'_, PomodoroStore>,
+) -> Result<PomodoroInfo, PomodoroError> {
+    modify(&app, &pomodoro, |session, now| {
+        session.run = Run::Paused {
+            remaining_millis: session.remaining_millis(now),
+        };
+    })
+}
+
+/// Continues a paused phase, or starts one that is waiting because
+/// `auto_advance` is off.
+#[tauri::command]
+pub fn resume_pomodoro(
+    app: AppHandle,
+    pomodoro: State<'_, PomodoroStore>,
+) -> Result<PomodoroInfo, PomodoroError> {
+    modify(&app, &pomodoro, |session, now| {
+        session.run = Run::Running {
+            ends_at_monotonic_millis: now.saturating_add(session.remaining_millis(now)),
+        };
+    })
+}
+
+/// Moves on to the next phase at once. A skipped work phase is not counted.
+#[tauri::command]
+pub fn skip_pomodoro_phase(
+    app: AppHandle,
+    pomodoro: State<'_, PomodoroStore>,
+) -> Result<PomodoroInfo, PomodoroError> {
+    let settings = app.state::<SettingsStore>().get().pomodoro;
+    let info = pomodoro.with(|current| {
+        let session = current.as_mut().ok_or(PomodoroError::NotStarted)?;
+        *session = session.next(&settings, false);
+        Ok::<_, PomodoroError>(session.info(&settings))
+    })?;
+    emit(&app, Some(&info));
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_pomodoro(app: AppHandle, pomodoro: State<'_, PomodoroStore>) {
+    pomodoro.with(|session| *session = None);
+    emit(&app, None);
+}
+
+/// Saves new Pomodoro settings. Phase lengths apply from the next phase.
+#[tauri::command]
+pub fn set_pomodoro_settings(
+    app: AppHandle,
+    player: State<'_, AudioPlayer>,
+    settings: PomodoroSettings,
+) -> Result<PomodoroSettings, PomodoroError> {
+    let lengths = [
+        settings.work_minutes,
+        settings.short_break_minutes,
+        settings.long_break_minutes,
+        settings.sessions_before_long_break,
+    ];
+    if lengths.contains(&0) {
+        return Err(PomodoroError::InvalidSettings(
+            "phase lengths and sessions before a long break must be at least 1".to_string(),
+        ));
+    }
+    if let Some(sound) = &settings.sound {
+        player
+            .source(sound)
+            .map_err(|err| PomodoroError::InvalidSettings(err.to_string()))?;
+    }
+    let updated = app
+        .state::<SettingsStore>()
+        .update(|current| current.pomodoro = settings)?;
+    Ok(updated.pomodoro)
+}
+
+/// Finished work sessions over `range`, by day of the active zone.
+#[tauri::command]
+pub fn get_pomodoro_stats(
+    app: AppHandle,
+    pomodoro: State<'_, PomodoroStore>,
+    range: StatsRange,
+) -> Result<PomodoroStats, PomodoroError> {
+    let today = today(&app)?;
+    let span = match range {
+        StatsRange::Day => 1,
+        StatsRange::Week => 7,
+    };
+    let days: Vec<DayTotal> = (0..span)
+        .rev()
+        .filter_map(|offset| today.checked_sub_days(Days::new(offset)))
+        .map(|date| pomodoro.day(date))
+        .collect();
+    Ok(PomodoroStats {
+        sessions: days.iter().map(|day| day.sessions).sum(),
+        focus_millis: days.iter().map(|day| day.focus_millis).sum(),
+        days,
+    })
+}
+
+fn modify(
+    app: &AppHandle,
+    pomodoro: &PomodoroStore,
+    change: impl FnOnce(&mut Session, i64),
+) -> Result<PomodoroInfo, PomodoroError> {
+    let settings = app.state::<SettingsStore>().get().pomodoro;
+    let now = clock::monotonic_millis();
+    pomodoro.with(|current| {
+        let session = current.as_mut().ok_or(PomodoroError::NotStarted)?;
+        change(session, now);
+        Ok(session.info(&settings))
+    })
+}
+
+fn today(app: &AppHandle) -> Result<NaiveDate, PomodoroError> {
+    let zone = active_tz(app)?;
+    let now = app.state::<TimeState>().schedule_now_millis();
+    Ok(DateTime::from_timestamp_millis(now)
+        .unwrap_or_default()
+        .with_timezone(&zone)
+        .date_naive())
+}
+
+/// Advances the cycle as each phase ends, counting finished work phases,
+/// with a sound, a notification, and `pomodoro-phase-changed`.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            advance(&app);
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+fn advance(app: &AppHandle) {
+    let pomodoro = app.state::<PomodoroStore>();
+    let settings = app.state::<SettingsStore>().get().pomodoro;
+    let now = clock::monotonic_millis();
+    let Some((finished, next)) = pomodoro.with(|current| {
+        let session = current.as_mut().filter(|session| session.is_over(now))?;
+        let finished = session.clone();
+        *session = session.next(&settings, true);
+        Some((finished, session.info(&settings)))
+    }) else {
+        return;
+    };
+
+    if finished.phase == Phase::Work {
+        let recorded =
+            today(app).and_then(|date| Ok(pomodoro.record(date, finished.phase_millis)?));
+        if let Err(err) = recorded {
+            eprintln!("failed to record Pomodoro session: {err}");
+        }
+    }
+    webhook::fire(
+        app,
+        WebhookEvent::PomodoroPhaseEnded,
+        finished.phase.id(),
+        vec![("next_phase", next.phase.id().to_string())],
+    );
+    if dnd::suppresses(app) {
+        emit(app, Some(&next));
+        return;
+    }
+    if let Some(sound) = &settings.sound
+        && let Err(err) = app.state::<AudioPlayer>().play(sound, &Playback::once(1.0))
+    {
+        eprintln!("failed to play Pomodoro sound: {err}");
+    }
+    let body = if settings.auto_advance {
+        format!("{} minutes", next.phase.minutes(&settings))
+    } else {
+        "Resume when you are ready.".to_string()
+    };
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title(next.phase.title())
+        .body(body)
+        .show()
+    {
+        eprintln!("failed to show Pomodoro notification: {err}");
+    }
+    emit(app, Some(&next));
+}
+
+fn emit(app: &AppHandle, info: Option<&PomodoroInfo>) {
+    if let Err(err) = app.emit(EVENT_POMODORO_PHASE_CHANGED, info) {
+        eprintln!("failed to emit {EVENT_POMODORO_PHASE_CHANGED}: {err}");
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::sync;
+
+pub const EVENT_SYSTEM_RESUMED: &str = "system-resumed";
+pub const EVENT_POWER_STATE_CHANGED: &str = "power-state-changed";
+
+/// How often the power source is re-read.
+const POWER_POLL_PERIOD: Duration = Duration::from_secs(30);
+
+/// Whether the machine currently runs on battery, managed by Tauri so the
+/// sync task can adapt its cadence.
+#[derive(Default)]
+pub struct PowerState {
+    on_battery: AtomicBool,
+}
+
+impl PowerState {
+    pub fn on_battery(&self) -> bool {
+        self.on_battery.load(Ordering::Relaxed)
+    }
+}
+
+/// Payload of the `power-state-changed` event.
+#[derive(Clone, Serialize)]
+struct PowerStateChanged {
+    on_battery: bool,
+}
+
+/// Starts listening for the system waking from sleep and for the power
+/// source switching between AC and battery.
+pub fn watch(app: &AppHandle) -> tauri::Result<()> {
+    watch_power_source(app);
+    platform::watch(app)
+}
+
+fn watch_power_source(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut previous = None;
+        loop {
+            let on_battery = tauri::async_runtime::spawn_blocking(read_on_battery)
+                .await
+                .unwrap_or(false);
+            if previous != Some(on_battery) {
+                app.state::<PowerState>()
+                    .on_battery
+                    .store(on_battery, Ordering::Relaxed);
+                let payload = PowerStateChanged { on_battery };
+                if let Err(err) = app.emit(EVENT_POWER_STATE_CHANGED, payload) {
+                    eprintln!("failed to emit {EVENT_POWER_STATE_CHANGED}: {err}");
+                }
+                previous = Some(on_battery);
+            }
+            tokio::time::sleep(POWER_POLL_PERIOD).await;
+        }
+    });
+}
+
+/// A machine without a readable battery is assumed to be on AC power.
+fn read_on_battery() -> bool {
+    let Ok(manager) = starship_battery::Manager::new() else {
+        return false;
+    };
+    let Ok(batteries) = manager.batteries() else {
+        return false;
+    };
+    batteries
+        .flatten()
+        .any(|battery| battery.state() == starship_battery::State::Discharging)
+}
+
+/// Tells the frontend the machine woke up and resyncs right away.
+pub fn resumed(app: &AppHandle) {
+    if let Err(err) = app.emit(EVENT_SYSTEM_RESUMED, ()) {
+        eprintln!("failed to emit {EVENT_SYSTEM_RESUMED}: {err}");
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        sync::resync(&app).await;
+    });
+}
+
+/// Windows broadcasts `WM_POWERBROADCAST` to every top-level window, so the
+/// main window is subclassed to observe it.
+#[cfg(windows)]
+mod platform {
+    use tauri::{AppHandle, Manager};
+    use windows_sys::Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        UI::{
+            Shell::{DefSubclassProc, SetWindowSubclass},
+            WindowsAndMessaging::{PBT_APMRESUMEAUTOMATIC, WM_POWERBROADCAST},
+        },
+    };
+
+    use crate::MAIN_WINDOW_LABEL;
+
+    const SUBCLASS_ID: usize = 0x5253_4d45;
+
+    pub fn watch(app: &AppHandle) -> tauri::Result<()> {
+        let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+            return Ok(());
+        };
+        let hwnd = window.hwnd()?.0 as HWND;
+        // The handle lives as long as the window, which lives as long as the app.
+        let app = Box::into_raw(Box::new(app.clone())) as usize;
+        if unsafe { SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, app) } == 0 {
+            eprintln!("failed to subclass main window for power notifications");
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        app: usize,
+    ) -> LRESULT {
+        if message == WM_POWERBROADCAST && wparam == PBT_APMRESUMEAUTOMATIC as WPARAM {
+            let app = unsafe { &*(app as *const AppHandle) };
+            super::resumed(app);
+        }
+        unsafe { DefSubclassProc(hwnd, message, wparam, lparam) }
+    }
+}
+
+/// Linux and macOS offer no notification reachable without extra system
+/// libraries. Their monotonic clock stops while suspended, so `sync::task`
+/// recognises a wake-up as a large wall-clock jump instead.
+#[cfg(not(windows))]
+mod platform {
+    use tauri::AppHandle;
+
+    pub fn watch(_app: &AppHandle) -> tauri::Result<()> {
+        Ok(())
+    }
+}
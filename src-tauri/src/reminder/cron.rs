@@ -0,0 +1,183 @@
+use chrono::{DateTime, Datelike, Days, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::zone::datemath;
+
+/// How far ahead to look for the next matching day: past every leap day of
+/// a four-year cycle, so `0 9 29 2 *` is found.
+const SEARCH_DAYS: u64 = 4 * 366 + 1;
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A five-field cron expression: minute, hour, day of month, month, and day
+/// of week. Fields take `*`, numbers, names (`JAN`, `MON`), ranges, lists
+/// and `/` steps; Sunday is 0 or 7. As in Vixie cron, a day matches either
+/// day field when both are restricted. `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    /// One bit per allowed value, bit 0 for the field's value 0.
+    minutes: u64,
+    hours: u64,
+    month_days: u64,
+    months: u64,
+    /// Bit 0 is Sunday.
+    weekdays: u64,
+    /// Whether the day-of-month and day-of-week fields were `*`.
+    any_month_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim().to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            _ => expression.trim().to_string(),
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, month_day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES, 0)?;
+        // 7 is another name for Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0)?,
+            hours: parse_field(hour, 0, 23, &[], 0)?,
+            month_days: parse_field(month_day, 1, 31, &[], 0)?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, 1)?,
+            weekdays,
+            any_month_day: month_day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// First instant after `after_millis` matching the expression on the
+    /// wall clock of `zone`. Times skipped by a DST gap happen right after
+    /// it; times repeated by an overlap happen once, the first time.
+    pub fn next_after(&self, zone: Tz, after_millis: i64) -> Option<i64> {
+        let today = DateTime::from_timestamp_millis(after_millis)?
+            .with_timezone(&zone)
+            .date_naive();
+        (0..=SEARCH_DAYS)
+            .map_while(|offset| today.checked_add_days(Days::new(offset)))
+            .filter(|date| self.matches_day(date.day(), date.month(), date.weekday()))
+            .find_map(|date| {
+                // The earliest of the day, since a time shifted out of a
+                // gap may land after later ones.
+                self.times()
+                    .filter_map(|time| datemath::resolve(zone, date.and_time(time)))
+                    .map(|local| local.timestamp_millis())
+                    .filter(|epoch_millis| *epoch_millis > after_millis)
+                    .min()
+            })
+    }
+
+    /// The next `count` instants after `after_millis`.
+    pub fn upcoming(&self, zone: Tz, after_millis: i64, count: usize) -> Vec<i64> {
+        let mut after = after_millis;
+        let mut found = Vec::with_capacity(count);
+        while found.len() < count {
+            let Some(next) = self.next_after(zone, after) else {
+                break;
+            };
+            found.push(next);
+            after = next;
+        }
+        found
+    }
+
+    fn matches_day(&self, month_day: u32, month: u32, weekday: chrono::Weekday) -> bool {
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let by_month_day = self.month_days & (1 << month_day) != 0;
+        let by_weekday = self.weekdays & (1 << weekday.num_days_from_sunday()) != 0;
+        match (self.any_month_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => by_weekday,
+            (false, true) => by_month_day,
+            (false, false) => by_month_day || by_weekday,
+        }
+    }
+
+    fn times(&self) -> impl Iterator<Item = NaiveTime> + '_ {
+        (0..24u32)
+            .filter(|hour| self.hours & (1 << hour) != 0)
+            .flat_map(move |hour| {
+                (0..60u32)
+                    .filter(|minute| self.minutes & (1 << minute) != 0)
+                    .filter_map(move |minute| NaiveTime::from_hms_opt(hour, minute, 0))
+            })
+    }
+}
+
+/// Parses one field into a bit mask over `min..=max`. `names[i]` stands for
+/// the value `i + name_base`.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_base: u32,
+) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let parsed = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+            .map(|index| index as u32 + name_base)
+            .or_else(|| text.parse().ok())
+            .ok_or_else(|| format!("invalid value in \"{field}\": {text}"))?;
+        if (min..=max).contains(&parsed) {
+            Ok(parsed)
+        } else {
+            Err(format!(
+                "{parsed} is out of range {min}-{max} in \"{field}\""
+            ))
+        }
+    };
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step in \"{field}\": {step}"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // "5/15" runs from 5 to the end of the range.
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let single = value(range)?;
+                    (single, single)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range runs backwards in \"{field}\": {range}"));
+        }
+        for allowed in (start..=end).step_by(step as usize) {
+            mask |= 1 << allowed;
+        }
+    }
+    Ok(mask)
+}
@@ -0,0 +1,357 @@
+pub mod cron;
+
+use std::{path::PathBuf, sync::Mutex, time::Duration};
+
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use thiserror::Error;
+
+use crate::{
+    dnd,
+    store::{self, StoreError},
+    sync::{TimeSyncError, state::TimeState},
+    zone::{LocalComponents, active::active_tz, parse_zone},
+};
+
+use cron::Cron;
+
+pub const EVENT_REMINDERS_CHANGED: &str = "reminders-changed";
+pub const EVENT_REMINDER_FIRED: &str = "reminder-fired";
+
+const REMINDERS_FILE_NAME: &str = "reminders.json";
+/// How often reminders are checked against the corrected clock.
+const CHECK_PERIOD: Duration = Duration::from_secs(1);
+/// Occurrences listed by `validate_cron`.
+const PREVIEW_COUNT: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum ReminderError {
+    #[error("invalid cron expression: {0}")]
+    InvalidCron(String),
+    #[error("no reminder with id {0}")]
+    NotFound(u64),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl ReminderError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidCron(_) => "invalid_cron",
+            Self::NotFound(_) => "reminder_not_found",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for ReminderError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ReminderError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// A reminder as the frontend defines it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderSpec {
+    /// Shown in the notification.
+    pub message: String,
+    /// Five-field cron expression, such as `25 17 * * MON-FRI`.
+    pub cron: String,
+    /// IANA zone the expression is read in; `None` follows the main clock.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: u64,
+    #[serde(flatten)]
+    pub spec: ReminderSpec,
+    /// Corrected time of the next occurrence; `None` while disabled.
+    pub next_fire_epoch_millis: Option<i64>,
+}
+
+/// Payload of the `reminder-fired` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderFired {
+    id: u64,
+    message: String,
+    scheduled_epoch_millis: i64,
+    fired_epoch_millis: i64,
+}
+
+/// One occurrence in the `validate_cron` preview.
+#[derive(Debug, Clone, Serialize)]
+pub struct CronOccurrence {
+    epoch_millis: i64,
+    local: LocalComponents,
+}
+
+/// All reminders, saved to `reminders.json` in the app data directory
+/// whenever one is added, edited, removed, or fires.
+pub struct ReminderStore {
+    reminders: Mutex<Vec<Reminder>>,
+    path: Option<PathBuf>,
+}
+
+impl ReminderStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, REMINDERS_FILE_NAME);
+        let reminders = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            reminders: Mutex::new(reminders),
+            path,
+        }
+    }
+
+    pub fn list(&self) -> Vec<Reminder> {
+        self.reminders
+            .lock()
+            .map(|reminders| reminders.clone())
+            .unwrap_or_default()
+    }
+
+    /// Applies `change` and saves the result. When `change` or the save
+    /// fails, the list is left untouched.
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Vec<Reminder>) -> Result<T, ReminderError>,
+    ) -> Result<T, ReminderError> {
+        store::update_locked(self.path.as_deref(), &self.reminders, change)
+    }
+}
+
+#[tauri::command]
+pub fn list_reminders(reminders: State<'_, ReminderStore>) -> Vec<Reminder> {
+    reminders.list()
+}
+
+#[tauri::command]
+pub fn create_reminder(app: AppHandle, reminder: ReminderSpec) -> Result<Reminder, ReminderError> {
+    let next_fire_epoch_millis = schedule(&app, &reminder)?;
+    let created = app.state::<ReminderStore>().update(|reminders| {
+        let id = reminders
+            .iter()
+            .map(|reminder| reminder.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let created = Reminder {
+            id,
+            spec: reminder,
+            next_fire_epoch_millis,
+        };
+        reminders.push(created.clone());
+        Ok(created)
+    })?;
+    publish(&app);
+    Ok(created)
+}
+
+#[tauri::command]
+pub fn update_reminder(
+    app: AppHandle,
+    id: u64,
+    reminder: ReminderSpec,
+) -> Result<Reminder, ReminderError> {
+    let next_fire_epoch_millis = schedule(&app, &reminder)?;
+    let updated = app.state::<ReminderStore>().update(|reminders| {
+        let existing = reminders
+            .iter_mut()
+            .find(|existing| existing.id == id)
+            .ok_or(ReminderError::NotFound(id))?;
+        existing.spec = reminder;
+        existing.next_fire_epoch_millis = next_fire_epoch_millis;
+        Ok(existing.clone())
+    })?;
+    publish(&app);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn delete_reminder(app: AppHandle, id: u64) -> Result<(), ReminderError> {
+    app.state::<ReminderStore>().update(|reminders| {
+        let index = reminders
+            .iter()
+            .position(|reminder| reminder.id == id)
+            .ok_or(ReminderError::NotFound(id))?;
+        reminders.remove(index);
+        Ok(())
+    })?;
+    publish(&app);
+    Ok(())
+}
+
+/// Checks a cron expression and returns its next five occurrences in
+/// `time_zone`, or the main clock's zone, for a preview while typing.
+#[tauri::command]
+pub fn validate_cron(
+    app: AppHandle,
+    expression: String,
+    time_zone: Option<String>,
+) -> Result<Vec<CronOccurrence>, ReminderError> {
+    let cron = Cron::parse(&expression).map_err(ReminderError::InvalidCron)?;
+    let zone = reminder_zone(&app, time_zone.as_deref())?;
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    Ok(cron
+        .upcoming(zone, now_millis, PREVIEW_COUNT)
+        .into_iter()
+        .filter_map(|epoch_millis| {
+            let local = DateTime::from_timestamp_millis(epoch_millis)?.with_timezone(&zone);
+            Some(CronOccurrence {
+                epoch_millis,
+                local: LocalComponents::from_datetime(&local),
+            })
+        })
+        .collect())
+}
+
+/// Moves reminders without a zone of their own on to their next time in the
+/// main clock's zone, after that zone changed.
+pub fn rezone(app: &AppHandle) -> Result<(), ReminderError> {
+    let follows_main_clock = |reminder: &Reminder| {
+        reminder.spec.time_zone.is_none() && reminder.next_fire_epoch_millis.is_some()
+    };
+    let store = app.state::<ReminderStore>();
+    if !store.list().iter().any(follows_main_clock) {
+        return Ok(());
+    }
+
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    store.update(|reminders| {
+        for reminder in reminders
+            .iter_mut()
+            .filter(|reminder| follows_main_clock(reminder))
+        {
+            reminder.next_fire_epoch_millis = next_fire(app, &reminder.spec, now_millis)?;
+        }
+        Ok(())
+    })?;
+    publish(app);
+    Ok(())
+}
+
+fn reminder_zone(app: &AppHandle, time_zone: Option<&str>) -> Result<Tz, ReminderError> {
+    Ok(match time_zone {
+        Some(name) => parse_zone(name)?,
+        None => active_tz(app)?,
+    })
+}
+
+/// Next time `reminder` fires after `after_millis`, or `None` if it is
+/// disabled or its expression never matches again.
+fn next_fire(
+    app: &AppHandle,
+    reminder: &ReminderSpec,
+    after_millis: i64,
+) -> Result<Option<i64>, ReminderError> {
+    let cron = Cron::parse(&reminder.cron).map_err(ReminderError::InvalidCron)?;
+    let zone = reminder_zone(app, reminder.time_zone.as_deref())?;
+    if !reminder.enabled {
+        return Ok(None);
+    }
+    Ok(cron.next_after(zone, after_millis))
+}
+
+fn schedule(app: &AppHandle, reminder: &ReminderSpec) -> Result<Option<i64>, ReminderError> {
+    next_fire(
+        app,
+        reminder,
+        app.state::<TimeState>().schedule_now_millis(),
+    )
+}
+
+fn publish(app: &AppHandle) {
+    let reminders = app.state::<ReminderStore>().list();
+    if let Err(err) = app.emit(EVENT_REMINDERS_CHANGED, reminders) {
+        eprintln!("failed to emit {EVENT_REMINDERS_CHANGED}: {err}");
+    }
+}
+
+/// Fires reminders whose time has come and moves each on to its next
+/// occurrence after now, so ones missed while the app was closed fire once.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match fire_due(&app) {
+                Ok(fired) if !fired.is_empty() => {
+                    publish(&app);
+                    for reminder in fired {
+                        notify(&app, reminder);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("failed to update fired reminders: {err}"),
+            }
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+fn fire_due(app: &AppHandle) -> Result<Vec<ReminderFired>, ReminderError> {
+    let store = app.state::<ReminderStore>();
+    let now_millis = app.state::<TimeState>().schedule_now_millis();
+    let is_due = |reminder: &Reminder| {
+        reminder
+            .next_fire_epoch_millis
+            .filter(|at| *at <= now_millis)
+    };
+    if !store
+        .list()
+        .iter()
+        .any(|reminder| is_due(reminder).is_some())
+    {
+        return Ok(Vec::new());
+    }
+    store.update(|reminders| {
+        let mut fired = Vec::new();
+        for reminder in reminders.iter_mut() {
+            let Some(scheduled) = is_due(reminder) else {
+                continue;
+            };
+            reminder.next_fire_epoch_millis = next_fire(app, &reminder.spec, now_millis)?;
+            fired.push(ReminderFired {
+                id: reminder.id,
+                message: reminder.spec.message.clone(),
+                scheduled_epoch_millis: scheduled,
+                fired_epoch_millis: now_millis,
+            });
+        }
+        Ok(fired)
+    })
+}
+
+fn notify(app: &AppHandle, reminder: ReminderFired) {
+    if !dnd::suppresses(app)
+        && let Err(err) = app
+            .notification()
+            .builder()
+            .title("Reminder")
+            .body(&reminder.message)
+            .show()
+    {
+        eprintln!("failed to show reminder notification: {err}");
+    }
+    if let Err(err) = app.emit(EVENT_REMINDER_FIRED, reminder) {
+        eprintln!("failed to emit {EVENT_REMINDER_FIRED}: {err}");
+    }
+}
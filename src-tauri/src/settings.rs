@@ -0,0 +1,667 @@
+use std::{
+    net::IpAddr,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{
+    store::{self, StoreError},
+    sync::{ExtractRule, ProviderKind},
+};
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub sync: SyncSettings,
+    pub network: NetworkSettings,
+    pub geolocation: GeolocationSettings,
+    /// IANA names of the user's favorite zones, in display order.
+    pub favorite_zones: Vec<String>,
+    /// Zone the main clock shows; `None` follows the OS zone.
+    pub active_zone: Option<String>,
+    pub dst_notice: DstNoticeSettings,
+    pub audio: AudioSettings,
+    pub pomodoro: PomodoroSettings,
+    pub chime: ChimeSettings,
+    pub dnd: DndSettings,
+    pub alerts: AlertSettings,
+    pub alarm_task: AlarmTaskSettings,
+    pub calendars: CalendarSettings,
+    pub wake_lock: WakeLockSettings,
+    pub shortcuts: ShortcutSettings,
+    pub window: WindowSettings,
+    pub webhooks: Vec<Webhook>,
+    pub developer: DeveloperSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// Base delay between background resyncs.
+    pub interval_secs: u64,
+    /// Fraction of `interval_secs` the delay may be randomly shifted by.
+    pub jitter_ratio: f64,
+    /// Factor `interval_secs` is stretched by while running on battery.
+    pub battery_interval_multiplier: f64,
+    /// Syncs requested sooner than this after the previous one reuse its result.
+    pub min_interval_secs: u64,
+    /// Number of quick syncs run at startup before the regular cadence.
+    pub burst_count: u32,
+    /// Delay between the startup burst syncs.
+    pub burst_interval_secs: u64,
+    /// Provider order for syncs; `None` uses the built-in order.
+    pub priority: Option<Vec<ProviderKind>>,
+    /// Providers never queried, whatever the priority says.
+    pub disabled_providers: Vec<ProviderKind>,
+    pub retry: RetrySettings,
+    pub history: HistorySettings,
+    pub slew: SlewSettings,
+    pub skew_warning: SkewWarningSettings,
+    pub plausibility: PlausibilitySettings,
+    /// User-defined time servers; tried before the built-in providers.
+    pub custom_endpoints: Vec<CustomEndpoint>,
+    /// Hosts whose `Date` response header serves as a last-resort time source.
+    pub date_header_hosts: Vec<String>,
+    /// NTS-KE hosts (`host` or `host:port`) tried in order before plain NTP;
+    /// an empty list disables NTS. Keys and cookies are kept between syncs.
+    pub nts_servers: Vec<String>,
+    /// Servers used by the `roughtime` provider, in order.
+    pub roughtime_servers: Vec<RoughtimeServer>,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: 15 * 60,
+            jitter_ratio: 0.1,
+            battery_interval_multiplier: 4.0,
+            min_interval_secs: 30,
+            burst_count: 3,
+            burst_interval_secs: 5,
+            priority: None,
+            disabled_providers: Vec::new(),
+            retry: RetrySettings::default(),
+            history: HistorySettings::default(),
+            slew: SlewSettings::default(),
+            skew_warning: SkewWarningSettings::default(),
+            plausibility: PlausibilitySettings::default(),
+            custom_endpoints: Vec::new(),
+            date_header_hosts: vec![
+                "https://www.google.com".to_string(),
+                "https://www.cloudflare.com".to_string(),
+            ],
+            nts_servers: vec![
+                "time.cloudflare.com".to_string(),
+                "nts.netnod.se".to_string(),
+            ],
+            roughtime_servers: vec![
+                RoughtimeServer {
+                    address: "roughtime.cloudflare.com:2002".to_string(),
+                    public_key: "gD63hSj3ScS+wuOeGrubXlq35N1c5Lby/S+T7MNTjxo=".to_string(),
+                },
+                RoughtimeServer {
+                    address: "roughtime.sandbox.google.com:2002".to_string(),
+                    public_key: "etPaaIxcBMY1oUeGpwvPMCJMwlRVNxv51KK/tktoJTQ=".to_string(),
+                },
+            ],
+        }
+    }
+}
+
+/// An HTTP time server registered by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomEndpoint {
+    pub name: String,
+    /// Request URL; `{zone}` is replaced with the URL-encoded IANA zone.
+    pub url: String,
+    /// How to find the timestamp in the response. Empty means the rules used
+    /// for the built-in providers.
+    #[serde(default)]
+    pub rules: Vec<ExtractRule>,
+}
+
+/// A Roughtime server and the long-term key its answers must be signed with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoughtimeServer {
+    /// `host:port` of the UDP endpoint.
+    pub address: String,
+    /// Base64-encoded Ed25519 public key.
+    pub public_key: String,
+}
+
+/// Retry policy for a single provider request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrySettings {
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubled for every further attempt.
+    pub base_delay_ms: u64,
+    /// Fraction each delay may be randomly shifted by.
+    pub jitter_ratio: f64,
+    /// Give up once this much time has passed since the first attempt.
+    pub deadline_ms: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            jitter_ratio: 0.2,
+            deadline_ms: 12_000,
+        }
+    }
+}
+
+/// How large corrections are blended into the displayed time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlewSettings {
+    pub enabled: bool,
+    /// Corrections up to this size are applied at once.
+    pub threshold_millis: i64,
+    /// Time over which a larger correction is spread.
+    pub window_millis: u64,
+}
+
+impl Default for SlewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_millis: 1000,
+            window_millis: 10_000,
+        }
+    }
+}
+
+/// When to warn that the system clock itself is wrong.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkewWarningSettings {
+    /// Offsets larger than this trigger `system-clock-skew`.
+    pub threshold_millis: i64,
+    /// Also show an OS notification the first time the threshold is crossed.
+    pub notify: bool,
+}
+
+impl Default for SkewWarningSettings {
+    fn default() -> Self {
+        Self {
+            threshold_millis: 10_000,
+            notify: false,
+        }
+    }
+}
+
+/// Bounds a provider's answer must fall within to be accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlausibilitySettings {
+    /// Earliest and latest calendar year a provider may report.
+    pub min_year: i32,
+    pub max_year: i32,
+    /// Largest accepted distance from the local clock; `None` accepts any,
+    /// which lets a machine whose clock reset to its BIOS default recover.
+    pub max_deviation_millis: Option<i64>,
+}
+
+impl Default for PlausibilitySettings {
+    fn default() -> Self {
+        Self {
+            min_year: 2024,
+            max_year: 2050,
+            max_deviation_millis: None,
+        }
+    }
+}
+
+/// Retention limits for the sync history log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Entries older than this are pruned.
+    pub max_age_days: u32,
+    /// Oldest entries beyond this count are pruned.
+    pub max_entries: u32,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: 90,
+            max_entries: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Per-request timeout for HTTP time providers.
+    pub timeout_secs: u64,
+    pub proxy: Option<ProxySettings>,
+    /// PEM file with additional trusted root certificates, e.g. a corporate CA.
+    pub ca_bundle_path: Option<PathBuf>,
+    /// URL that answers `204 No Content` when the internet is reachable.
+    pub connectivity_check_url: String,
+    /// Delay between connectivity checks.
+    pub connectivity_check_secs: u64,
+    pub doh: DohSettings,
+    /// Address family tried first when a provider host has both.
+    pub ip_preference: IpPreference,
+    /// Local address outgoing HTTP connections are bound to.
+    pub local_address: Option<IpAddr>,
+    /// Network interface outgoing HTTP connections are bound to; honoured on
+    /// Linux and macOS only.
+    pub interface: Option<String>,
+    /// Providers that only accept servers presenting one of the listed keys.
+    pub certificate_pins: Vec<CertificatePin>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 5,
+            proxy: None,
+            ca_bundle_path: None,
+            connectivity_check_url: "http://connectivitycheck.gstatic.com/generate_204".to_string(),
+            connectivity_check_secs: 15,
+            doh: DohSettings::default(),
+            ip_preference: IpPreference::default(),
+            local_address: None,
+            interface: None,
+            certificate_pins: Vec::new(),
+        }
+    }
+}
+
+/// SPKI pins for an HTTP-based provider, so a TLS-intercepting proxy cannot
+/// answer in its place even with a locally trusted CA.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificatePin {
+    pub provider: ProviderKind,
+    /// Base64 SHA-256 hashes of DER SubjectPublicKeyInfo; a match anywhere
+    /// in the served chain is accepted.
+    pub spki_sha256: Vec<String>,
+}
+
+/// Which address family to connect over first. The other family is still
+/// tried if the preferred one does not connect quickly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpPreference {
+    /// Keep the order the resolver returned.
+    #[default]
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+/// DNS-over-HTTPS resolution for provider hostnames, for networks that
+/// hijack plain DNS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DohSettings {
+    pub server: DohServer,
+    /// HTTP-based providers whose requests resolve hosts over DoH; empty
+    /// disables it.
+    pub providers: Vec<ProviderKind>,
+}
+
+impl Default for DohSettings {
+    fn default() -> Self {
+        Self {
+            server: DohServer::Cloudflare,
+            providers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DohServer {
+    Cloudflare,
+    Google,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// Proxy URL; the scheme selects HTTP, HTTPS or SOCKS5.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// IP-based zone detection. Off by default because the lookup reveals the
+/// public address to the service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeolocationSettings {
+    pub enabled: bool,
+    /// Service returning JSON with a `timezone` field for the caller's IP.
+    pub url: String,
+    /// How long a detected zone is reused before asking the service again.
+    pub cache_secs: u64,
+}
+
+impl Default for GeolocationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "https://ipapi.co/json/".to_string(),
+            cache_secs: 60 * 60,
+        }
+    }
+}
+
+/// Advance warning of daylight saving changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DstNoticeSettings {
+    pub enabled: bool,
+    /// How long before a transition the warning is shown.
+    pub lead_secs: u64,
+    /// IANA zones to watch; empty watches the OS zone.
+    pub time_zones: Vec<String>,
+    /// Also show an OS notification, not just the `dst-transition-upcoming` event.
+    pub notify: bool,
+}
+
+impl Default for DstNoticeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lead_secs: 24 * 60 * 60,
+            time_zones: Vec::new(),
+            notify: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Silences alarm and timer sounds; notifications still show.
+    pub muted: bool,
+}
+
+/// Phase lengths and flow of the Pomodoro cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PomodoroSettings {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// Work sessions per cycle; the break after the last is a long one.
+    pub sessions_before_long_break: u32,
+    /// Start each phase as the previous one ends, instead of waiting for
+    /// `resume_pomodoro`.
+    pub auto_advance: bool,
+    /// Sound played as a phase ends.
+    pub sound: Option<String>,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            sessions_before_long_break: 4,
+            auto_advance: true,
+            sound: Some("chime".to_string()),
+        }
+    }
+}
+
+/// The hourly chime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChimeSettings {
+    pub enabled: bool,
+    /// Also chime on the half hour.
+    pub half_hour: bool,
+    /// Built-in tone or imported sound id, as for alarms.
+    pub sound: String,
+    /// From 0 to 1.
+    pub volume: f32,
+    /// Span of the day without chimes; `None` chimes around the clock.
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for ChimeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            half_hour: false,
+            sound: "chime".to_string(),
+            volume: 0.6,
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+        }
+    }
+}
+
+/// From `start` up to `end` ("HH:MM" on the main clock), running past
+/// midnight when `end` is earlier than `start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// How the app behaves while the OS is in Do Not Disturb.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DndSettings {
+    /// Hold back chimes, non-critical alarms, and notifications meanwhile.
+    pub respect: bool,
+}
+
+impl Default for DndSettings {
+    fn default() -> Self {
+        Self { respect: true }
+    }
+}
+
+/// What happens to the main window when an alarm rings or a timer ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// Show and focus the window, even when hidden to the tray, so the
+    /// dismiss controls are at hand.
+    pub show_window: bool,
+    /// Also flash the taskbar entry or bounce the dock icon.
+    pub request_attention: bool,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            show_window: true,
+            request_attention: true,
+        }
+    }
+}
+
+/// When the app keeps the display on and the system awake.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeLockSettings {
+    /// Hold the wake lock while any timer runs.
+    pub while_timers_run: bool,
+    /// Hold the wake lock while the stopwatch runs.
+    pub while_stopwatch_runs: bool,
+}
+
+impl Default for WakeLockSettings {
+    fn default() -> Self {
+        Self {
+            while_timers_run: true,
+            while_stopwatch_runs: true,
+        }
+    }
+}
+
+/// Global hotkeys, in accelerator syntax such as "CommandOrControl+Alt+S";
+/// `None` leaves the action without one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    /// Starts the stopwatch, or stops it while it runs.
+    pub toggle_stopwatch: Option<String>,
+    pub lap_stopwatch: Option<String>,
+    /// Starts a timer of `timer_minutes`.
+    pub start_timer: Option<String>,
+    pub timer_minutes: u32,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            toggle_stopwatch: None,
+            lap_stopwatch: None,
+            start_timer: None,
+            timer_minutes: 5,
+        }
+    }
+}
+
+/// How the main window is placed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// Move the window to the monitor under the cursor, at the corner or
+    /// position it last had there.
+    pub follow_cursor: bool,
+}
+
+/// An HTTP request sent when an alarm rings, a timer ends, or a Pomodoro
+/// phase ends, for home-automation setups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Webhook {
+    pub url: String,
+    pub method: String,
+    pub events: Vec<WebhookEvent>,
+    /// Body template in which `{{name}}` stands for a variable such as
+    /// `{{label}}`; `None` sends every variable as a JSON object.
+    pub body: Option<String>,
+    /// Also decides escaping: variables in a JSON body are JSON-escaped.
+    pub content_type: String,
+    pub enabled: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            method: "POST".to_string(),
+            events: Vec::new(),
+            body: None,
+            content_type: "application/json".to_string(),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    AlarmFired,
+    TimerFinished,
+    PomodoroPhaseEnded,
+}
+
+/// ICS calendars whose events the clock shows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    pub subscriptions: Vec<CalendarSubscription>,
+    /// How old a fetched calendar may get before it is fetched again.
+    pub refresh_minutes: u64,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            refresh_minutes: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSubscription {
+    pub url: String,
+    /// Shown with the calendar's events; defaults to the URL.
+    pub name: String,
+}
+
+/// Whether the OS is asked to start the app for alarms while it is closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmTaskSettings {
+    pub enabled: bool,
+}
+
+impl Default for AlarmTaskSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Switches for debugging aids that must stay off in normal use.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeveloperSettings {
+    /// Allow `set_debug_offset` to shift the displayed time.
+    pub debug_offset: bool,
+}
+
+/// Settings loaded from `settings.json` in the app config directory.
+pub struct SettingsStore {
+    current: Mutex<Settings>,
+    path: Option<PathBuf>,
+}
+
+impl SettingsStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::config_file(app, SETTINGS_FILE_NAME);
+        let settings = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+
+        Self {
+            current: Mutex::new(settings),
+            path,
+        }
+    }
+
+    /// Applies `change` and writes the result back to `settings.json`. The
+    /// in-memory settings only change if the write succeeds.
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let mut current = self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut updated = current.clone();
+        change(&mut updated);
+        store::write_json(path, &updated)?;
+        *current = updated.clone();
+        Ok(updated)
+    }
+
+    pub fn get(&self) -> Settings {
+        self.current
+            .lock()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+}
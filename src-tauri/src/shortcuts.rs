@@ -0,0 +1,152 @@
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use thiserror::Error;
+
+use crate::{
+    settings::{SettingsStore, ShortcutSettings},
+    stopwatch,
+    store::StoreError,
+    timer,
+};
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+
+#[derive(Debug, Error)]
+pub enum ShortcutError {
+    #[error("invalid shortcut: {0}")]
+    Invalid(String),
+    #[error("shortcut {0} is used twice")]
+    Duplicate(String),
+    #[error("failed to register shortcut {0}")]
+    Unavailable(String),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl ShortcutError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid_shortcut",
+            Self::Duplicate(_) => "duplicate_shortcut",
+            Self::Unavailable(_) => "shortcut_unavailable",
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for ShortcutError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ShortcutError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    ToggleStopwatch,
+    LapStopwatch,
+    StartTimer { minutes: u32 },
+}
+
+#[tauri::command]
+pub fn get_shortcuts(app: AppHandle) -> ShortcutSettings {
+    app.state::<SettingsStore>().get().shortcuts
+}
+
+/// Registers `shortcuts` in place of the current ones and saves them. If
+/// any cannot be registered, for instance because another app holds it,
+/// the previous shortcuts stay in effect.
+#[tauri::command]
+pub fn set_shortcuts(app: AppHandle, shortcuts: ShortcutSettings) -> Result<(), ShortcutError> {
+    bindings(&shortcuts)?;
+    if let Err(err) = apply(&app, &shortcuts) {
+        let previous = app.state::<SettingsStore>().get().shortcuts;
+        if let Err(err) = apply(&app, &previous) {
+            eprintln!("failed to restore global shortcuts: {err}");
+        }
+        return Err(err);
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.shortcuts = shortcuts)?;
+    Ok(())
+}
+
+/// Registers the saved shortcuts at startup.
+pub fn register(app: &AppHandle) {
+    if let Err(err) = apply(app, &app.state::<SettingsStore>().get().shortcuts) {
+        eprintln!("failed to register global shortcuts: {err}");
+    }
+}
+
+/// Drops every registered shortcut, then registers those in `shortcuts`.
+fn apply(app: &AppHandle, shortcuts: &ShortcutSettings) -> Result<(), ShortcutError> {
+    let bindings = bindings(shortcuts)?;
+    let manager = app.global_shortcut();
+    manager
+        .unregister_all()
+        .map_err(|err| ShortcutError::Unavailable(err.to_string()))?;
+    for (text, shortcut, action) in bindings {
+        manager
+            .on_shortcut(shortcut, move |app, _, event| {
+                if event.state() == ShortcutState::Pressed {
+                    run(app, action);
+                }
+            })
+            .map_err(|err| ShortcutError::Unavailable(format!("{text}: {err}")))?;
+    }
+    Ok(())
+}
+
+/// The shortcuts that are set, parsed, with the action each one runs.
+fn bindings(
+    shortcuts: &ShortcutSettings,
+) -> Result<Vec<(String, Shortcut, Action)>, ShortcutError> {
+    let wanted = [
+        (&shortcuts.toggle_stopwatch, Action::ToggleStopwatch),
+        (&shortcuts.lap_stopwatch, Action::LapStopwatch),
+        (
+            &shortcuts.start_timer,
+            Action::StartTimer {
+                minutes: shortcuts.timer_minutes,
+            },
+        ),
+    ];
+    let mut bindings: Vec<(String, Shortcut, Action)> = Vec::new();
+    for (text, action) in wanted {
+        let Some(text) = text
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        else {
+            continue;
+        };
+        let shortcut: Shortcut = text
+            .parse()
+            .map_err(|_| ShortcutError::Invalid(text.to_string()))?;
+        if bindings.iter().any(|(_, bound, _)| *bound == shortcut) {
+            return Err(ShortcutError::Duplicate(text.to_string()));
+        }
+        bindings.push((text.to_string(), shortcut, action));
+    }
+    Ok(bindings)
+}
+
+fn run(app: &AppHandle, action: Action) {
+    match action {
+        Action::ToggleStopwatch => {
+            stopwatch::toggle(app);
+        }
+        Action::LapStopwatch => {
+            stopwatch::lap_stopwatch(app.clone(), app.state());
+        }
+        Action::StartTimer { minutes } => {
+            let duration_millis = i64::from(minutes) * MILLIS_PER_MINUTE;
+            if let Err(err) = timer::start_timer(app.clone(), app.state(), duration_millis, None) {
+                eprintln!("failed to start timer from shortcut: {err}");
+            }
+        }
+    }
+}
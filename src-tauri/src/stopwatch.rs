@@ -0,0 +1,272 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    settings::SettingsStore,
+    store::{self, StoreError},
+    sync::{clock, history::ExportFormat, state::TimeState},
+    wakelock,
+};
+
+pub const EVENT_STOPWATCH_CHANGED: &str = "stopwatch-changed";
+
+const STOPWATCH_FILE_NAME: &str = "stopwatch.json";
+const LAPS_CSV_HEADER: &str = "lap,lap_micros,split_micros";
+
+/// The stopwatch, timed with `Instant` in the backend: timers in a hidden
+/// webview are throttled, and the display would drift while hidden.
+/// Saved to `stopwatch.json` in the app data directory on every change.
+pub struct StopwatchStore {
+    current: Mutex<Stopwatch>,
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+struct Stopwatch {
+    /// Elapsed time of the runs before the current one.
+    accumulated: Duration,
+    /// Start of the current run; `None` while stopped.
+    running_since: Option<Instant>,
+    /// Elapsed time at each lap, in order.
+    splits: Vec<Duration>,
+}
+
+impl Stopwatch {
+    fn elapsed(&self) -> Duration {
+        self.accumulated
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    fn snapshot(&self) -> StopwatchSnapshot {
+        let mut previous = Duration::ZERO;
+        let laps = self
+            .splits
+            .iter()
+            .enumerate()
+            .map(|(index, split)| {
+                let lap = Lap {
+                    index: index + 1,
+                    lap_micros: micros(*split - previous),
+                    split_micros: micros(*split),
+                };
+                previous = *split;
+                lap
+            })
+            .collect();
+        StopwatchSnapshot {
+            running: self.running_since.is_some(),
+            elapsed_micros: micros(self.elapsed()),
+            monotonic_anchor_millis: clock::monotonic_millis(),
+            laps,
+        }
+    }
+}
+
+/// The stopwatch as the frontend sees it. Also the payload of the
+/// `stopwatch-changed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct StopwatchSnapshot {
+    running: bool,
+    elapsed_micros: u64,
+    /// `monotonic_now` when `elapsed_micros` was read, so a running
+    /// stopwatch can be extrapolated between events.
+    monotonic_anchor_millis: i64,
+    laps: Vec<Lap>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Lap {
+    /// 1-based.
+    index: usize,
+    /// Time since the previous lap.
+    lap_micros: u64,
+    /// Time since the start.
+    split_micros: u64,
+}
+
+/// The stopwatch as saved to disk. A running stopwatch keeps its start as
+/// a wall-clock instant, since `Instant`s do not survive a restart.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedStopwatch {
+    accumulated_micros: u64,
+    running_since_epoch_millis: Option<i64>,
+    split_micros: Vec<u64>,
+}
+
+impl StopwatchStore {
+    /// Reads the saved stopwatch. A stopwatch that was running when the app
+    /// quit has kept running, measured by the synced clock, so it needs
+    /// `TimeState`.
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, STOPWATCH_FILE_NAME);
+        let saved: SavedStopwatch = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        let now_epoch_millis = app.state::<TimeState>().now_millis();
+        let mut accumulated = Duration::from_micros(saved.accumulated_micros);
+        if let Some(since) = saved.running_since_epoch_millis {
+            let away = u64::try_from(now_epoch_millis - since).unwrap_or(0);
+            accumulated += Duration::from_millis(away);
+            keep_awake(app, true);
+        }
+        Self {
+            current: Mutex::new(Stopwatch {
+                accumulated,
+                running_since: saved.running_since_epoch_millis.map(|_| Instant::now()),
+                splits: saved
+                    .split_micros
+                    .into_iter()
+                    .map(Duration::from_micros)
+                    .collect(),
+            }),
+            path,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.with(|stopwatch| stopwatch.running_since.is_some())
+    }
+
+    fn with<T>(&self, change: impl FnOnce(&mut Stopwatch) -> T) -> T {
+        change(&mut self.current.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn save(&self, now_epoch_millis: i64) -> Result<(), StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let saved = self.with(|stopwatch| {
+            let running_for = stopwatch.running_since.map(|since| since.elapsed());
+            SavedStopwatch {
+                accumulated_micros: micros(stopwatch.accumulated),
+                running_since_epoch_millis: running_for.map(|running_for| {
+                    now_epoch_millis - i64::try_from(running_for.as_millis()).unwrap_or(0)
+                }),
+                split_micros: stopwatch.splits.iter().copied().map(micros).collect(),
+            }
+        });
+        store::write_json(path, &saved)
+    }
+}
+
+#[tauri::command]
+pub fn get_stopwatch(stopwatch: State<'_, StopwatchStore>) -> StopwatchSnapshot {
+    stopwatch.with(|stopwatch| stopwatch.snapshot())
+}
+
+/// Starts or continues the stopwatch.
+#[tauri::command]
+pub fn start_stopwatch(app: AppHandle, stopwatch: State<'_, StopwatchStore>) -> StopwatchSnapshot {
+    change(&app, &stopwatch, |stopwatch| {
+        stopwatch.running_since.get_or_insert_with(Instant::now);
+    })
+}
+
+#[tauri::command]
+pub fn stop_stopwatch(app: AppHandle, stopwatch: State<'_, StopwatchStore>) -> StopwatchSnapshot {
+    change(&app, &stopwatch, |stopwatch| {
+        if let Some(since) = stopwatch.running_since.take() {
+            stopwatch.accumulated += since.elapsed();
+        }
+    })
+}
+
+/// Starts the stopwatch while it is stopped and stops it while it runs.
+pub fn toggle(app: &AppHandle) -> StopwatchSnapshot {
+    change(app, &app.state(), |stopwatch| {
+        match stopwatch.running_since.take() {
+            Some(since) => stopwatch.accumulated += since.elapsed(),
+            None => stopwatch.running_since = Some(Instant::now()),
+        }
+    })
+}
+
+/// Records a lap at the current elapsed time; works while stopped too.
+#[tauri::command]
+pub fn lap_stopwatch(app: AppHandle, stopwatch: State<'_, StopwatchStore>) -> StopwatchSnapshot {
+    change(&app, &stopwatch, |stopwatch| {
+        let split = stopwatch.elapsed();
+        stopwatch.splits.push(split);
+    })
+}
+
+/// Stops the stopwatch, sets it to zero, and clears the laps.
+#[tauri::command]
+pub fn reset_stopwatch(app: AppHandle, stopwatch: State<'_, StopwatchStore>) -> StopwatchSnapshot {
+    change(&app, &stopwatch, |stopwatch| {
+        *stopwatch = Stopwatch::default()
+    })
+}
+
+/// Writes the laps to `path` and returns how many were exported.
+#[tauri::command]
+pub fn export_laps(
+    stopwatch: State<'_, StopwatchStore>,
+    path: PathBuf,
+    format: ExportFormat,
+) -> Result<usize, StoreError> {
+    let laps = stopwatch.with(|stopwatch| stopwatch.snapshot().laps);
+    let encoded = match format {
+        ExportFormat::Csv => encode_csv(&laps).into_bytes(),
+        ExportFormat::Json => serde_json::to_vec_pretty(&laps)?,
+    };
+    fs::write(&path, encoded)?;
+    Ok(laps.len())
+}
+
+fn change(
+    app: &AppHandle,
+    stopwatch: &StopwatchStore,
+    change: impl FnOnce(&mut Stopwatch),
+) -> StopwatchSnapshot {
+    let snapshot = stopwatch.with(|stopwatch| {
+        change(stopwatch);
+        stopwatch.snapshot()
+    });
+    if let Err(err) = stopwatch.save(app.state::<TimeState>().now_millis()) {
+        eprintln!("failed to save stopwatch: {err}");
+    }
+    if let Err(err) = app.emit(EVENT_STOPWATCH_CHANGED, &snapshot) {
+        eprintln!("failed to emit {EVENT_STOPWATCH_CHANGED}: {err}");
+    }
+    keep_awake(app, snapshot.running);
+    snapshot
+}
+
+/// Holds the wake lock while the stopwatch runs, unless turned off in the
+/// settings.
+pub fn keep_awake(app: &AppHandle, running: bool) {
+    let reason = (running
+        && app
+            .state::<SettingsStore>()
+            .get()
+            .wake_lock
+            .while_stopwatch_runs)
+        .then(|| "The stopwatch is running".to_string());
+    if let Err(err) = wakelock::set(app, wakelock::HOLDER_STOPWATCH, reason) {
+        eprintln!("failed to update the wake lock for the stopwatch: {err}");
+    }
+}
+
+fn encode_csv(laps: &[Lap]) -> String {
+    let mut csv = String::from(LAPS_CSV_HEADER);
+    csv.push('\n');
+    for lap in laps {
+        let _ = writeln!(csv, "{},{},{}", lap.index, lap.lap_micros, lap.split_micros);
+    }
+    csv
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
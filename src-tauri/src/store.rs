@@ -0,0 +1,86 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Serialize, Serializer, de::DeserializeOwned, ser::SerializeStruct};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("storage directory unavailable")]
+    NoDirectory,
+    #[error("failed to write file: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to encode file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl StoreError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoDirectory => "no_directory",
+            Self::Io(_) => "io",
+            Self::Json(_) => "json",
+        }
+    }
+}
+
+impl Serialize for StoreError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("StoreError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Path of `name` inside the app config directory (user-editable settings).
+pub fn config_file(app: &AppHandle, name: &str) -> Option<PathBuf> {
+    app.path().app_config_dir().ok().map(|dir| dir.join(name))
+}
+
+/// Path of `name` inside the app data directory (state the app maintains).
+pub fn data_file(app: &AppHandle, name: &str) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(name))
+}
+
+/// Reads a JSON file, treating a missing or malformed file as absent.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let raw = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&raw)
+        .inspect_err(|err| eprintln!("ignoring invalid {}: {err}", path.display()))
+        .ok()
+}
+
+/// Applies `change` to a copy of the value behind `lock`, saves the copy to
+/// `path`, and only then puts it in place, holding the lock throughout so
+/// saves land in order. When `change` or the save fails, nothing is saved
+/// and the value is left as it was.
+pub fn update_locked<T: Clone + Serialize, R, E: From<StoreError>>(
+    path: Option<&Path>,
+    lock: &Mutex<T>,
+    change: impl FnOnce(&mut T) -> Result<R, E>,
+) -> Result<R, E> {
+    let path = path.ok_or(StoreError::NoDirectory)?;
+    let mut value = lock.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut next = value.clone();
+    let result = change(&mut next)?;
+    write_json(path, &next)?;
+    *value = next;
+    Ok(result)
+}
+
+/// Writes `value` as pretty JSON, replacing the file atomically.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StoreError> {
+    let parent = path.parent().ok_or(StoreError::NoDirectory)?;
+    fs::create_dir_all(parent)?;
+
+    let encoded = serde_json::to_vec_pretty(value)?;
+    let staging = path.with_extension("json.tmp");
+    fs::write(&staging, encoded)?;
+    fs::rename(&staging, path)?;
+    Ok(())
+}
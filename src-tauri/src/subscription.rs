@@ -0,0 +1,533 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use crate::{
+    alarm::recurrence::Recurrence,
+    ical::{self, Component, Property},
+    net::SharedClient,
+    settings::{CalendarSubscription, SettingsStore},
+    store::{self, StoreError},
+    sync::{TimeSyncError, state::TimeState},
+    zone::active::active_tz,
+};
+
+pub const EVENT_NEXT_EVENT_CHANGED: &str = "next-event-changed";
+
+const CACHE_FILE_NAME: &str = "calendar_cache.json";
+/// How often the next event is recomputed and stale calendars refetched.
+const CHECK_PERIOD: Duration = Duration::from_secs(15);
+/// Wait after a failed fetch before trying that calendar again.
+const RETRY_AFTER_MILLIS: i64 = 5 * 60 * 1000;
+/// Excluded instances skipped in a row before a recurring event is taken
+/// to have no further occurrences.
+const MAX_EXCLUDED_IN_A_ROW: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    #[error("unsupported calendar url: {0}")]
+    InvalidUrl(String),
+    #[error("no calendar subscription for {0}")]
+    NotFound(String),
+    #[error("invalid calendar: {0}")]
+    InvalidCalendar(String),
+    #[error("failed to fetch calendar: {0}")]
+    Fetch(#[from] reqwest::Error),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl CalendarError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidUrl(_) => "invalid_calendar_url",
+            Self::NotFound(_) => "calendar_not_found",
+            Self::InvalidCalendar(_) => "invalid_calendar",
+            Self::Fetch(_) => "calendar_fetch_failed",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for CalendarError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CalendarError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// One occurrence of a calendar event, as `get_next_events` lists it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarEvent {
+    /// Name of the subscription the event comes from.
+    calendar: String,
+    summary: String,
+    location: Option<String>,
+    start_epoch_millis: i64,
+    end_epoch_millis: i64,
+    all_day: bool,
+}
+
+/// A subscription with the state of its last fetch.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStatus {
+    url: String,
+    name: String,
+    /// Corrected time of the last successful fetch.
+    fetched_epoch_millis: Option<i64>,
+    event_count: usize,
+    /// Why the last fetch failed; the cached events stay in use meanwhile.
+    error: Option<String>,
+}
+
+/// A calendar body as last fetched, saved to `calendar_cache.json` so the
+/// events are there at startup and while offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFeed {
+    body: String,
+    fetched_epoch_millis: i64,
+}
+
+#[derive(Default)]
+struct Feed {
+    cached: Option<CachedFeed>,
+    events: Vec<FeedEvent>,
+    attempted_epoch_millis: Option<i64>,
+    error: Option<String>,
+}
+
+/// A VEVENT reduced to what is needed to list its occurrences.
+struct FeedEvent {
+    summary: String,
+    location: Option<String>,
+    /// The first occurrence, in the zone its recurrence is evaluated in.
+    start: DateTime<Tz>,
+    duration: TimeDelta,
+    all_day: bool,
+    /// `None` for single events and rules the app cannot evaluate, which
+    /// keep only their first occurrence.
+    recurrence: Option<Recurrence>,
+    /// Starts of instances removed by EXDATE or replaced by an override.
+    excluded: Vec<i64>,
+}
+
+impl FeedEvent {
+    /// Reads `event`; `overridden` maps UIDs to the instances other VEVENTs
+    /// with a RECURRENCE-ID replace. Cancelled and unreadable events give
+    /// `None`.
+    fn from_component(
+        event: &Component,
+        floating: Tz,
+        overridden: &HashMap<String, Vec<i64>>,
+    ) -> Option<Self> {
+        if event
+            .property("STATUS")
+            .is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED"))
+        {
+            return None;
+        }
+        let start_property = event.property("DTSTART")?;
+        let start = start_property.date_time(floating).ok()?;
+        let all_day = !start_property.value.contains('T');
+        let duration = match (event.property("DTEND"), event.property("DURATION")) {
+            (Some(end), _) => end.date_time(floating).ok()? - start,
+            (None, Some(duration)) => ical::parse_duration(&duration.value)?,
+            (None, None) if all_day => TimeDelta::days(1),
+            (None, None) => TimeDelta::zero(),
+        };
+
+        let is_override = event.property("RECURRENCE-ID").is_some();
+        let recurrence = event
+            .property("RRULE")
+            .filter(|_| !is_override)
+            .map(|rule| Recurrence::Rrule {
+                rule: rule.value.clone(),
+                start: Some(start.date_naive().format("%Y-%m-%d").to_string()),
+            })
+            .filter(|recurrence| recurrence.validate().is_ok());
+        let mut excluded: Vec<i64> = event
+            .properties
+            .iter()
+            .filter(|property| property.name == "EXDATE")
+            .flat_map(|exdate| {
+                exdate.value.split(',').filter_map(|value| {
+                    let single = Property {
+                        name: exdate.name.clone(),
+                        params: exdate.params.clone(),
+                        value: value.to_string(),
+                    };
+                    single.date_time(floating).ok()
+                })
+            })
+            .map(|instance| instance.timestamp_millis())
+            .collect();
+        if !is_override
+            && let Some(uid) = event.property("UID")
+            && let Some(instances) = overridden.get(&uid.value)
+        {
+            excluded.extend(instances);
+        }
+
+        Some(Self {
+            summary: event
+                .property("SUMMARY")
+                .map(|summary| ical::unescape(&summary.value))
+                .unwrap_or_default(),
+            location: event
+                .property("LOCATION")
+                .map(|location| ical::unescape(&location.value))
+                .filter(|location| !location.is_empty()),
+            start,
+            duration,
+            all_day,
+            recurrence,
+            excluded,
+        })
+    }
+
+    /// Start of the first occurrence after `after_millis`.
+    fn next_start(&self, after_millis: i64) -> Option<i64> {
+        let first = self.start.timestamp_millis();
+        let Some(recurrence) = &self.recurrence else {
+            return (first > after_millis).then_some(first);
+        };
+        let mut after = after_millis;
+        for _ in 0..MAX_EXCLUDED_IN_A_ROW {
+            let next = recurrence.next_fire(self.start.timezone(), self.start.time(), after)?;
+            if !self.excluded.contains(&next) {
+                return Some(next);
+            }
+            after = next;
+        }
+        None
+    }
+}
+
+/// Fetched calendars by URL, with the event last announced as next.
+pub struct SubscriptionStore {
+    feeds: Mutex<BTreeMap<String, Feed>>,
+    next: Mutex<Option<CalendarEvent>>,
+    path: Option<PathBuf>,
+}
+
+impl SubscriptionStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, CACHE_FILE_NAME);
+        let cached: BTreeMap<String, CachedFeed> = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        let floating = active_tz(app).unwrap_or(Tz::UTC);
+        let feeds = cached
+            .into_iter()
+            .map(|(url, cached)| {
+                let events = parse_feed(&cached.body, floating).unwrap_or_default();
+                let feed = Feed {
+                    cached: Some(cached),
+                    events,
+                    ..Feed::default()
+                };
+                (url, feed)
+            })
+            .collect();
+        Self {
+            feeds: Mutex::new(feeds),
+            next: Mutex::new(None),
+            path,
+        }
+    }
+
+    fn with_feed<T>(&self, url: &str, change: impl FnOnce(&mut Feed) -> T) -> T {
+        let mut feeds = self.feeds.lock().unwrap_or_else(PoisonError::into_inner);
+        change(feeds.entry(url.to_string()).or_default())
+    }
+
+    /// Keeps the feeds of `subscriptions` and saves the cache.
+    fn save(&self, subscriptions: &[CalendarSubscription]) -> Result<(), StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let mut feeds = self.feeds.lock().unwrap_or_else(PoisonError::into_inner);
+        feeds.retain(|url, _| {
+            subscriptions
+                .iter()
+                .any(|subscription| subscription.url == *url)
+        });
+        let cached: BTreeMap<&String, &CachedFeed> = feeds
+            .iter()
+            .filter_map(|(url, feed)| Some((url, feed.cached.as_ref()?)))
+            .collect();
+        store::write_json(path, &cached)
+    }
+}
+
+#[tauri::command]
+pub fn list_calendar_subscriptions(app: AppHandle) -> Vec<SubscriptionStatus> {
+    let subscriptions = app.state::<SettingsStore>().get().calendars.subscriptions;
+    let store = app.state::<SubscriptionStore>();
+    let feeds = store.feeds.lock().unwrap_or_else(PoisonError::into_inner);
+    subscriptions
+        .into_iter()
+        .map(|subscription| {
+            let feed = feeds.get(&subscription.url);
+            SubscriptionStatus {
+                fetched_epoch_millis: feed
+                    .and_then(|feed| feed.cached.as_ref())
+                    .map(|cached| cached.fetched_epoch_millis),
+                event_count: feed.map_or(0, |feed| feed.events.len()),
+                error: feed.and_then(|feed| feed.error.clone()),
+                url: subscription.url,
+                name: subscription.name,
+            }
+        })
+        .collect()
+}
+
+/// Subscribes to the ICS calendar at `url` (http, https, or webcal) and
+/// fetches it right away. A failed first fetch is reported but keeps the
+/// subscription, to be retried in the background.
+#[tauri::command]
+pub async fn add_calendar_subscription(
+    app: AppHandle,
+    url: String,
+    name: Option<String>,
+) -> Result<(), CalendarError> {
+    let url = url.trim().to_string();
+    http_url(&url)?;
+    let name = name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| url.clone());
+    app.state::<SettingsStore>().update(|settings| {
+        let subscriptions = &mut settings.calendars.subscriptions;
+        match subscriptions
+            .iter_mut()
+            .find(|existing| existing.url == url)
+        {
+            Some(existing) => existing.name = name,
+            None => subscriptions.push(CalendarSubscription {
+                url: url.clone(),
+                name,
+            }),
+        }
+    })?;
+    let result = refresh(&app, &url).await;
+    publish_next(&app);
+    result
+}
+
+#[tauri::command]
+pub fn remove_calendar_subscription(app: AppHandle, url: String) -> Result<(), CalendarError> {
+    let settings = app.state::<SettingsStore>();
+    if !settings
+        .get()
+        .calendars
+        .subscriptions
+        .iter()
+        .any(|subscription| subscription.url == url)
+    {
+        return Err(CalendarError::NotFound(url));
+    }
+    let updated = settings.update(|settings| {
+        settings
+            .calendars
+            .subscriptions
+            .retain(|subscription| subscription.url != url)
+    })?;
+    app.state::<SubscriptionStore>()
+        .save(&updated.calendars.subscriptions)?;
+    publish_next(&app);
+    Ok(())
+}
+
+/// Fetches every subscribed calendar now. Returns the first failure after
+/// trying them all.
+#[tauri::command]
+pub async fn refresh_calendars(app: AppHandle) -> Result<(), CalendarError> {
+    let subscriptions = app.state::<SettingsStore>().get().calendars.subscriptions;
+    let mut first_error = None;
+    for subscription in subscriptions {
+        if let Err(err) = refresh(&app, &subscription.url).await {
+            first_error.get_or_insert(err);
+        }
+    }
+    publish_next(&app);
+    first_error.map_or(Ok(()), Err)
+}
+
+/// The next `count` events to start across all subscriptions, soonest
+/// first.
+#[tauri::command]
+pub fn get_next_events(app: AppHandle, count: usize) -> Vec<CalendarEvent> {
+    let mut events = upcoming(&app);
+    events.truncate(count);
+    events
+}
+
+/// Refetches calendars older than `calendars.refresh_minutes` and emits
+/// `next-event-changed` whenever the next timed event changes, including
+/// when it starts and the one after takes its place.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = app.state::<SettingsStore>().get().calendars;
+            let now_millis = app.state::<TimeState>().now_millis();
+            let refresh_millis =
+                i64::try_from(settings.refresh_minutes.saturating_mul(60_000)).unwrap_or(i64::MAX);
+            for subscription in settings.subscriptions {
+                let stale = app
+                    .state::<SubscriptionStore>()
+                    .with_feed(&subscription.url, |feed| {
+                        let fetched = feed
+                            .cached
+                            .as_ref()
+                            .map(|cached| cached.fetched_epoch_millis);
+                        fetched.is_none_or(|at| now_millis - at >= refresh_millis)
+                            && feed
+                                .attempted_epoch_millis
+                                .is_none_or(|at| now_millis - at >= RETRY_AFTER_MILLIS)
+                    });
+                if stale && let Err(err) = refresh(&app, &subscription.url).await {
+                    eprintln!("failed to refresh calendar {}: {err}", subscription.url);
+                }
+            }
+            publish_next(&app);
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+/// Fetches the calendar at `url` into the store. On failure the previous
+/// events are kept and the error is recorded for `list_calendar_subscriptions`.
+async fn refresh(app: &AppHandle, url: &str) -> Result<(), CalendarError> {
+    let store = app.state::<SubscriptionStore>();
+    let attempted = app.state::<TimeState>().now_millis();
+    store.with_feed(url, |feed| feed.attempted_epoch_millis = Some(attempted));
+    let result = async {
+        let body = fetch(app, url).await?;
+        let events = parse_feed(&body, active_tz(app)?)?;
+        Ok::<_, CalendarError>((body, events))
+    }
+    .await;
+    match result {
+        Ok((body, events)) => {
+            store.with_feed(url, |feed| {
+                feed.cached = Some(CachedFeed {
+                    body,
+                    fetched_epoch_millis: attempted,
+                });
+                feed.events = events;
+                feed.error = None;
+            });
+            let subscriptions = app.state::<SettingsStore>().get().calendars.subscriptions;
+            store.save(&subscriptions)?;
+            Ok(())
+        }
+        Err(err) => {
+            store.with_feed(url, |feed| feed.error = Some(err.to_string()));
+            Err(err)
+        }
+    }
+}
+
+async fn fetch(app: &AppHandle, url: &str) -> Result<String, CalendarError> {
+    let client = app.state::<SharedClient>().get();
+    Ok(client
+        .get(http_url(url)?)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// `url` with `webcal://` read as `https://`, rejecting other schemes.
+fn http_url(url: &str) -> Result<reqwest::Url, CalendarError> {
+    let invalid = || CalendarError::InvalidUrl(url.to_string());
+    let mut parsed = reqwest::Url::parse(url).map_err(|_| invalid())?;
+    match parsed.scheme() {
+        "http" | "https" => {}
+        "webcal" | "webcals" => parsed.set_scheme("https").map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    }
+    Ok(parsed)
+}
+
+/// The events of an ICS body. Floating times are read in `floating`.
+fn parse_feed(body: &str, floating: Tz) -> Result<Vec<FeedEvent>, CalendarError> {
+    let components = ical::parse(body).map_err(CalendarError::InvalidCalendar)?;
+    let events: Vec<&Component> = ical::events(&components).collect();
+    let mut overridden: HashMap<String, Vec<i64>> = HashMap::new();
+    for event in &events {
+        if let (Some(uid), Some(instance)) =
+            (event.property("UID"), event.property("RECURRENCE-ID"))
+            && let Ok(instance) = instance.date_time(floating)
+        {
+            overridden
+                .entry(uid.value.clone())
+                .or_default()
+                .push(instance.timestamp_millis());
+        }
+    }
+    Ok(events
+        .into_iter()
+        .filter_map(|event| FeedEvent::from_component(event, floating, &overridden))
+        .collect())
+}
+
+/// Every subscribed event that has yet to start, soonest first, with one
+/// entry per event for its next occurrence.
+fn upcoming(app: &AppHandle) -> Vec<CalendarEvent> {
+    let subscriptions = app.state::<SettingsStore>().get().calendars.subscriptions;
+    let now_millis = app.state::<TimeState>().now_millis();
+    let store = app.state::<SubscriptionStore>();
+    let feeds = store.feeds.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut events: Vec<CalendarEvent> = subscriptions
+        .iter()
+        .filter_map(|subscription| Some((subscription, feeds.get(&subscription.url)?)))
+        .flat_map(|(subscription, feed)| {
+            feed.events.iter().filter_map(move |event| {
+                let start = event.next_start(now_millis)?;
+                Some(CalendarEvent {
+                    calendar: subscription.name.clone(),
+                    summary: event.summary.clone(),
+                    location: event.location.clone(),
+                    start_epoch_millis: start,
+                    end_epoch_millis: start + event.duration.num_milliseconds(),
+                    all_day: event.all_day,
+                })
+            })
+        })
+        .collect();
+    events.sort_by_key(|event| event.start_epoch_millis);
+    events
+}
+
+/// Emits `next-event-changed` with the next event that is not all-day, or
+/// `None` when there is none, if it differs from the last one emitted.
+fn publish_next(app: &AppHandle) {
+    let next = upcoming(app).into_iter().find(|event| !event.all_day);
+    let store = app.state::<SubscriptionStore>();
+    let mut last = store.next.lock().unwrap_or_else(PoisonError::into_inner);
+    if *last == next {
+        return;
+    }
+    last.clone_from(&next);
+    drop(last);
+    if let Err(err) = app.emit(EVENT_NEXT_EVENT_CHANGED, next) {
+        eprintln!("failed to emit {EVENT_NEXT_EVENT_CHANGED}: {err}");
+    }
+}
@@ -0,0 +1,100 @@
+use futures::future::join_all;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::{
+    DEFAULT_PROVIDER_PRIORITY, DEFAULT_TIME_ZONE, provider_context,
+    source::{ProviderContext, ProviderKind, TimeProvider, TimeSource},
+    state::TimeState,
+};
+
+const DEFAULT_ROUNDS: u32 = 5;
+const MAX_ROUNDS: u32 = 20;
+
+/// Latency and reliability of one provider over several probes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderBenchmark {
+    provider: ProviderKind,
+    rounds: u32,
+    failures: u32,
+    failure_rate: f64,
+    median_rtt_millis: Option<i64>,
+    /// Standard deviation of the round-trip time.
+    jitter_millis: Option<f64>,
+}
+
+/// Probes every configured provider `rounds` times and reports how each one
+/// performed, best first. The order also becomes the built-in priority for
+/// later syncs unless `sync.priority` overrides it.
+#[tauri::command]
+pub async fn benchmark_time_sources(app: AppHandle, rounds: Option<u32>) -> Vec<ProviderBenchmark> {
+    let rounds = rounds.unwrap_or(DEFAULT_ROUNDS).clamp(1, MAX_ROUNDS);
+    let context = provider_context(&app, DEFAULT_TIME_ZONE);
+
+    let mut kinds: Vec<ProviderKind> = DEFAULT_PROVIDER_PRIORITY
+        .into_iter()
+        .filter(|kind| *kind != ProviderKind::System)
+        .collect();
+    if !context.settings.roughtime_servers.is_empty() {
+        kinds.push(ProviderKind::Roughtime);
+    }
+    if !context.settings.custom_endpoints.is_empty() {
+        kinds.push(ProviderKind::Custom);
+    }
+    kinds.retain(|kind| !context.settings.disabled_providers.contains(kind));
+
+    let mut results = join_all(kinds.iter().map(|kind| probe(*kind, &context, rounds))).await;
+    results.sort_by(|a, b| {
+        a.failure_rate.total_cmp(&b.failure_rate).then(
+            a.median_rtt_millis
+                .unwrap_or(i64::MAX)
+                .cmp(&b.median_rtt_millis.unwrap_or(i64::MAX)),
+        )
+    });
+
+    let ranking = results
+        .iter()
+        .filter(|result| result.failures < result.rounds)
+        .map(|result| result.provider)
+        .collect();
+    app.state::<TimeState>().set_ranking(ranking);
+    results
+}
+
+/// Queries one provider sequentially so its probes do not compete.
+async fn probe(kind: ProviderKind, context: &ProviderContext, rounds: u32) -> ProviderBenchmark {
+    let provider = TimeProvider::new(kind, context);
+    let mut rtts = Vec::with_capacity(rounds as usize);
+    let mut failures = 0;
+    for _ in 0..rounds {
+        match provider.now().await {
+            Ok(sample) => rtts.push(sample.rtt_millis),
+            Err(err) => {
+                eprintln!("benchmark of {kind:?} failed: {err}");
+                failures += 1;
+            }
+        }
+    }
+
+    rtts.sort_unstable();
+    let median_rtt_millis = rtts.get(rtts.len() / 2).copied();
+    let jitter_millis = (!rtts.is_empty()).then(|| {
+        let count = rtts.len() as f64;
+        let mean = rtts.iter().sum::<i64>() as f64 / count;
+        let variance = rtts
+            .iter()
+            .map(|rtt| (*rtt as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        variance.sqrt()
+    });
+
+    ProviderBenchmark {
+        provider: kind,
+        rounds,
+        failures,
+        failure_rate: f64::from(failures) / f64::from(rounds),
+        median_rtt_millis,
+        jitter_millis,
+    }
+}
@@ -0,0 +1,44 @@
+use std::{sync::LazyLock, time::Instant};
+
+static PROCESS_START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Milliseconds elapsed on the monotonic clock since the process started.
+///
+/// Unlike the wall clock this never jumps when the system time is adjusted,
+/// so it is the reference the frontend should extrapolate display time from.
+pub fn monotonic_millis() -> i64 {
+    i64::try_from(PROCESS_START.elapsed().as_millis()).unwrap_or(i64::MAX)
+}
+
+/// Milliseconds on a clock that, unlike `monotonic_millis`, keeps counting
+/// while the machine is suspended. Only its differences are meaningful.
+///
+/// Windows needs no counterpart: its monotonic clock already runs on through
+/// sleep.
+#[cfg(not(windows))]
+pub fn boot_millis() -> i64 {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const CLOCK: libc::clockid_t = libc::CLOCK_BOOTTIME;
+    // Apple's CLOCK_MONOTONIC includes time asleep, unlike `Instant`.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC;
+
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(CLOCK, &mut now) } != 0 {
+        return monotonic_millis();
+    }
+    i64::from(now.tv_sec) * 1000 + i64::from(now.tv_nsec) / 1_000_000
+}
+
+/// Pins the monotonic epoch to the earliest possible moment.
+pub fn init() {
+    LazyLock::force(&PROCESS_START);
+}
+
+#[tauri::command]
+pub fn monotonic_now() -> i64 {
+    monotonic_millis()
+}
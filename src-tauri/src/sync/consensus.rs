@@ -0,0 +1,91 @@
+use futures::future::join_all;
+
+use super::{
+    TimeSyncError, plausibility,
+    source::{ProviderContext, ProviderKind, SyncSample, TimeProvider},
+};
+
+/// Samples within this distance of the median are always accepted, so that a
+/// handful of well-agreeing providers are not discarded for millisecond noise.
+const MIN_OUTLIER_THRESHOLD_MILLIS: i64 = 250;
+/// Multiple of the median absolute deviation beyond which a sample is an outlier.
+const OUTLIER_MAD_FACTOR: i64 = 3;
+
+pub struct ConsensusOutcome {
+    pub sample: SyncSample,
+    /// Largest distance between an accepted sample and the median offset.
+    pub dispersion_millis: i64,
+    pub accepted: usize,
+}
+
+/// Queries every provider concurrently and returns the median offset of the
+/// samples that survive outlier rejection.
+pub async fn query_consensus(
+    kinds: &[ProviderKind],
+    context: &ProviderContext,
+) -> Result<ConsensusOutcome, TimeSyncError> {
+    let providers: Vec<TimeProvider> = kinds
+        .iter()
+        .filter(|kind| **kind != ProviderKind::System)
+        .map(|kind| TimeProvider::new(*kind, context))
+        .collect();
+
+    let results = join_all(providers.iter().map(|provider| provider.query(context))).await;
+    let samples: Vec<SyncSample> = results
+        .into_iter()
+        .filter_map(|result| {
+            result
+                .and_then(|sample| plausibility::check(&sample, context).map(|()| sample))
+                .inspect_err(|err| eprintln!("consensus source failed: {err}"))
+                .ok()
+        })
+        .collect();
+
+    combine(samples)
+        .ok_or_else(|| TimeSyncError::Network("no consensus time source succeeded".to_string()))
+}
+
+fn combine(mut samples: Vec<SyncSample>) -> Option<ConsensusOutcome> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let center = median(samples.iter().map(|sample| sample.offset_millis).collect());
+    let mad = median(
+        samples
+            .iter()
+            .map(|sample| (sample.offset_millis - center).abs())
+            .collect(),
+    );
+    let threshold = (mad * OUTLIER_MAD_FACTOR).max(MIN_OUTLIER_THRESHOLD_MILLIS);
+    samples.retain(|sample| (sample.offset_millis - center).abs() <= threshold);
+
+    let offset_millis = median(samples.iter().map(|sample| sample.offset_millis).collect());
+    let dispersion_millis = samples
+        .iter()
+        .map(|sample| (sample.offset_millis - offset_millis).abs())
+        .max()
+        .unwrap_or(0);
+    let representative = samples
+        .iter()
+        .min_by_key(|sample| (sample.offset_millis - offset_millis).abs())?;
+
+    Some(ConsensusOutcome {
+        sample: SyncSample {
+            offset_millis,
+            ..*representative
+        },
+        dispersion_millis,
+        accepted: samples.len(),
+    })
+}
+
+fn median(mut values: Vec<i64>) -> i64 {
+    values.sort_unstable();
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2
+    } else {
+        values[middle]
+    }
+}
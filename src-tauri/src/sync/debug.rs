@@ -0,0 +1,67 @@
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager, State};
+use thiserror::Error;
+
+use crate::settings::SettingsStore;
+
+use super::{clock, state::TimeState};
+
+pub const EVENT_DEBUG_OFFSET_CHANGED: &str = "debug-offset-changed";
+
+#[derive(Debug, Error)]
+pub enum DebugError {
+    #[error("debug offset override is disabled in settings")]
+    Disabled,
+}
+
+impl DebugError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Disabled => "debug_offset_disabled",
+        }
+    }
+}
+
+impl Serialize for DebugError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DebugError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Payload of the `debug-offset-changed` event.
+#[derive(Clone, Serialize)]
+struct DebugOffsetChanged {
+    offset_millis: i64,
+    /// Time to display now, with the new offset applied.
+    epoch_millis: i64,
+    monotonic_anchor_millis: i64,
+}
+
+/// Shifts the displayed time by `millis` on top of the synced offset, to see
+/// how the UI behaves when the clock is off. Zero removes the override.
+///
+/// Only available when `developer.debug_offset` is enabled in the settings.
+#[tauri::command]
+pub fn set_debug_offset(
+    app: AppHandle,
+    state: State<'_, TimeState>,
+    millis: i64,
+) -> Result<(), DebugError> {
+    if !app.state::<SettingsStore>().get().developer.debug_offset {
+        return Err(DebugError::Disabled);
+    }
+    state.set_debug_offset(millis);
+
+    let payload = DebugOffsetChanged {
+        offset_millis: millis,
+        epoch_millis: state.display_now_millis(),
+        monotonic_anchor_millis: clock::monotonic_millis(),
+    };
+    if let Err(err) = app.emit(EVENT_DEBUG_OFFSET_CHANGED, payload) {
+        eprintln!("failed to emit {EVENT_DEBUG_OFFSET_CHANGED}: {err}");
+    }
+    Ok(())
+}
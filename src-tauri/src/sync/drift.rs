@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+
+/// Successful syncs kept for the fit; older ones age out.
+const MAX_SAMPLES: usize = 16;
+/// Shortest span of samples that is used to estimate drift, so that
+/// measurement noise does not dominate the rate.
+const MIN_SPAN_MILLIS: i64 = 60_000;
+/// Real oscillators stay well within this; larger fits are treated as noise.
+const MAX_DRIFT_PPM: f64 = 500.0;
+pub const PPM: f64 = 1_000_000.0;
+
+/// Least-squares fit of offset against time over recent successful syncs,
+/// giving the rate at which the local oscillator gains or loses time.
+#[derive(Debug, Default)]
+pub struct DriftModel {
+    /// `(synced_at_millis, offset_millis)` pairs, oldest first.
+    samples: VecDeque<(i64, i64)>,
+    ppm: Option<f64>,
+}
+
+impl DriftModel {
+    pub fn add(&mut self, synced_at_millis: i64, offset_millis: i64) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((synced_at_millis, offset_millis));
+        self.ppm = self.fit();
+    }
+
+    /// Keeps the fit consistent after the system clock jumped by
+    /// `delta_millis`, which shifts every recorded offset equally.
+    pub fn shift(&mut self, delta_millis: i64) {
+        for (_, offset) in &mut self.samples {
+            *offset -= delta_millis;
+        }
+    }
+
+    /// Parts per million; positive when the local clock runs slow.
+    pub fn ppm(&self) -> Option<f64> {
+        self.ppm
+    }
+
+    fn fit(&self) -> Option<f64> {
+        let (first, _) = *self.samples.front()?;
+        let (last, _) = *self.samples.back()?;
+        if last - first < MIN_SPAN_MILLIS {
+            return None;
+        }
+
+        // Centre on the first sample to keep the sums small.
+        let count = self.samples.len() as f64;
+        let points = self
+            .samples
+            .iter()
+            .map(|&(at, offset)| ((at - first) as f64, offset as f64));
+        let (sum_x, sum_y) = points
+            .clone()
+            .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        let (mean_x, mean_y) = (sum_x / count, sum_y / count);
+        let (covariance, variance) = points.fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x - mean_x;
+            (cov + dx * (y - mean_y), var + dx * dx)
+        });
+        if variance == 0.0 {
+            return None;
+        }
+
+        let ppm = covariance / variance * PPM;
+        (ppm.abs() <= MAX_DRIFT_PPM).then_some(ppm)
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use thiserror::Error;
+
+use crate::pinning;
+
+#[derive(Debug, Clone, Error)]
+pub enum TimeSyncError {
+    #[error("network request failed: {0}")]
+    Network(String),
+    #[error("request timed out")]
+    Timeout,
+    #[error("failed to parse response")]
+    Parse,
+    #[error("unknown time zone: {name}{}", did_you_mean(.suggestions))]
+    InvalidTimeZone {
+        name: String,
+        /// Closest valid zone names, best first.
+        suggestions: Vec<String>,
+    },
+    #[error("rate limited by provider")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("response failed authentication: {0}")]
+    Authentication(String),
+    #[error("implausible response: {0}")]
+    Implausible(String),
+    #[error("invalid simulation: {0}")]
+    InvalidSimulation(String),
+    #[error("certificate pin mismatch: {0}")]
+    PinMismatch(String),
+}
+
+impl TimeSyncError {
+    /// Stable machine-readable identifier the frontend can branch on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "network",
+            Self::Timeout => "timeout",
+            Self::Parse => "parse",
+            Self::InvalidTimeZone { .. } => "invalid_time_zone",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Authentication(_) => "authentication",
+            Self::Implausible(_) => "implausible",
+            Self::InvalidSimulation(_) => "invalid_simulation",
+            Self::PinMismatch(_) => "pin_mismatch",
+        }
+    }
+}
+
+impl From<reqwest::Error> for TimeSyncError {
+    fn from(err: reqwest::Error) -> Self {
+        if let Some(mismatch) = pinning::find_mismatch(&err) {
+            Self::PinMismatch(mismatch.to_string())
+        } else if err.is_timeout() {
+            Self::Timeout
+        } else if err.is_decode() {
+            Self::Parse
+        } else {
+            Self::Network(err.to_string())
+        }
+    }
+}
+
+impl From<std::io::Error> for TimeSyncError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Network(err.to_string()),
+        }
+    }
+}
+
+impl Serialize for TimeSyncError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TimeSyncError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let Self::RateLimited { retry_after } = self {
+            state.serialize_field(
+                "retry_after_secs",
+                &retry_after.map(|delay| delay.as_secs()),
+            )?;
+        }
+        if let Self::InvalidTimeZone { suggestions, .. } = self {
+            state.serialize_field("suggestions", suggestions)?;
+        }
+        state.end()
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean {}?)", suggestions.join(", "))
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::MILLIS_PER_SECOND;
+
+/// One way of locating a timestamp in an HTTP response. Rules are tried in
+/// order and the first one that yields a timestamp wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtractRule {
+    /// Number or numeric string of seconds since the Unix epoch.
+    UnixSeconds { pointer: String },
+    /// Number or numeric string of milliseconds since the Unix epoch.
+    UnixMillis { pointer: String },
+    /// RFC 3339 string, or a naive ISO 8601 string interpreted as UTC.
+    Iso { pointer: String },
+    /// Object with `year`, `month`, `day`, `hour`, `minute`, `seconds` and
+    /// optional `milliSeconds` fields, interpreted as UTC.
+    Components { pointer: String },
+    /// The RFC 2822 `Date` response header.
+    DateHeader,
+}
+
+/// The parts of an HTTP response the rules can look at.
+pub struct HttpPayload {
+    pub json: Option<Value>,
+    pub date_header: Option<String>,
+}
+
+/// Rules covering the field layouts of timeapi.io and worldtimeapi.org; the
+/// last-resort path when a response does not match its typed model.
+pub fn builtin_rules() -> Vec<ExtractRule> {
+    let mut rules: Vec<ExtractRule> = ["/unixTime", "/unixtime"]
+        .into_iter()
+        .map(|pointer| ExtractRule::UnixSeconds {
+            pointer: pointer.to_string(),
+        })
+        .collect();
+    rules.extend(
+        [
+            "/dateTime",
+            "/dateTimeUtc",
+            "/currentLocalTime",
+            "/currentUtcTime",
+            "/utc_datetime",
+            "/datetime",
+        ]
+        .into_iter()
+        .map(|pointer| ExtractRule::Iso {
+            pointer: pointer.to_string(),
+        }),
+    );
+    rules.push(ExtractRule::Components {
+        pointer: String::new(),
+    });
+    rules
+}
+
+pub fn extract_epoch_millis(rules: &[ExtractRule], payload: &HttpPayload) -> Option<i64> {
+    rules.iter().find_map(|rule| apply_rule(rule, payload))
+}
+
+fn apply_rule(rule: &ExtractRule, payload: &HttpPayload) -> Option<i64> {
+    let lookup = |pointer: &str| payload.json.as_ref()?.pointer(pointer);
+
+    match rule {
+        ExtractRule::UnixSeconds { pointer } => lookup(pointer)
+            .and_then(value_to_i64)
+            .map(|seconds| seconds * MILLIS_PER_SECOND),
+        ExtractRule::UnixMillis { pointer } => lookup(pointer).and_then(value_to_i64),
+        ExtractRule::Iso { pointer } => lookup(pointer)
+            .and_then(Value::as_str)
+            .and_then(parse_iso_candidate),
+        ExtractRule::Components { pointer } => lookup(pointer).and_then(parse_components),
+        ExtractRule::DateHeader => payload
+            .date_header
+            .as_deref()
+            .and_then(|header| DateTime::parse_from_rfc2822(header).ok())
+            .map(|parsed| parsed.timestamp_millis()),
+    }
+}
+
+fn parse_components(payload: &Value) -> Option<i64> {
+    let year = payload.get("year").and_then(value_to_i64)?;
+    let month = payload.get("month").and_then(value_to_i64)?;
+    let day = payload.get("day").and_then(value_to_i64)?;
+    let hour = payload.get("hour").and_then(value_to_i64)?;
+    let minute = payload.get("minute").and_then(value_to_i64)?;
+    let seconds = payload.get("seconds").and_then(value_to_i64)?;
+    let millis = payload
+        .get("milliSeconds")
+        .and_then(value_to_i64)
+        .unwrap_or(0);
+
+    let year_i32 = i32::try_from(year).ok()?;
+    let month_u32 = u32::try_from(month).ok()?;
+    let day_u32 = u32::try_from(day).ok()?;
+    let hour_u32 = u32::try_from(hour).ok()?;
+    let minute_u32 = u32::try_from(minute).ok()?;
+    let second_u32 = u32::try_from(seconds).ok()?;
+    let millis_u32 = u32::try_from(millis).ok()?;
+
+    let date = NaiveDate::from_ymd_opt(year_i32, month_u32, day_u32)?;
+    let time = NaiveTime::from_hms_milli_opt(hour_u32, minute_u32, second_u32, millis_u32)?;
+
+    Some(NaiveDateTime::new(date, time).and_utc().timestamp_millis())
+}
+
+pub fn parse_iso_candidate(value: &str) -> Option<i64> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.timestamp_millis());
+    }
+
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(naive.and_utc().timestamp_millis());
+        }
+    }
+
+    None
+}
+
+fn value_to_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|float| float.round() as i64)),
+        Value::String(text) => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|float| float.round() as i64),
+        _ => None,
+    }
+}
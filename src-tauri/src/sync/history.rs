@@ -0,0 +1,301 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, SecondsFormat};
+use rusqlite::{Connection, Row, params};
+use serde::{Deserialize, Serialize, Serializer, de::DeserializeOwned, ser::SerializeStruct};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+use crate::{settings::HistorySettings, store};
+
+use super::{ProviderKind, SyncSource, TimeSyncResult};
+
+const HISTORY_FILE_NAME: &str = "sync_history.sqlite3";
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("sync history is unavailable")]
+    Unavailable,
+    #[error("sync history query failed: {0}")]
+    Sql(#[from] rusqlite::Error),
+    #[error("failed to write export: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to encode export: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl HistoryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unavailable => "unavailable",
+            Self::Sql(_) => "sql",
+            Self::Io(_) => "io",
+            Self::Json(_) => "json",
+        }
+    }
+}
+
+impl Serialize for HistoryError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("HistoryError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// One sync attempt as stored in the history log.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    /// Corrected wall-clock time of the attempt.
+    pub recorded_at_millis: i64,
+    pub source: SyncSource,
+    pub provider: ProviderKind,
+    pub offset_millis: i64,
+    pub rtt_millis: i64,
+    /// `TimeSyncError::code()` of the failure, if the attempt failed.
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn from_result(result: &TimeSyncResult) -> Self {
+        Self {
+            recorded_at_millis: result.epoch_millis,
+            source: result.source,
+            provider: result.provider,
+            offset_millis: result.offset_millis,
+            rtt_millis: result.rtt_millis,
+            error_code: result.error.as_ref().map(|err| err.code().to_string()),
+            error_message: result.error.as_ref().map(ToString::to_string),
+        }
+    }
+
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            recorded_at_millis: row.get(0)?,
+            source: decode_enum(&row.get::<_, String>(1)?).unwrap_or(SyncSource::Remote),
+            provider: decode_enum(&row.get::<_, String>(2)?).unwrap_or(ProviderKind::System),
+            offset_millis: row.get(3)?,
+            rtt_millis: row.get(4)?,
+            error_code: row.get(5)?,
+            error_message: row.get(6)?,
+        })
+    }
+}
+
+/// File formats accepted by `export_sync_history` and the stopwatch's
+/// `export_laps`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+const CSV_HEADER: &str = "recorded_at,recorded_at_millis,source,provider,offset_millis,rtt_millis,error_code,error_message";
+
+/// Inclusive bounds on `recorded_at_millis`; either end may be open.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct HistoryRange {
+    pub from_millis: Option<i64>,
+    pub to_millis: Option<i64>,
+}
+
+/// SQLite-backed log of every sync attempt, kept in the app data directory.
+/// When the database cannot be opened the app keeps working without history.
+/// SQLite blocks, so it is only used from blocking threads.
+pub struct HistoryStore {
+    connection: Option<Mutex<Connection>>,
+}
+
+impl HistoryStore {
+    pub fn open(app: &AppHandle) -> Self {
+        let connection = store::data_file(app, HISTORY_FILE_NAME).and_then(|path| {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            open_database(&path)
+                .inspect_err(|err| eprintln!("sync history disabled: {err}"))
+                .ok()
+        });
+        Self {
+            connection: connection.map(Mutex::new),
+        }
+    }
+
+    fn record(
+        &self,
+        entry: &HistoryEntry,
+        retention: &HistorySettings,
+    ) -> Result<(), HistoryError> {
+        let connection = self.lock()?;
+        connection.execute(
+            "INSERT INTO samples (recorded_at_millis, source, provider, offset_millis, rtt_millis, error_code, error_message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.recorded_at_millis,
+                encode_enum(&entry.source),
+                encode_enum(&entry.provider),
+                entry.offset_millis,
+                entry.rtt_millis,
+                entry.error_code,
+                entry.error_message,
+            ],
+        )?;
+
+        let cutoff = entry.recorded_at_millis - i64::from(retention.max_age_days) * MILLIS_PER_DAY;
+        connection.execute(
+            "DELETE FROM samples WHERE recorded_at_millis < ?1",
+            params![cutoff],
+        )?;
+        connection.execute(
+            "DELETE FROM samples WHERE id NOT IN
+             (SELECT id FROM samples ORDER BY recorded_at_millis DESC, id DESC LIMIT ?1)",
+            params![retention.max_entries],
+        )?;
+        Ok(())
+    }
+
+    /// Entries inside `range`, oldest first.
+    pub fn query(&self, range: HistoryRange) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let connection = self.lock()?;
+        let mut statement = connection.prepare(
+            "SELECT recorded_at_millis, source, provider, offset_millis, rtt_millis, error_code, error_message
+             FROM samples
+             WHERE recorded_at_millis >= ?1 AND recorded_at_millis <= ?2
+             ORDER BY recorded_at_millis, id",
+        )?;
+        let rows = statement.query_map(
+            params![
+                range.from_millis.unwrap_or(i64::MIN),
+                range.to_millis.unwrap_or(i64::MAX)
+            ],
+            HistoryEntry::from_row,
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>, HistoryError> {
+        self.connection
+            .as_ref()
+            .and_then(|connection| connection.lock().ok())
+            .ok_or(HistoryError::Unavailable)
+    }
+}
+
+fn open_database(path: &Path) -> Result<Connection, HistoryError> {
+    let connection = Connection::open(path)?;
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS samples (
+             id INTEGER PRIMARY KEY,
+             recorded_at_millis INTEGER NOT NULL,
+             source TEXT NOT NULL,
+             provider TEXT NOT NULL,
+             offset_millis INTEGER NOT NULL,
+             rtt_millis INTEGER NOT NULL,
+             error_code TEXT,
+             error_message TEXT
+         );
+         CREATE INDEX IF NOT EXISTS samples_recorded_at ON samples (recorded_at_millis);",
+    )?;
+    Ok(connection)
+}
+
+/// Stores a unit enum under its serde name so the column stays readable.
+fn encode_enum<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn decode_enum<T: DeserializeOwned>(raw: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(raw.to_string())).ok()
+}
+
+fn encode_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for entry in entries {
+        let recorded_at = DateTime::from_timestamp_millis(entry.recorded_at_millis)
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))
+            .unwrap_or_default();
+        let fields = [
+            recorded_at,
+            entry.recorded_at_millis.to_string(),
+            encode_enum(&entry.source),
+            encode_enum(&entry.provider),
+            entry.offset_millis.to_string(),
+            entry.rtt_millis.to_string(),
+            entry.error_code.clone().unwrap_or_default(),
+            entry.error_message.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Appends `entry` to the history and prunes whatever falls outside
+/// `retention`, on a blocking thread so the sync is not held up.
+pub fn record(app: &AppHandle, entry: HistoryEntry, retention: HistorySettings) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = app.state::<HistoryStore>().record(&entry, &retention) {
+            eprintln!("failed to record sync history: {err}");
+        }
+    });
+}
+
+/// Returns logged sync attempts within `range`, oldest first.
+#[tauri::command]
+pub async fn get_sync_history(
+    app: AppHandle,
+    range: Option<HistoryRange>,
+) -> Result<Vec<HistoryEntry>, HistoryError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<HistoryStore>().query(range.unwrap_or_default())
+    })
+    .await
+    .map_err(|_| HistoryError::Unavailable)?
+}
+
+/// Writes the sync attempts within `range` to `path` and returns how many
+/// entries were exported.
+#[tauri::command]
+pub async fn export_sync_history(
+    app: AppHandle,
+    path: PathBuf,
+    format: ExportFormat,
+    range: Option<HistoryRange>,
+) -> Result<usize, HistoryError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = app
+            .state::<HistoryStore>()
+            .query(range.unwrap_or_default())?;
+        let encoded = match format {
+            ExportFormat::Csv => encode_csv(&entries).into_bytes(),
+            ExportFormat::Json => serde_json::to_vec_pretty(&entries)?,
+        };
+        fs::write(&path, encoded)?;
+        Ok::<_, HistoryError>(entries.len())
+    })
+    .await
+    .map_err(|_| HistoryError::Unavailable)?
+}
@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    StatusCode,
+    header::{DATE, RETRY_AFTER},
+};
+
+use crate::settings::{CustomEndpoint, RetrySettings};
+
+use super::{
+    TimeSyncError,
+    extract::{ExtractRule, HttpPayload, builtin_rules, extract_epoch_millis},
+    models::{self, TimeApiResponse, WorldTimeApiResponse},
+    retry::with_retry,
+    source::{ProviderContext, ProviderKind, RoundTrip, SyncSample, TimeSource},
+};
+
+/// `Date` headers are truncated to whole seconds, so the server's clock is on
+/// average half a second ahead of the value it reports.
+const DATE_HEADER_TRUNCATION_MILLIS: i64 = 500;
+const TIME_API_ENDPOINT: &str = "https://timeapi.io/api/Time/current/zone";
+const WORLD_TIME_API_ENDPOINT: &str = "https://worldtimeapi.org/api/timezone";
+
+pub struct TimeApiSource {
+    zone: String,
+    retry: RetrySettings,
+    client: reqwest::Client,
+}
+
+impl TimeApiSource {
+    pub fn new(context: &ProviderContext) -> Self {
+        Self {
+            zone: context.zone.clone(),
+            retry: context.settings.retry.clone(),
+            client: context.client(ProviderKind::TimeApi),
+        }
+    }
+}
+
+impl TimeSource for TimeApiSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let url = format!(
+            "{TIME_API_ENDPOINT}?timeZone={}",
+            urlencoding::encode(&self.zone)
+        );
+        fetch_sample(
+            &self.client,
+            &url,
+            &self.retry,
+            models::decode::<TimeApiResponse>,
+            ProviderKind::TimeApi,
+        )
+        .await
+    }
+}
+
+pub struct WorldTimeApiSource {
+    zone: String,
+    retry: RetrySettings,
+    client: reqwest::Client,
+}
+
+impl WorldTimeApiSource {
+    pub fn new(context: &ProviderContext) -> Self {
+        Self {
+            zone: context.zone.clone(),
+            retry: context.settings.retry.clone(),
+            client: context.client(ProviderKind::WorldTimeApi),
+        }
+    }
+}
+
+impl TimeSource for WorldTimeApiSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let path = self
+            .zone
+            .split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        let url = format!("{WORLD_TIME_API_ENDPOINT}/{path}");
+        fetch_sample(
+            &self.client,
+            &url,
+            &self.retry,
+            models::decode::<WorldTimeApiResponse>,
+            ProviderKind::WorldTimeApi,
+        )
+        .await
+    }
+}
+
+/// User-registered endpoints from `sync.custom_endpoints`, tried in order.
+pub struct CustomSource {
+    zone: String,
+    endpoints: Vec<CustomEndpoint>,
+    retry: RetrySettings,
+    client: reqwest::Client,
+}
+
+impl CustomSource {
+    pub fn new(context: &ProviderContext) -> Self {
+        Self {
+            zone: context.zone.clone(),
+            endpoints: context.settings.custom_endpoints.clone(),
+            retry: context.settings.retry.clone(),
+            client: context.client(ProviderKind::Custom),
+        }
+    }
+}
+
+impl TimeSource for CustomSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let mut last_error =
+            TimeSyncError::Network("no custom time endpoints configured".to_string());
+        for endpoint in &self.endpoints {
+            let url = endpoint
+                .url
+                .replace("{zone}", &urlencoding::encode(&self.zone));
+            let rules = if endpoint.rules.is_empty() {
+                builtin_rules()
+            } else {
+                endpoint.rules.clone()
+            };
+            match fetch_sample(
+                &self.client,
+                &url,
+                &self.retry,
+                |payload| extract_epoch_millis(&rules, payload),
+                ProviderKind::Custom,
+            )
+            .await
+            {
+                Ok(sample) => return Ok(sample),
+                Err(err) => {
+                    eprintln!("custom time endpoint {} failed: {err}", endpoint.name);
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Reads the `Date` header of a HEAD request to well-known hosts. Coarse, but
+/// needs no JSON API and works wherever plain HTTPS does.
+pub struct DateHeaderSource {
+    hosts: Vec<String>,
+    retry: RetrySettings,
+    client: reqwest::Client,
+}
+
+impl DateHeaderSource {
+    pub fn new(context: &ProviderContext) -> Self {
+        Self {
+            hosts: context.settings.date_header_hosts.clone(),
+            retry: context.settings.retry.clone(),
+            client: context.client(ProviderKind::HttpDate),
+        }
+    }
+}
+
+impl TimeSource for DateHeaderSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let mut last_error = TimeSyncError::Network("no date header hosts configured".to_string());
+        for host in &self.hosts {
+            let fetched = with_retry(&self.retry, || fetch_date_header(&self.client, host)).await;
+            match fetched {
+                Ok(((payload, round_trip), attempts)) => {
+                    let Some(epoch_millis) =
+                        extract_epoch_millis(&[ExtractRule::DateHeader], &payload)
+                    else {
+                        eprintln!("date header host {host} sent no usable Date header");
+                        last_error = TimeSyncError::Parse;
+                        continue;
+                    };
+                    return Ok(round_trip.sample(
+                        ProviderKind::HttpDate,
+                        epoch_millis + DATE_HEADER_TRUNCATION_MILLIS,
+                        attempts,
+                    ));
+                }
+                Err(err) => {
+                    eprintln!("date header host {host} failed: {err}");
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+async fn fetch_sample(
+    client: &reqwest::Client,
+    url: &str,
+    retry: &RetrySettings,
+    decode: impl Fn(&HttpPayload) -> Option<i64>,
+    provider: ProviderKind,
+) -> Result<SyncSample, TimeSyncError> {
+    let ((payload, round_trip), attempts) = with_retry(retry, || fetch(client, url)).await?;
+    let epoch_millis = decode(&payload).ok_or(TimeSyncError::Parse)?;
+    Ok(round_trip.sample(provider, epoch_millis, attempts))
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(HttpPayload, RoundTrip), TimeSyncError> {
+    let round_trip = RoundTrip::start();
+    let response = client.get(url).send().await?;
+    let round_trip = round_trip.finish();
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(TimeSyncError::RateLimited {
+            retry_after: retry_after(&response),
+        });
+    }
+    if !response.status().is_success() {
+        return Err(TimeSyncError::Network(format!(
+            "unexpected status: {}",
+            response.status()
+        )));
+    }
+
+    let date_header = date_header(&response);
+    let body = response.bytes().await?;
+    let payload = HttpPayload {
+        json: serde_json::from_slice(&body).ok(),
+        date_header,
+    };
+    Ok((payload, round_trip))
+}
+
+/// Any status is fine here as long as the server stamped the response.
+async fn fetch_date_header(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(HttpPayload, RoundTrip), TimeSyncError> {
+    let round_trip = RoundTrip::start();
+    let response = client.head(url).send().await?;
+    let round_trip = round_trip.finish();
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(TimeSyncError::RateLimited {
+            retry_after: retry_after(&response),
+        });
+    }
+
+    let payload = HttpPayload {
+        json: None,
+        date_header: date_header(&response),
+    };
+    Ok((payload, round_trip))
+}
+
+fn date_header(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(DATE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// `Retry-After` as either delay-seconds or an HTTP date (RFC 9110 §10.2.3).
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay_millis = (at.with_timezone(&Utc) - Utc::now()).num_milliseconds();
+    Some(Duration::from_millis(delay_millis.max(0).unsigned_abs()))
+}
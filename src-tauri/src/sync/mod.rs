@@ -0,0 +1,425 @@
+pub mod benchmark;
+pub mod clock;
+mod consensus;
+pub mod debug;
+mod drift;
+mod error;
+mod extract;
+pub mod history;
+mod http;
+mod models;
+mod ntp;
+pub mod nts;
+mod persist;
+mod plausibility;
+pub mod providers;
+mod race;
+mod ratelimit;
+mod retry;
+mod roughtime;
+mod scheduler;
+pub mod simulate;
+mod skew;
+mod slew;
+mod source;
+pub mod state;
+pub mod status;
+pub mod task;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    net::SharedClient,
+    settings::{SettingsStore, SyncSettings},
+    zone::{default_zone_name, parse_zone},
+};
+
+pub use error::TimeSyncError;
+pub use extract::ExtractRule;
+pub use source::ProviderKind;
+
+use source::{ProviderContext, SyncSample, TimeProvider};
+use state::{LastAttempt, SyncAnchor, TimeState};
+
+pub const MILLIS_PER_SECOND: i64 = 1000;
+
+pub const EVENT_TIME_OFFSET_UPDATED: &str = "time-offset-updated";
+pub const EVENT_SYSTEM_CLOCK_CHANGED: &str = "system-clock-changed";
+
+pub const DEFAULT_TIME_ZONE: &str = "Etc/UTC";
+const DEFAULT_PROVIDER_PRIORITY: [ProviderKind; 5] = [
+    ProviderKind::Ntp,
+    ProviderKind::TimeApi,
+    ProviderKind::WorldTimeApi,
+    ProviderKind::HttpDate,
+    ProviderKind::System,
+];
+const DEFAULT_CONSENSUS_PROVIDERS: [ProviderKind; 3] = [
+    ProviderKind::Ntp,
+    ProviderKind::TimeApi,
+    ProviderKind::WorldTimeApi,
+];
+
+/// How `sync_time` turns the configured providers into a single sample.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// Try providers one by one and take the first successful answer.
+    #[default]
+    Priority,
+    /// Query all providers concurrently and take the median offset.
+    Consensus,
+    /// Query all providers concurrently and take the first plausible answer.
+    Race,
+}
+
+/// Where the time in a `TimeSyncResult` ultimately came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSource {
+    /// A provider answered during this sync.
+    Remote,
+    /// Every provider failed; the offset from the previous successful sync is reused.
+    Cached,
+    /// Every provider failed and nothing was cached; the system clock is used as-is.
+    SystemFallback,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TimeSyncResult {
+    source: SyncSource,
+    /// Why the sync did not produce a remote sample, if it did not.
+    error: Option<TimeSyncError>,
+    epoch_millis: i64,
+    /// Time the clock shows at the anchor; differs from `epoch_millis` while
+    /// a large correction is being slewed in.
+    display_epoch_millis: i64,
+    /// How fast the displayed time advances relative to real time; only
+    /// differs from 1 while a simulation runs.
+    display_rate: f64,
+    offset_millis: i64,
+    /// `clock::monotonic_millis()` at the instant `epoch_millis` was valid.
+    monotonic_anchor_millis: i64,
+    provider: ProviderKind,
+    rtt_millis: i64,
+    /// Requests the answering provider needed, including retries.
+    attempts: u32,
+    dispersion_millis: Option<i64>,
+    sample_count: usize,
+    /// Age of the offset in use; zero for a fresh remote sample.
+    offset_age_millis: Option<i64>,
+    /// Error bound of `epoch_millis`: half the round trip, since the path
+    /// may be fully asymmetric, plus provider disagreement, widened by the
+    /// age of the offset. `None` when the bare system clock is used.
+    uncertainty_millis: Option<i64>,
+}
+
+impl TimeSyncResult {
+    fn from_sample(
+        sample: SyncSample,
+        dispersion_millis: Option<i64>,
+        sample_count: usize,
+    ) -> Self {
+        let monotonic_anchor_millis = clock::monotonic_millis();
+        let epoch_millis = Utc::now().timestamp_millis() + sample.offset_millis;
+        Self {
+            source: SyncSource::Remote,
+            error: None,
+            epoch_millis,
+            display_epoch_millis: epoch_millis,
+            display_rate: 1.0,
+            offset_millis: sample.offset_millis,
+            monotonic_anchor_millis,
+            provider: sample.provider,
+            rtt_millis: sample.rtt_millis,
+            attempts: sample.attempts,
+            dispersion_millis,
+            sample_count,
+            offset_age_millis: Some(0),
+            uncertainty_millis: Some(sample.rtt_millis / 2 + dispersion_millis.unwrap_or(0)),
+        }
+    }
+
+    fn cached(anchor: SyncAnchor, now_millis: i64, error: Option<TimeSyncError>) -> Self {
+        Self {
+            source: SyncSource::Cached,
+            error,
+            epoch_millis: now_millis,
+            display_epoch_millis: now_millis,
+            display_rate: 1.0,
+            offset_millis: anchor.offset_millis,
+            monotonic_anchor_millis: clock::monotonic_millis(),
+            provider: anchor.provider,
+            rtt_millis: 0,
+            attempts: 0,
+            dispersion_millis: None,
+            sample_count: 0,
+            offset_age_millis: Some((now_millis - anchor.synced_at_millis).max(0)),
+            uncertainty_millis: Some(anchor.uncertainty_at(now_millis)),
+        }
+    }
+
+    fn system_fallback(error: Option<TimeSyncError>) -> Self {
+        let sample = SyncSample {
+            provider: ProviderKind::System,
+            offset_millis: 0,
+            rtt_millis: 0,
+            attempts: 0,
+        };
+        Self {
+            source: SyncSource::SystemFallback,
+            error,
+            offset_age_millis: None,
+            uncertainty_millis: None,
+            ..Self::from_sample(sample, None, 0)
+        }
+    }
+
+    /// Points the displayed time at the running simulation, if any, and
+    /// re-anchors it so the frontend extrapolates from the same instant. The
+    /// debug offset is added on top of either.
+    fn apply_display_overrides(&mut self, state: &TimeState) {
+        if let Some(simulation) = state.simulation() {
+            self.monotonic_anchor_millis = clock::monotonic_millis();
+            self.display_epoch_millis = simulation.at(self.monotonic_anchor_millis);
+            self.display_rate = simulation.rate();
+        }
+        self.display_epoch_millis += state.debug_offset_millis();
+    }
+}
+
+/// Payload of the `time-offset-updated` event.
+#[derive(Clone, Serialize)]
+struct OffsetUpdated {
+    source: SyncSource,
+    error: Option<TimeSyncError>,
+    offset_millis: i64,
+    uncertainty_millis: Option<i64>,
+    epoch_millis: i64,
+    display_epoch_millis: i64,
+    display_rate: f64,
+    monotonic_anchor_millis: i64,
+    provider: ProviderKind,
+}
+
+#[tauri::command]
+pub async fn sync_time(
+    app: AppHandle,
+    time_zone: Option<String>,
+    providers: Option<Vec<ProviderKind>>,
+    mode: Option<SyncMode>,
+) -> Result<TimeSyncResult, TimeSyncError> {
+    let zone = time_zone.unwrap_or_else(default_zone_name);
+    // Reject typos before any provider sees them; remote APIs would fail and
+    // the cached fallback would hide the mistake.
+    parse_zone(&zone)?;
+    Ok(run_sync(&app, &zone, providers, mode.unwrap_or_default(), false).await)
+}
+
+/// Syncs immediately, ignoring `min_interval_secs`; used when the cached
+/// offset is known to be stale, such as right after a wake from sleep.
+pub async fn resync(app: &AppHandle) -> TimeSyncResult {
+    run_sync(app, DEFAULT_TIME_ZONE, None, SyncMode::default(), true).await
+}
+
+/// Syncs unless another sync finished less than `min_interval_secs` ago (and
+/// `force` is unset), in which case the cached offset is returned without
+/// touching the network.
+/// Concurrent callers queue on the guard, so only the first one hits the
+/// providers and the rest reuse its result.
+async fn run_sync(
+    app: &AppHandle,
+    zone: &str,
+    providers: Option<Vec<ProviderKind>>,
+    mode: SyncMode,
+    force: bool,
+) -> TimeSyncResult {
+    let state = app.state::<TimeState>();
+    let mut last_attempt = state.sync_guard.lock().await;
+
+    let min_interval_millis = app
+        .state::<SettingsStore>()
+        .get()
+        .sync
+        .min_interval_secs
+        .saturating_mul(1000);
+    if let Some(previous) = last_attempt.as_ref().filter(|_| !force) {
+        let elapsed = clock::monotonic_millis() - previous.finished_millis;
+        if elapsed < i64::try_from(min_interval_millis).unwrap_or(i64::MAX) {
+            let mut result = fallback(&state, previous.error.clone());
+            result.apply_display_overrides(&state);
+            return result;
+        }
+    }
+
+    let mut result = perform_sync(app, zone, providers, mode).await;
+    publish(app, &mut result);
+    *last_attempt = Some(LastAttempt {
+        finished_millis: clock::monotonic_millis(),
+        error: result.error.clone(),
+    });
+    result
+}
+
+async fn perform_sync(
+    app: &AppHandle,
+    zone: &str,
+    providers: Option<Vec<ProviderKind>>,
+    mode: SyncMode,
+) -> TimeSyncResult {
+    let context = provider_context(app, zone);
+    let ranking = app.state::<TimeState>().ranking();
+    let mut kinds = providers.unwrap_or_else(|| match mode {
+        SyncMode::Priority | SyncMode::Race => default_priority(&context.settings, &ranking),
+        SyncMode::Consensus => DEFAULT_CONSENSUS_PROVIDERS.to_vec(),
+    });
+    kinds.retain(|kind| !context.settings.disabled_providers.contains(kind));
+
+    let outcome = match mode {
+        SyncMode::Priority => query_by_priority(&kinds, &context)
+            .await
+            .map(|sample| TimeSyncResult::from_sample(sample, None, 1)),
+        SyncMode::Race => race::query_race(&kinds, &context)
+            .await
+            .map(|sample| TimeSyncResult::from_sample(sample, None, 1)),
+        SyncMode::Consensus => consensus::query_consensus(&kinds, &context)
+            .await
+            .map(|outcome| {
+                TimeSyncResult::from_sample(
+                    outcome.sample,
+                    Some(outcome.dispersion_millis),
+                    outcome.accepted,
+                )
+            }),
+    };
+
+    let error = match outcome {
+        Ok(result) if result.provider != ProviderKind::System => return result,
+        Ok(_) => TimeSyncError::Network("no remote time source succeeded".to_string()),
+        Err(err) => err,
+    };
+
+    eprintln!("time sync fallback triggered: {error}");
+    fallback(&app.state::<TimeState>(), Some(error))
+}
+
+fn provider_context(app: &AppHandle, zone: &str) -> ProviderContext {
+    ProviderContext {
+        zone: zone.to_string(),
+        settings: app.state::<SettingsStore>().get().sync,
+        clients: app.state::<SharedClient>().inner().clone(),
+        app: app.clone(),
+    }
+}
+
+/// The configured priority list, or the built-in one led by the user's own
+/// endpoints when any are registered. Built-in providers are reordered by
+/// `ranking`, the outcome of the last benchmark; `System` always stays last.
+fn default_priority(settings: &SyncSettings, ranking: &[ProviderKind]) -> Vec<ProviderKind> {
+    if let Some(priority) = &settings.priority {
+        return priority.clone();
+    }
+
+    let mut builtin = DEFAULT_PROVIDER_PRIORITY.to_vec();
+    builtin.sort_by_key(|kind| match kind {
+        ProviderKind::System => usize::MAX,
+        _ => ranking
+            .iter()
+            .position(|ranked| ranked == kind)
+            .unwrap_or(ranking.len()),
+    });
+
+    let mut priority = Vec::with_capacity(builtin.len() + 1);
+    if !settings.custom_endpoints.is_empty() {
+        priority.push(ProviderKind::Custom);
+    }
+    priority.extend(builtin);
+    priority
+}
+
+/// Seeds the shared state with the offset persisted by a previous session.
+pub fn restore(app: &AppHandle) {
+    if let Some(anchor) = persist::load(app) {
+        app.state::<TimeState>().record(anchor);
+    }
+}
+
+/// Result built from the previous successful sync, or the bare system clock.
+fn fallback(state: &TimeState, error: Option<TimeSyncError>) -> TimeSyncResult {
+    match state.latest() {
+        Some(anchor) => TimeSyncResult::cached(anchor, state.now_millis(), error),
+        None => TimeSyncResult::system_fallback(error),
+    }
+}
+
+fn publish(app: &AppHandle, result: &mut TimeSyncResult) {
+    let settings = app.state::<SettingsStore>().get().sync;
+    history::record(
+        app,
+        history::HistoryEntry::from_result(result),
+        settings.history.clone(),
+    );
+
+    if result.source == SyncSource::Remote {
+        let anchor = SyncAnchor {
+            epoch_millis: result.epoch_millis,
+            monotonic_millis: result.monotonic_anchor_millis,
+            offset_millis: result.offset_millis,
+            rtt_millis: result.rtt_millis,
+            uncertainty_millis: result.uncertainty_millis.unwrap_or_default(),
+            provider: result.provider,
+            synced_at_millis: result.epoch_millis,
+        };
+        let state = app.state::<TimeState>();
+        if state.record_success(anchor, &settings.slew) {
+            result.display_epoch_millis = state.now_millis();
+            result.monotonic_anchor_millis = clock::monotonic_millis();
+            slew::spawn_progress(app);
+        }
+        persist::save(app, &anchor);
+        skew::check(app, result.offset_millis, &settings.skew_warning);
+    } else {
+        app.state::<TimeState>()
+            .record_failure(result.error.clone());
+    }
+    result.apply_display_overrides(&app.state::<TimeState>());
+
+    let payload = OffsetUpdated {
+        source: result.source,
+        error: result.error.clone(),
+        offset_millis: result.offset_millis,
+        uncertainty_millis: result.uncertainty_millis,
+        epoch_millis: result.epoch_millis,
+        display_epoch_millis: result.display_epoch_millis,
+        display_rate: result.display_rate,
+        monotonic_anchor_millis: result.monotonic_anchor_millis,
+        provider: result.provider,
+    };
+    if let Err(err) = app.emit(EVENT_TIME_OFFSET_UPDATED, payload) {
+        eprintln!("failed to emit {EVENT_TIME_OFFSET_UPDATED}: {err}");
+    }
+}
+
+async fn query_by_priority(
+    priority: &[ProviderKind],
+    context: &ProviderContext,
+) -> Result<SyncSample, TimeSyncError> {
+    let mut last_error = TimeSyncError::Network("no time source configured".to_string());
+    for kind in priority {
+        let provider = TimeProvider::new(*kind, context);
+        let sample = provider
+            .query(context)
+            .await
+            .and_then(|sample| plausibility::check(&sample, context).map(|()| sample));
+        match sample {
+            Ok(sample) => return Ok(sample),
+            Err(err) => {
+                eprintln!("time source {kind:?} failed: {err}");
+                last_error = err;
+            }
+        }
+    }
+    Err(last_error)
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::{
+    MILLIS_PER_SECOND,
+    extract::{HttpPayload, builtin_rules, extract_epoch_millis},
+};
+
+/// A known provider's response body that can name the instant it describes.
+pub trait ProviderResponse: DeserializeOwned {
+    fn epoch_millis(&self) -> Option<i64>;
+}
+
+/// Decodes `payload` as `T`, falling back to the generic extractor when the
+/// body does not match the expected schema.
+pub fn decode<T: ProviderResponse>(payload: &HttpPayload) -> Option<i64> {
+    payload
+        .json
+        .as_ref()
+        .and_then(|json| {
+            T::deserialize(json)
+                .inspect_err(|err| eprintln!("unexpected provider response shape: {err}"))
+                .ok()
+        })
+        .and_then(|response| response.epoch_millis())
+        .or_else(|| extract_epoch_millis(&builtin_rules(), payload))
+}
+
+/// `GET https://timeapi.io/api/Time/current/zone`. Times are local to
+/// `time_zone` and carry no UTC offset.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeApiResponse {
+    #[serde(alias = "currentLocalTime", alias = "datetime")]
+    date_time: Option<String>,
+    #[serde(alias = "timezone", alias = "timeZoneName")]
+    time_zone: Option<String>,
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+    hour: Option<u32>,
+    minute: Option<u32>,
+    #[serde(alias = "second")]
+    seconds: Option<u32>,
+    #[serde(alias = "milliseconds", alias = "millisecond")]
+    milli_seconds: Option<u32>,
+}
+
+impl TimeApiResponse {
+    fn zone(&self) -> Tz {
+        self.time_zone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    fn components(&self) -> Option<NaiveDateTime> {
+        let date = NaiveDate::from_ymd_opt(self.year?, self.month?, self.day?)?;
+        let time = NaiveTime::from_hms_milli_opt(
+            self.hour?,
+            self.minute?,
+            self.seconds?,
+            self.milli_seconds.unwrap_or(0),
+        )?;
+        Some(NaiveDateTime::new(date, time))
+    }
+}
+
+impl ProviderResponse for TimeApiResponse {
+    fn epoch_millis(&self) -> Option<i64> {
+        if let Some(text) = &self.date_time
+            && let Ok(parsed) = DateTime::parse_from_rfc3339(text)
+        {
+            return Some(parsed.timestamp_millis());
+        }
+
+        let local = self
+            .date_time
+            .as_deref()
+            .and_then(parse_naive)
+            .or_else(|| self.components())?;
+        self.zone()
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|time| time.timestamp_millis())
+    }
+}
+
+/// `GET https://worldtimeapi.org/api/timezone/{zone}`.
+#[derive(Debug, Deserialize)]
+pub struct WorldTimeApiResponse {
+    #[serde(alias = "utcDatetime", alias = "utc_date_time")]
+    utc_datetime: Option<String>,
+    #[serde(alias = "dateTime", alias = "date_time")]
+    datetime: Option<String>,
+    #[serde(alias = "unixTime", alias = "unix_time")]
+    unixtime: Option<i64>,
+}
+
+impl ProviderResponse for WorldTimeApiResponse {
+    fn epoch_millis(&self) -> Option<i64> {
+        [&self.utc_datetime, &self.datetime]
+            .into_iter()
+            .flatten()
+            .find_map(|text| DateTime::parse_from_rfc3339(text).ok())
+            .map(|parsed| parsed.timestamp_millis())
+            .or_else(|| self.unixtime.map(|seconds| seconds * MILLIS_PER_SECOND))
+    }
+}
+
+fn parse_naive(text: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .into_iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+}
@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::UdpSocket;
+
+use super::{
+    MILLIS_PER_SECOND, TimeSyncError, nts,
+    source::{ProviderContext, ProviderKind, SyncSample, TimeSource},
+};
+
+pub const DEFAULT_NTP_SERVER: &str = "pool.ntp.org:123";
+
+pub const EVENT_NTS_FALLBACK: &str = "nts-fallback";
+
+pub(super) const NTP_PACKET_SIZE: usize = 48;
+const NTP_VERSION: u8 = 4;
+const NTP_MODE_CLIENT: u8 = 3;
+const NTP_MODE_SERVER: u8 = 4;
+const NTP_LEAP_UNSYNCHRONIZED: u8 = 3;
+const NTP_MAX_STRATUM: u8 = 15;
+pub(super) const NTP_TIMEOUT: Duration = Duration::from_secs(3);
+/// Longest the NTS servers may take together before plain NTP is used, so a
+/// network that blocks NTS-KE delays each sync by this much at most.
+const NTS_TOTAL_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Seconds between the NTP prime epoch (1900-01-01) and the Unix epoch.
+const NTP_UNIX_EPOCH_DELTA: i64 = 2_208_988_800;
+/// Length of one NTP era (2^32 seconds, roughly 136 years).
+const NTP_ERA_SECONDS: i64 = 1 << 32;
+
+/// Payload of the `nts-fallback` event.
+#[derive(Clone, Serialize)]
+struct NtsFallback {
+    error: TimeSyncError,
+    server: String,
+}
+
+/// Queries the configured NTS servers first and falls back to plain NTP
+/// against `server` when none of them completes an authenticated exchange.
+pub struct NtpSource {
+    server: String,
+    nts_servers: Vec<String>,
+    app: AppHandle,
+}
+
+impl NtpSource {
+    pub fn new(server: &str, context: &ProviderContext) -> Self {
+        Self {
+            server: server.to_string(),
+            nts_servers: context.settings.nts_servers.clone(),
+            app: context.app.clone(),
+        }
+    }
+
+    async fn query_nts(&self) -> Result<(i64, i64), TimeSyncError> {
+        let sessions = self.app.state::<nts::SessionCache>();
+        let servers = async {
+            let mut last_error = TimeSyncError::Network("no nts servers configured".to_string());
+            for server in &self.nts_servers {
+                match nts::query(&sessions, server).await {
+                    Ok(measurement) => return Ok(measurement),
+                    Err(err) => {
+                        eprintln!("nts server {server} failed: {err}");
+                        last_error = err;
+                    }
+                }
+            }
+            Err(last_error)
+        };
+        tokio::time::timeout(NTS_TOTAL_TIMEOUT, servers)
+            .await
+            .map_err(|_| TimeSyncError::Timeout)?
+    }
+}
+
+impl TimeSource for NtpSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let measurement = if self.nts_servers.is_empty() {
+            query(&self.server).await?
+        } else {
+            match self.query_nts().await {
+                Ok(measurement) => measurement,
+                Err(error) => {
+                    eprintln!("nts unavailable, falling back to plain ntp: {error}");
+                    let payload = NtsFallback {
+                        error,
+                        server: self.server.clone(),
+                    };
+                    if let Err(err) = self.app.emit(EVENT_NTS_FALLBACK, payload) {
+                        eprintln!("failed to emit {EVENT_NTS_FALLBACK}: {err}");
+                    }
+                    query(&self.server).await?
+                }
+            }
+        };
+
+        let (offset_millis, rtt_millis) = measurement;
+        Ok(SyncSample {
+            provider: ProviderKind::Ntp,
+            offset_millis,
+            rtt_millis,
+            attempts: 1,
+        })
+    }
+}
+
+/// Queries `server` once using SNTPv4 (RFC 4330) and returns the clock offset
+/// relative to the local system clock together with the round-trip delay,
+/// both in milliseconds.
+async fn query(server: &str) -> Result<(i64, i64), TimeSyncError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+
+    let originate = Utc::now();
+    let originate_raw = to_ntp_timestamp(originate);
+    let request = client_header(originate_raw);
+
+    socket.send(&request).await?;
+
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let received = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| TimeSyncError::Timeout)??;
+    let destination = Utc::now();
+
+    if received < NTP_PACKET_SIZE {
+        return Err(TimeSyncError::Parse);
+    }
+
+    parse_response(&response, originate_raw, originate, destination)
+}
+
+/// Client-mode header carrying `originate_raw` as its transmit timestamp.
+pub(super) fn client_header(originate_raw: u64) -> [u8; NTP_PACKET_SIZE] {
+    let mut header = [0u8; NTP_PACKET_SIZE];
+    header[0] = (NTP_VERSION << 3) | NTP_MODE_CLIENT;
+    header[40..48].copy_from_slice(&originate_raw.to_be_bytes());
+    header
+}
+
+/// Validates a server header and returns `(offset, delay)` in milliseconds.
+pub(super) fn parse_response(
+    packet: &[u8; NTP_PACKET_SIZE],
+    originate_raw: u64,
+    originate: DateTime<Utc>,
+    destination: DateTime<Utc>,
+) -> Result<(i64, i64), TimeSyncError> {
+    let leap = packet[0] >> 6;
+    let mode = packet[0] & 0x07;
+    let stratum = packet[1];
+
+    if mode != NTP_MODE_SERVER || leap == NTP_LEAP_UNSYNCHRONIZED {
+        return Err(TimeSyncError::Parse);
+    }
+    // Stratum 0 is a Kiss-o'-Death packet; the server wants us to back off.
+    if stratum == 0 {
+        return Err(TimeSyncError::RateLimited { retry_after: None });
+    }
+    if stratum > NTP_MAX_STRATUM {
+        return Err(TimeSyncError::Network(format!(
+            "ntp server is unsynchronized (stratum {stratum})"
+        )));
+    }
+
+    let echoed_originate = read_u64(packet, 24);
+    if echoed_originate != originate_raw {
+        return Err(TimeSyncError::Parse);
+    }
+
+    let receive_raw = read_u64(packet, 32);
+    let transmit_raw = read_u64(packet, 40);
+    if transmit_raw == 0 {
+        return Err(TimeSyncError::Parse);
+    }
+
+    let pivot = originate.timestamp();
+    let t1 = originate.timestamp_millis();
+    let t2 = from_ntp_timestamp(receive_raw, pivot);
+    let t3 = from_ntp_timestamp(transmit_raw, pivot);
+    let t4 = destination.timestamp_millis();
+
+    let offset_millis = ((t2 - t1) + (t3 - t4)) / 2;
+    let delay_millis = ((t4 - t1) - (t3 - t2)).max(0);
+    Ok((offset_millis, delay_millis))
+}
+
+fn read_u64(packet: &[u8; NTP_PACKET_SIZE], start: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&packet[start..start + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+pub(super) fn to_ntp_timestamp(time: DateTime<Utc>) -> u64 {
+    let seconds = (time.timestamp() + NTP_UNIX_EPOCH_DELTA).rem_euclid(NTP_ERA_SECONDS) as u64;
+    let fraction = (u64::from(time.timestamp_subsec_nanos()) << 32) / 1_000_000_000;
+    (seconds << 32) | fraction
+}
+
+/// Converts a 64-bit NTP timestamp into Unix milliseconds, choosing the era
+/// closest to `pivot_unix_seconds` so the conversion survives the 2036 rollover.
+fn from_ntp_timestamp(raw: u64, pivot_unix_seconds: i64) -> i64 {
+    let seconds = (raw >> 32) as i64;
+    let fraction = raw & 0xFFFF_FFFF;
+
+    let pivot_ntp = pivot_unix_seconds + NTP_UNIX_EPOCH_DELTA;
+    let era = pivot_ntp.div_euclid(NTP_ERA_SECONDS);
+    let mut ntp_seconds = era * NTP_ERA_SECONDS + seconds;
+    if ntp_seconds - pivot_ntp > NTP_ERA_SECONDS / 2 {
+        ntp_seconds -= NTP_ERA_SECONDS;
+    } else if pivot_ntp - ntp_seconds > NTP_ERA_SECONDS / 2 {
+        ntp_seconds += NTP_ERA_SECONDS;
+    }
+
+    let millis = ((fraction * 1000) >> 32) as i64;
+    (ntp_seconds - NTP_UNIX_EPOCH_DELTA) * MILLIS_PER_SECOND + millis
+}
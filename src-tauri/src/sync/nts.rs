@@ -0,0 +1,393 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use aes_siv::{
+    Aes128SivAead, Nonce,
+    aead::{Aead, KeyInit, Payload},
+};
+use chrono::Utc;
+use rustls::{ClientConfig, RootCertStore, pki_types::ServerName};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+};
+use tokio_rustls::{TlsConnector, client::TlsStream};
+
+use super::{
+    TimeSyncError,
+    ntp::{self, NTP_PACKET_SIZE, NTP_TIMEOUT},
+};
+
+const NTS_KE_PORT: u16 = 4460;
+const NTS_KE_ALPN: &[u8] = b"ntske/1";
+const NTS_KE_TIMEOUT: Duration = Duration::from_secs(5);
+const NTS_DEFAULT_NTP_PORT: u16 = 123;
+
+const RECORD_CRITICAL: u16 = 0x8000;
+const RECORD_END: u16 = 0;
+const RECORD_NEXT_PROTOCOL: u16 = 1;
+const RECORD_ERROR: u16 = 2;
+const RECORD_AEAD: u16 = 4;
+const RECORD_NEW_COOKIE: u16 = 5;
+const RECORD_SERVER: u16 = 6;
+const RECORD_PORT: u16 = 7;
+const MAX_RECORD_COUNT: usize = 64;
+
+const PROTOCOL_NTPV4: u16 = 0;
+/// AEAD_AES_SIV_CMAC_256, the one algorithm every NTS server must support.
+const AEAD_AES_SIV_CMAC_256: u16 = 15;
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 16;
+const EXPORTER_LABEL: &[u8] = b"EXPORTER-network-time-security";
+
+const EXT_UNIQUE_IDENTIFIER: u16 = 0x0104;
+const EXT_COOKIE: u16 = 0x0204;
+const EXT_AUTHENTICATOR: u16 = 0x0404;
+const UNIQUE_IDENTIFIER_SIZE: usize = 32;
+const MAX_RESPONSE_SIZE: usize = 2048;
+
+/// Keys and cookies produced by NTS key establishment (RFC 8915 section 4).
+struct Session {
+    ntp_server: String,
+    c2s: [u8; KEY_SIZE],
+    s2c: [u8; KEY_SIZE],
+    /// Unused cookies; each exchange spends one and receives a fresh one.
+    cookies: Vec<Vec<u8>>,
+}
+
+/// Sessions kept between syncs, by NTS-KE server, so the TLS key
+/// establishment only runs again once a session stops working.
+#[derive(Default)]
+pub struct SessionCache {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionCache {
+    fn take(&self, ke_server: &str) -> Option<Session> {
+        self.sessions.lock().ok()?.remove(ke_server)
+    }
+
+    fn put(&self, ke_server: &str, session: Session) {
+        if session.cookies.is_empty() {
+            return;
+        }
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(ke_server.to_string(), session);
+        }
+    }
+}
+
+/// Runs one authenticated NTP exchange with the cached session for
+/// `ke_server`, or after NTS-KE against it when there is none or it fails,
+/// returning `(offset, delay)` in milliseconds like the plain NTP client.
+pub async fn query(cache: &SessionCache, ke_server: &str) -> Result<(i64, i64), TimeSyncError> {
+    if let Some(mut session) = cache.take(ke_server) {
+        match exchange(&mut session).await {
+            Ok(measurement) => {
+                cache.put(ke_server, session);
+                return Ok(measurement);
+            }
+            Err(err) => eprintln!("cached nts session for {ke_server} failed: {err}"),
+        }
+    }
+
+    let mut session = tokio::time::timeout(NTS_KE_TIMEOUT, establish(ke_server))
+        .await
+        .map_err(|_| TimeSyncError::Timeout)??;
+    let measurement = exchange(&mut session).await?;
+    cache.put(ke_server, session);
+    Ok(measurement)
+}
+
+async fn establish(ke_server: &str) -> Result<Session, TimeSyncError> {
+    let (host, port) = split_host_port(ke_server, NTS_KE_PORT);
+    let mut stream = connect_tls(host, port).await?;
+
+    let mut request = Vec::new();
+    push_record(
+        &mut request,
+        RECORD_NEXT_PROTOCOL,
+        true,
+        &PROTOCOL_NTPV4.to_be_bytes(),
+    );
+    push_record(
+        &mut request,
+        RECORD_AEAD,
+        true,
+        &AEAD_AES_SIV_CMAC_256.to_be_bytes(),
+    );
+    push_record(&mut request, RECORD_END, true, &[]);
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    let mut cookies = Vec::new();
+    let mut ntp_host = host.to_string();
+    let mut ntp_port = NTS_DEFAULT_NTP_PORT;
+    for _ in 0..MAX_RECORD_COUNT {
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await?;
+        let record_type = u16::from_be_bytes([header[0], header[1]]) & !RECORD_CRITICAL;
+        let mut body = vec![0u8; usize::from(u16::from_be_bytes([header[2], header[3]]))];
+        stream.read_exact(&mut body).await?;
+
+        match record_type {
+            RECORD_END => {
+                if cookies.is_empty() {
+                    return Err(TimeSyncError::Parse);
+                }
+                let (c2s, s2c) = export_keys(&stream)?;
+                return Ok(Session {
+                    ntp_server: format!("{ntp_host}:{ntp_port}"),
+                    c2s,
+                    s2c,
+                    cookies,
+                });
+            }
+            RECORD_ERROR => {
+                return Err(TimeSyncError::Network(format!(
+                    "nts-ke server reported error {}",
+                    read_u16(&body)?
+                )));
+            }
+            RECORD_NEXT_PROTOCOL if read_u16(&body)? != PROTOCOL_NTPV4 => {
+                return Err(TimeSyncError::Network(
+                    "nts-ke server rejected ntpv4".to_string(),
+                ));
+            }
+            RECORD_AEAD if read_u16(&body)? != AEAD_AES_SIV_CMAC_256 => {
+                return Err(TimeSyncError::Network(
+                    "nts-ke server chose an unsupported aead".to_string(),
+                ));
+            }
+            RECORD_NEW_COOKIE => cookies.push(body),
+            RECORD_SERVER => {
+                ntp_host = String::from_utf8(body).map_err(|_| TimeSyncError::Parse)?;
+            }
+            RECORD_PORT => ntp_port = read_u16(&body)?,
+            _ => {}
+        }
+    }
+    Err(TimeSyncError::Parse)
+}
+
+async fn connect_tls(host: &str, port: u16) -> Result<TlsStream<TcpStream>, TimeSyncError> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let mut config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(|err| TimeSyncError::Network(err.to_string()))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    config.alpn_protocols = vec![NTS_KE_ALPN.to_vec()];
+
+    let name = ServerName::try_from(host.to_string())
+        .map_err(|_| TimeSyncError::Network(format!("invalid nts-ke host {host}")))?;
+    let tcp = TcpStream::connect((host, port)).await?;
+    Ok(TlsConnector::from(Arc::new(config))
+        .connect(name, tcp)
+        .await?)
+}
+
+fn export_keys(
+    stream: &TlsStream<TcpStream>,
+) -> Result<([u8; KEY_SIZE], [u8; KEY_SIZE]), TimeSyncError> {
+    let connection = stream.get_ref().1;
+    let mut context = [0u8; 5];
+    context[..2].copy_from_slice(&PROTOCOL_NTPV4.to_be_bytes());
+    context[2..4].copy_from_slice(&AEAD_AES_SIV_CMAC_256.to_be_bytes());
+
+    let mut export = |direction: u8| {
+        context[4] = direction;
+        connection
+            .export_keying_material([0u8; KEY_SIZE], EXPORTER_LABEL, Some(&context))
+            .map_err(|err| TimeSyncError::Authentication(err.to_string()))
+    };
+    let c2s = export(0)?;
+    let s2c = export(1)?;
+    Ok((c2s, s2c))
+}
+
+/// Spends one of the session's cookies on an authenticated NTP exchange and
+/// keeps the fresh cookies the server sends back.
+async fn exchange(session: &mut Session) -> Result<(i64, i64), TimeSyncError> {
+    let cookie = session.cookies.pop().ok_or(TimeSyncError::Parse)?;
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(&session.ntp_server).await?;
+
+    let mut unique_id = [0u8; UNIQUE_IDENTIFIER_SIZE];
+    rand::fill(&mut unique_id);
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::fill(&mut nonce);
+
+    let originate = Utc::now();
+    let originate_raw = ntp::to_ntp_timestamp(originate);
+    let mut packet = ntp::client_header(originate_raw).to_vec();
+    push_extension(&mut packet, EXT_UNIQUE_IDENTIFIER, &unique_id);
+    push_extension(&mut packet, EXT_COOKIE, &cookie);
+
+    let cipher = Aes128SivAead::new(&session.c2s.into());
+    let tag = cipher
+        .encrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: &[],
+                aad: &packet,
+            },
+        )
+        .map_err(|_| TimeSyncError::Authentication("failed to seal nts request".to_string()))?;
+    let mut authenticator = Vec::with_capacity(4 + NONCE_SIZE + tag.len());
+    authenticator.extend_from_slice(&(NONCE_SIZE as u16).to_be_bytes());
+    authenticator.extend_from_slice(&(tag.len() as u16).to_be_bytes());
+    authenticator.extend_from_slice(&nonce);
+    authenticator.extend_from_slice(&tag);
+    push_extension(&mut packet, EXT_AUTHENTICATOR, &authenticator);
+
+    socket.send(&packet).await?;
+    let mut response = [0u8; MAX_RESPONSE_SIZE];
+    let received = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| TimeSyncError::Timeout)??;
+    let destination = Utc::now();
+
+    let response = &response[..received];
+    let plaintext = verify_response(response, &unique_id, &session.s2c)?;
+    session.cookies.extend(cookies_in(&plaintext));
+    let header: &[u8; NTP_PACKET_SIZE] = response
+        .get(..NTP_PACKET_SIZE)
+        .and_then(|header| header.try_into().ok())
+        .ok_or(TimeSyncError::Parse)?;
+    ntp::parse_response(header, originate_raw, originate, destination)
+}
+
+/// Checks that the response echoes our unique identifier and that its
+/// authenticator opens under the server-to-client key, returning the
+/// extension fields it encrypted.
+fn verify_response(
+    packet: &[u8],
+    unique_id: &[u8],
+    s2c: &[u8; KEY_SIZE],
+) -> Result<Vec<u8>, TimeSyncError> {
+    let mut offset = NTP_PACKET_SIZE;
+    let mut echoed_id = false;
+    while offset + 4 <= packet.len() {
+        let field_type = u16::from_be_bytes([packet[offset], packet[offset + 1]]);
+        let length = usize::from(u16::from_be_bytes([packet[offset + 2], packet[offset + 3]]));
+        let body = packet
+            .get(offset + 4..offset + length)
+            .filter(|_| length >= 4)
+            .ok_or(TimeSyncError::Parse)?;
+
+        match field_type {
+            EXT_UNIQUE_IDENTIFIER => echoed_id = body == unique_id,
+            EXT_AUTHENTICATOR => {
+                if !echoed_id {
+                    return Err(TimeSyncError::Authentication(
+                        "nts response does not match our request".to_string(),
+                    ));
+                }
+                return open_authenticator(body, &packet[..offset], s2c);
+            }
+            _ => {}
+        }
+        offset += length;
+    }
+    Err(TimeSyncError::Authentication(
+        "nts response is not authenticated".to_string(),
+    ))
+}
+
+fn open_authenticator(
+    body: &[u8],
+    associated_data: &[u8],
+    s2c: &[u8; KEY_SIZE],
+) -> Result<Vec<u8>, TimeSyncError> {
+    let nonce_length = usize::from(read_u16(body.get(..2).ok_or(TimeSyncError::Parse)?)?);
+    let ciphertext_length = usize::from(read_u16(body.get(2..4).ok_or(TimeSyncError::Parse)?)?);
+    if nonce_length != NONCE_SIZE {
+        return Err(TimeSyncError::Parse);
+    }
+    let nonce: [u8; NONCE_SIZE] = body
+        .get(4..4 + nonce_length)
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or(TimeSyncError::Parse)?;
+    let ciphertext_start = 4 + padded(nonce_length);
+    let ciphertext = body
+        .get(ciphertext_start..ciphertext_start + ciphertext_length)
+        .ok_or(TimeSyncError::Parse)?;
+
+    Aes128SivAead::new(&(*s2c).into())
+        .decrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: associated_data,
+            },
+        )
+        .map_err(|_| TimeSyncError::Authentication("nts authenticator mismatch".to_string()))
+}
+
+/// The cookies among the extension fields in `fields`.
+fn cookies_in(fields: &[u8]) -> Vec<Vec<u8>> {
+    let mut cookies = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= fields.len() {
+        let field_type = u16::from_be_bytes([fields[offset], fields[offset + 1]]);
+        let length = usize::from(u16::from_be_bytes([fields[offset + 2], fields[offset + 3]]));
+        let Some(body) = fields
+            .get(offset + 4..offset + length)
+            .filter(|_| length >= 4)
+        else {
+            break;
+        };
+        if field_type == EXT_COOKIE {
+            cookies.push(body.to_vec());
+        }
+        offset += length;
+    }
+    cookies
+}
+
+fn push_record(buffer: &mut Vec<u8>, record_type: u16, critical: bool, body: &[u8]) {
+    let header = if critical {
+        record_type | RECORD_CRITICAL
+    } else {
+        record_type
+    };
+    buffer.extend_from_slice(&header.to_be_bytes());
+    buffer.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(body);
+}
+
+/// Appends an NTP extension field, zero-padding its body to a word boundary.
+fn push_extension(buffer: &mut Vec<u8>, field_type: u16, body: &[u8]) {
+    let length = 4 + padded(body.len());
+    buffer.extend_from_slice(&field_type.to_be_bytes());
+    buffer.extend_from_slice(&(length as u16).to_be_bytes());
+    buffer.extend_from_slice(body);
+    buffer.resize(buffer.len() + padded(body.len()) - body.len(), 0);
+}
+
+fn padded(length: usize) -> usize {
+    length.div_ceil(4) * 4
+}
+
+fn read_u16(bytes: &[u8]) -> Result<u16, TimeSyncError> {
+    let bytes: [u8; 2] = bytes.try_into().map_err(|_| TimeSyncError::Parse)?;
+    Ok(u16::from_be_bytes(bytes))
+}
+
+/// Splits `host:port`, using `default_port` when no port is given.
+fn split_host_port(address: &str, default_port: u16) -> (&str, u16) {
+    match address.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => (host, port),
+            Err(_) => (address, default_port),
+        },
+        None => (address, default_port),
+    }
+}
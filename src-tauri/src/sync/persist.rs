@@ -0,0 +1,53 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::store;
+
+use super::{ProviderKind, clock, state::SyncAnchor};
+
+const LAST_SYNC_FILE_NAME: &str = "last_sync.json";
+
+/// The last successful sync as written to disk. Only wall-clock values are
+/// stored because the monotonic clock restarts with the process.
+#[derive(Serialize, Deserialize)]
+struct PersistedSync {
+    offset_millis: i64,
+    rtt_millis: i64,
+    uncertainty_millis: i64,
+    provider: ProviderKind,
+    synced_at_millis: i64,
+}
+
+/// Restores the last known offset so the clock is corrected before the
+/// first sync of this session completes.
+pub fn load(app: &AppHandle) -> Option<SyncAnchor> {
+    let path = store::data_file(app, LAST_SYNC_FILE_NAME)?;
+    let persisted: PersistedSync = store::read_json(&path)?;
+
+    Some(SyncAnchor {
+        epoch_millis: Utc::now().timestamp_millis() + persisted.offset_millis,
+        monotonic_millis: clock::monotonic_millis(),
+        offset_millis: persisted.offset_millis,
+        rtt_millis: persisted.rtt_millis,
+        uncertainty_millis: persisted.uncertainty_millis,
+        provider: persisted.provider,
+        synced_at_millis: persisted.synced_at_millis,
+    })
+}
+
+pub fn save(app: &AppHandle, anchor: &SyncAnchor) {
+    let Some(path) = store::data_file(app, LAST_SYNC_FILE_NAME) else {
+        return;
+    };
+    let persisted = PersistedSync {
+        offset_millis: anchor.offset_millis,
+        rtt_millis: anchor.rtt_millis,
+        uncertainty_millis: anchor.uncertainty_millis,
+        provider: anchor.provider,
+        synced_at_millis: anchor.synced_at_millis,
+    };
+    if let Err(err) = store::write_json(&path, &persisted) {
+        eprintln!("failed to persist last sync: {err}");
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Datelike, Utc};
+use tauri::Manager;
+
+use super::{
+    TimeSyncError,
+    source::{ProviderContext, ProviderKind, SyncSample},
+    state::TimeState,
+};
+
+/// Rejects samples whose implied time falls outside `sync.plausibility`,
+/// recording the rejection so it shows up in `get_sync_status`.
+pub fn check(sample: &SyncSample, context: &ProviderContext) -> Result<(), TimeSyncError> {
+    if sample.provider == ProviderKind::System {
+        return Ok(());
+    }
+
+    let bounds = &context.settings.plausibility;
+    let epoch_millis = Utc::now().timestamp_millis() + sample.offset_millis;
+    let year = DateTime::from_timestamp_millis(epoch_millis).map(|time| time.year());
+
+    let reason = if !year.is_some_and(|year| (bounds.min_year..=bounds.max_year).contains(&year)) {
+        Some(format!(
+            "{:?} reported a time outside {}–{}",
+            sample.provider, bounds.min_year, bounds.max_year
+        ))
+    } else if let Some(max) = bounds.max_deviation_millis
+        && sample.offset_millis.abs() > max
+    {
+        Some(format!(
+            "{:?} deviates from the local clock by {} ms",
+            sample.provider, sample.offset_millis
+        ))
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => {
+            eprintln!("rejecting sample: {reason}");
+            let error = TimeSyncError::Implausible(reason);
+            context
+                .app
+                .state::<TimeState>()
+                .record_rejection(error.clone());
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    settings::{Settings, SettingsStore},
+    store::StoreError,
+};
+
+use super::{ProviderKind, default_priority, state::TimeState};
+
+pub const EVENT_PROVIDERS_CHANGED: &str = "providers-changed";
+
+const ALL_PROVIDERS: [ProviderKind; 7] = [
+    ProviderKind::Ntp,
+    ProviderKind::TimeApi,
+    ProviderKind::WorldTimeApi,
+    ProviderKind::Roughtime,
+    ProviderKind::HttpDate,
+    ProviderKind::Custom,
+    ProviderKind::System,
+];
+
+/// A provider as shown in the provider editor; the list is in the order
+/// priority syncs try them.
+#[derive(Clone, Serialize)]
+pub struct TimeProviderInfo {
+    provider: ProviderKind,
+    enabled: bool,
+}
+
+/// Every known provider, those in the effective priority order first.
+#[tauri::command]
+pub fn list_time_providers(app: AppHandle) -> Vec<TimeProviderInfo> {
+    describe(&app, &app.state::<SettingsStore>().get())
+}
+
+/// Enables or disables `provider` for all sync modes and persists the choice.
+#[tauri::command]
+pub fn set_provider_enabled(
+    app: AppHandle,
+    provider: ProviderKind,
+    enabled: bool,
+) -> Result<Vec<TimeProviderInfo>, StoreError> {
+    let settings = app.state::<SettingsStore>().update(|settings| {
+        let disabled = &mut settings.sync.disabled_providers;
+        disabled.retain(|kind| *kind != provider);
+        if !enabled {
+            disabled.push(provider);
+        }
+    })?;
+    Ok(publish(&app, &settings))
+}
+
+/// Replaces the provider order used by priority and race syncs and persists
+/// it; `None` restores the built-in, benchmark-ranked order.
+#[tauri::command]
+pub fn set_provider_priority(
+    app: AppHandle,
+    priority: Option<Vec<ProviderKind>>,
+) -> Result<Vec<TimeProviderInfo>, StoreError> {
+    let settings = app.state::<SettingsStore>().update(|settings| {
+        settings.sync.priority = priority.map(|priority| {
+            let mut unique = Vec::with_capacity(priority.len());
+            for kind in priority {
+                if !unique.contains(&kind) {
+                    unique.push(kind);
+                }
+            }
+            unique
+        });
+    })?;
+    Ok(publish(&app, &settings))
+}
+
+fn describe(app: &AppHandle, settings: &Settings) -> Vec<TimeProviderInfo> {
+    let mut order = default_priority(&settings.sync, &app.state::<TimeState>().ranking());
+    for kind in ALL_PROVIDERS {
+        if !order.contains(&kind) {
+            order.push(kind);
+        }
+    }
+    order
+        .into_iter()
+        .map(|provider| TimeProviderInfo {
+            provider,
+            enabled: !settings.sync.disabled_providers.contains(&provider),
+        })
+        .collect()
+}
+
+fn publish(app: &AppHandle, settings: &Settings) -> Vec<TimeProviderInfo> {
+    let providers = describe(app, settings);
+    if let Err(err) = app.emit(EVENT_PROVIDERS_CHANGED, &providers) {
+        eprintln!("failed to emit {EVENT_PROVIDERS_CHANGED}: {err}");
+    }
+    providers
+}
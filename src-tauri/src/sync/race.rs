@@ -0,0 +1,47 @@
+use futures::future::select_ok;
+
+use super::{
+    TimeSyncError, plausibility,
+    source::{ProviderContext, ProviderKind, SyncSample, TimeProvider},
+};
+
+/// Answers slower than this are too imprecise to win a race.
+const MAX_RACE_RTT_MILLIS: i64 = 10_000;
+
+/// Queries every provider concurrently and returns the first sample that
+/// passes sanity checks. The remaining requests are dropped, which cancels them.
+pub async fn query_race(
+    kinds: &[ProviderKind],
+    context: &ProviderContext,
+) -> Result<SyncSample, TimeSyncError> {
+    let providers: Vec<TimeProvider> = kinds
+        .iter()
+        .filter(|kind| **kind != ProviderKind::System)
+        .map(|kind| TimeProvider::new(*kind, context))
+        .collect();
+    if providers.is_empty() {
+        return Err(TimeSyncError::Network(
+            "no time source configured".to_string(),
+        ));
+    }
+
+    let racers = providers.iter().map(|provider| {
+        Box::pin(async move {
+            let sample = provider.query(context).await?;
+            plausibility::check(&sample, context)?;
+            check_rtt(&sample)?;
+            Ok::<_, TimeSyncError>(sample)
+        })
+    });
+    select_ok(racers).await.map(|(sample, _)| sample)
+}
+
+fn check_rtt(sample: &SyncSample) -> Result<(), TimeSyncError> {
+    if !(0..=MAX_RACE_RTT_MILLIS).contains(&sample.rtt_millis) {
+        return Err(TimeSyncError::Implausible(format!(
+            "{:?} answered after {} ms",
+            sample.provider, sample.rtt_millis
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use super::{ProviderKind, TimeSyncError, clock};
+
+/// Back-off used when a provider rate limits without sending `Retry-After`.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(60);
+/// Longest back-off honoured, so a bogus header cannot sideline a provider
+/// for the rest of the session.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Providers that asked us to slow down, each skipped until its back-off
+/// expires so the next provider in the priority list answers instead.
+#[derive(Default)]
+pub struct RateLimits {
+    /// Monotonic time at which each provider may be queried again.
+    until: Mutex<HashMap<ProviderKind, i64>>,
+}
+
+impl RateLimits {
+    /// Starts backing off `provider` if `error` says it is rate limited.
+    pub fn record(&self, provider: ProviderKind, error: &TimeSyncError) {
+        let TimeSyncError::RateLimited { retry_after } = error else {
+            return;
+        };
+        let backoff = retry_after.unwrap_or(DEFAULT_BACKOFF).min(MAX_BACKOFF);
+        let until =
+            clock::monotonic_millis() + i64::try_from(backoff.as_millis()).unwrap_or(i64::MAX);
+        eprintln!(
+            "time source {provider:?} rate limited; skipping it for {}s",
+            backoff.as_secs()
+        );
+        if let Ok(mut limits) = self.until.lock() {
+            limits.insert(provider, until);
+        }
+    }
+
+    /// Time left before `provider` may be queried again, if it is backing off.
+    pub fn remaining(&self, provider: ProviderKind) -> Option<Duration> {
+        let mut limits = self.until.lock().ok()?;
+        let until = *limits.get(&provider)?;
+        let remaining = until - clock::monotonic_millis();
+        if remaining <= 0 {
+            limits.remove(&provider);
+            return None;
+        }
+        Some(Duration::from_millis(remaining.unsigned_abs()))
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+use crate::settings::RetrySettings;
+
+use super::TimeSyncError;
+
+/// Runs `attempt` until it succeeds, fails permanently, or the policy runs out
+/// of attempts or time. Returns the value along with the attempts it took.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetrySettings,
+    mut attempt: F,
+) -> Result<(T, u32), TimeSyncError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, TimeSyncError>>,
+{
+    let deadline = Instant::now() + Duration::from_millis(policy.deadline_ms);
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let err = match attempt().await {
+            Ok(value) => return Ok((value, attempts)),
+            Err(err) => err,
+        };
+
+        if attempts >= max_attempts || !is_retryable(&err) {
+            return Err(err);
+        }
+
+        let delay = backoff_delay(policy, attempts);
+        if Instant::now() + delay >= deadline {
+            return Err(err);
+        }
+        eprintln!("retrying after attempt {attempts} failed: {err}");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn is_retryable(err: &TimeSyncError) -> bool {
+    matches!(err, TimeSyncError::Network(_) | TimeSyncError::Timeout)
+}
+
+/// Exponential backoff (`base * 2^(attempt - 1)`) with symmetric random jitter.
+fn backoff_delay(policy: &RetrySettings, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let base = policy.base_delay_ms.saturating_mul(1 << exponent) as f64;
+    let jitter = policy.jitter_ratio.clamp(0.0, 1.0);
+    let factor = if jitter > 0.0 {
+        1.0 + rand::random_range(-jitter..=jitter)
+    } else {
+        1.0
+    };
+    Duration::from_millis((base * factor).round() as u64)
+}
@@ -0,0 +1,253 @@
+use std::{collections::HashMap, time::Duration};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use sha2::{Digest, Sha512};
+use tokio::net::UdpSocket;
+
+use crate::settings::RoughtimeServer;
+
+use super::{
+    TimeSyncError,
+    source::{ProviderContext, ProviderKind, RoundTrip, SyncSample, TimeSource},
+};
+
+const REQUEST_SIZE: usize = 1024;
+const NONCE_SIZE: usize = 64;
+const MAX_RESPONSE_SIZE: usize = 4096;
+const ROUGHTIME_TIMEOUT: Duration = Duration::from_secs(3);
+const MICROS_PER_MILLI: u64 = 1000;
+
+const DELEGATION_CONTEXT: &[u8] = b"RoughTime v1 delegation signature--\0";
+const RESPONSE_CONTEXT: &[u8] = b"RoughTime v1 response signature\0";
+
+const TAG_SIG: u32 = tag(b"SIG\0");
+const TAG_NONC: u32 = tag(b"NONC");
+const TAG_PAD: u32 = tag(b"PAD\xff");
+const TAG_SREP: u32 = tag(b"SREP");
+const TAG_CERT: u32 = tag(b"CERT");
+const TAG_DELE: u32 = tag(b"DELE");
+const TAG_PUBK: u32 = tag(b"PUBK");
+const TAG_MINT: u32 = tag(b"MINT");
+const TAG_MAXT: u32 = tag(b"MAXT");
+const TAG_ROOT: u32 = tag(b"ROOT");
+const TAG_MIDP: u32 = tag(b"MIDP");
+const TAG_PATH: u32 = tag(b"PATH");
+const TAG_INDX: u32 = tag(b"INDX");
+
+const fn tag(name: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*name)
+}
+
+/// Roughtime client (Google protocol) whose answers are signed by a key
+/// delegated from each server's long-term public key.
+pub struct RoughtimeSource {
+    servers: Vec<RoughtimeServer>,
+}
+
+impl RoughtimeSource {
+    pub fn new(context: &ProviderContext) -> Self {
+        Self {
+            servers: context.settings.roughtime_servers.clone(),
+        }
+    }
+}
+
+impl TimeSource for RoughtimeSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        let mut last_error = TimeSyncError::Network("no roughtime servers configured".to_string());
+        for server in &self.servers {
+            match query(server).await {
+                Ok(sample) => return Ok(sample),
+                Err(err) => {
+                    eprintln!("roughtime server {} failed: {err}", server.address);
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+async fn query(server: &RoughtimeServer) -> Result<SyncSample, TimeSyncError> {
+    let public_key = decode_public_key(&server.public_key)?;
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::fill(&mut nonce);
+    let request = encode_request(&nonce);
+
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(&server.address).await?;
+
+    let round_trip = RoundTrip::start();
+    socket.send(&request).await?;
+    let mut buffer = [0u8; MAX_RESPONSE_SIZE];
+    let received = tokio::time::timeout(ROUGHTIME_TIMEOUT, socket.recv(&mut buffer))
+        .await
+        .map_err(|_| TimeSyncError::Timeout)??;
+    let round_trip = round_trip.finish();
+
+    let midpoint_micros = verify_response(&buffer[..received], &nonce, &public_key)?;
+    let epoch_millis =
+        i64::try_from(midpoint_micros / MICROS_PER_MILLI).map_err(|_| TimeSyncError::Parse)?;
+    Ok(round_trip.sample(ProviderKind::Roughtime, epoch_millis, 1))
+}
+
+fn decode_public_key(encoded: &str) -> Result<VerifyingKey, TimeSyncError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| TimeSyncError::Authentication("invalid roughtime public key".to_string()))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| TimeSyncError::Authentication("invalid roughtime public key".to_string()))?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| TimeSyncError::Authentication("invalid roughtime public key".to_string()))
+}
+
+/// Builds a `{NONC, PAD}` message padded to the 1024 bytes servers require.
+fn encode_request(nonce: &[u8; NONCE_SIZE]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(REQUEST_SIZE);
+    message.extend_from_slice(&2u32.to_le_bytes());
+    message.extend_from_slice(&(NONCE_SIZE as u32).to_le_bytes());
+    message.extend_from_slice(&TAG_NONC.to_le_bytes());
+    message.extend_from_slice(&TAG_PAD.to_le_bytes());
+    message.extend_from_slice(nonce);
+    message.resize(REQUEST_SIZE, 0);
+    message
+}
+
+/// Checks the delegation certificate, the response signature and the Merkle
+/// path for our nonce, returning the signed midpoint in Unix microseconds.
+fn verify_response(
+    packet: &[u8],
+    nonce: &[u8; NONCE_SIZE],
+    public_key: &VerifyingKey,
+) -> Result<u64, TimeSyncError> {
+    let message = parse_message(packet)?;
+    let cert = parse_message(field(&message, TAG_CERT)?)?;
+    let signed_response = field(&message, TAG_SREP)?;
+    let response = parse_message(signed_response)?;
+    let delegation_bytes = field(&cert, TAG_DELE)?;
+    let delegation = parse_message(delegation_bytes)?;
+
+    verify_signature(
+        public_key,
+        DELEGATION_CONTEXT,
+        delegation_bytes,
+        field(&cert, TAG_SIG)?,
+    )?;
+
+    let delegated_key: [u8; 32] = field(&delegation, TAG_PUBK)?
+        .try_into()
+        .map_err(|_| TimeSyncError::Parse)?;
+    let delegated_key = VerifyingKey::from_bytes(&delegated_key)
+        .map_err(|_| TimeSyncError::Authentication("invalid delegated key".to_string()))?;
+    verify_signature(
+        &delegated_key,
+        RESPONSE_CONTEXT,
+        signed_response,
+        field(&message, TAG_SIG)?,
+    )?;
+
+    let index = read_u32(field(&message, TAG_INDX)?)?;
+    let path = field(&message, TAG_PATH)?;
+    let root = field(&response, TAG_ROOT)?;
+    if merkle_root(nonce, index, path)? != root {
+        return Err(TimeSyncError::Authentication(
+            "roughtime response does not cover our nonce".to_string(),
+        ));
+    }
+
+    let midpoint = read_u64(field(&response, TAG_MIDP)?)?;
+    let min_time = read_u64(field(&delegation, TAG_MINT)?)?;
+    let max_time = read_u64(field(&delegation, TAG_MAXT)?)?;
+    if midpoint < min_time || midpoint > max_time {
+        return Err(TimeSyncError::Authentication(
+            "roughtime delegation expired".to_string(),
+        ));
+    }
+
+    Ok(midpoint)
+}
+
+fn verify_signature(
+    key: &VerifyingKey,
+    context: &[u8],
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<(), TimeSyncError> {
+    let signature = Signature::from_slice(signature).map_err(|_| TimeSyncError::Parse)?;
+    let mut signed = Vec::with_capacity(context.len() + payload.len());
+    signed.extend_from_slice(context);
+    signed.extend_from_slice(payload);
+    key.verify(&signed, &signature)
+        .map_err(|_| TimeSyncError::Authentication("roughtime signature mismatch".to_string()))
+}
+
+fn merkle_root(nonce: &[u8], mut index: u32, path: &[u8]) -> Result<Vec<u8>, TimeSyncError> {
+    if !path.len().is_multiple_of(NONCE_SIZE) {
+        return Err(TimeSyncError::Parse);
+    }
+
+    let mut hash = Sha512::new()
+        .chain_update([0u8])
+        .chain_update(nonce)
+        .finalize()
+        .to_vec();
+    for sibling in path.chunks(NONCE_SIZE) {
+        let hasher = Sha512::new().chain_update([1u8]);
+        let hasher = if index & 1 == 0 {
+            hasher.chain_update(&hash).chain_update(sibling)
+        } else {
+            hasher.chain_update(sibling).chain_update(&hash)
+        };
+        hash = hasher.finalize().to_vec();
+        index >>= 1;
+    }
+    Ok(hash)
+}
+
+/// Splits a Roughtime tag/value message into its fields.
+fn parse_message(bytes: &[u8]) -> Result<HashMap<u32, &[u8]>, TimeSyncError> {
+    let count = read_u32(bytes.get(..4).ok_or(TimeSyncError::Parse)?)? as usize;
+    if count == 0 {
+        return Ok(HashMap::new());
+    }
+
+    let header_size = 4 + (count - 1) * 4 + count * 4;
+    let header = bytes.get(..header_size).ok_or(TimeSyncError::Parse)?;
+    let values = &bytes[header_size..];
+
+    let mut offsets = vec![0usize];
+    for index in 0..count - 1 {
+        let start = 4 + index * 4;
+        offsets.push(read_u32(&header[start..start + 4])? as usize);
+    }
+    offsets.push(values.len());
+
+    let mut fields = HashMap::with_capacity(count);
+    for index in 0..count {
+        let start = 4 + (count - 1) * 4 + index * 4;
+        let tag = read_u32(&header[start..start + 4])?;
+        let (from, to) = (offsets[index], offsets[index + 1]);
+        if from > to || to > values.len() || from % 4 != 0 {
+            return Err(TimeSyncError::Parse);
+        }
+        fields.insert(tag, &values[from..to]);
+    }
+    Ok(fields)
+}
+
+fn field<'a>(message: &HashMap<u32, &'a [u8]>, tag: u32) -> Result<&'a [u8], TimeSyncError> {
+    message.get(&tag).copied().ok_or(TimeSyncError::Parse)
+}
+
+fn read_u32(bytes: &[u8]) -> Result<u32, TimeSyncError> {
+    let bytes: [u8; 4] = bytes.try_into().map_err(|_| TimeSyncError::Parse)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(bytes: &[u8]) -> Result<u64, TimeSyncError> {
+    let bytes: [u8; 8] = bytes.try_into().map_err(|_| TimeSyncError::Parse)?;
+    Ok(u64::from_le_bytes(bytes))
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::settings::{SettingsStore, SyncSettings};
+
+use super::state::TimeState;
+
+/// Decides when the background loop syncs next: a short burst of quick
+/// syncs after startup so the offset and drift estimate converge, then the
+/// configured interval with jitter.
+pub struct Scheduler {
+    burst_remaining: u32,
+}
+
+impl Scheduler {
+    pub fn new(settings: &SyncSettings) -> Self {
+        Self {
+            burst_remaining: settings.burst_count,
+        }
+    }
+
+    /// Whether the upcoming sync belongs to the startup burst; burst syncs
+    /// bypass `min_interval_secs` so they are not collapsed into one.
+    pub fn in_burst(&self) -> bool {
+        self.burst_remaining > 0
+    }
+
+    /// Sleeps until the next sync is due, publishing the due time so the
+    /// status command can report it.
+    pub async fn wait(&mut self, app: &AppHandle, on_battery: bool) {
+        let settings = app.state::<SettingsStore>().get();
+        let delay = self.next_delay(&settings.sync, on_battery);
+        let state = app.state::<TimeState>();
+        let delay_millis = i64::try_from(delay.as_millis()).unwrap_or(i64::MAX);
+        state.schedule_next(state.now_millis().saturating_add(delay_millis));
+        tokio::time::sleep(delay).await;
+    }
+
+    fn next_delay(&mut self, settings: &SyncSettings, on_battery: bool) -> Duration {
+        if self.burst_remaining > 0 {
+            self.burst_remaining -= 1;
+            if self.burst_remaining > 0 {
+                return Duration::from_secs(settings.burst_interval_secs.max(1));
+            }
+        }
+
+        let mut base = settings.interval_secs.max(1) as f64;
+        if on_battery {
+            base *= settings.battery_interval_multiplier.max(1.0);
+        }
+        let jitter = settings.jitter_ratio.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            1.0 + rand::random_range(-jitter..=jitter)
+        } else {
+            1.0
+        };
+        Duration::from_secs_f64(base * factor)
+    }
+}
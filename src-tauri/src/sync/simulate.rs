@@ -0,0 +1,141 @@
+use chrono::DateTime;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use super::{TimeSyncError, clock, state::TimeState};
+
+pub const EVENT_SIMULATION_CHANGED: &str = "simulation-changed";
+
+const ARG_SIMULATE_TIME: &str = "--simulate-time";
+const ARG_SIMULATE_SPEED: &str = "--simulate-speed";
+
+/// A fake clock that replaces the displayed time, for demo recordings and
+/// end-to-end tests of things like DST transitions. It starts at a fixed
+/// instant and advances `rate` times as fast as the monotonic clock; a rate
+/// of zero freezes it.
+#[derive(Debug, Clone, Copy)]
+pub struct Simulation {
+    origin_epoch_millis: i64,
+    origin_monotonic_millis: i64,
+    rate: f64,
+}
+
+impl Simulation {
+    pub fn new(epoch_millis: i64, rate: f64) -> Result<Self, TimeSyncError> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(TimeSyncError::InvalidSimulation(format!(
+                "speed must be a non-negative number, got {rate}"
+            )));
+        }
+        Ok(Self {
+            origin_epoch_millis: epoch_millis,
+            origin_monotonic_millis: clock::monotonic_millis(),
+            rate,
+        })
+    }
+
+    /// Simulated time at `monotonic_millis`.
+    pub fn at(&self, monotonic_millis: i64) -> i64 {
+        let elapsed = (monotonic_millis - self.origin_monotonic_millis) as f64;
+        self.origin_epoch_millis + (elapsed * self.rate).round() as i64
+    }
+
+    pub fn now_millis(&self) -> i64 {
+        self.at(clock::monotonic_millis())
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+/// Reads `--simulate-time <rfc3339>` and the optional `--simulate-speed
+/// <factor>` from the command line.
+pub fn from_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<Simulation>, TimeSyncError> {
+    let mut start = None;
+    let mut speed = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            ARG_SIMULATE_TIME => start = Some(expect_value(&mut args, ARG_SIMULATE_TIME)?),
+            ARG_SIMULATE_SPEED => speed = Some(expect_value(&mut args, ARG_SIMULATE_SPEED)?),
+            _ => {}
+        }
+    }
+
+    let Some(start) = start else {
+        return match speed {
+            Some(_) => Err(TimeSyncError::InvalidSimulation(format!(
+                "{ARG_SIMULATE_SPEED} requires {ARG_SIMULATE_TIME}"
+            ))),
+            None => Ok(None),
+        };
+    };
+    let rate = match speed {
+        Some(speed) => speed
+            .parse::<f64>()
+            .map_err(|_| TimeSyncError::InvalidSimulation(format!("invalid speed: {speed}")))?,
+        None => 1.0,
+    };
+    Simulation::new(parse_start(&start)?, rate).map(Some)
+}
+
+fn expect_value(
+    args: &mut impl Iterator<Item = String>,
+    flag: &str,
+) -> Result<String, TimeSyncError> {
+    args.next()
+        .ok_or_else(|| TimeSyncError::InvalidSimulation(format!("{flag} requires a value")))
+}
+
+fn parse_start(value: &str) -> Result<i64, TimeSyncError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|start| start.timestamp_millis())
+        .map_err(|_| TimeSyncError::InvalidSimulation(format!("invalid start time: {value}")))
+}
+
+/// Payload of the `simulation-changed` event.
+#[derive(Clone, Serialize)]
+struct SimulationChanged {
+    active: bool,
+    /// Time to display now, simulated or not.
+    epoch_millis: i64,
+    monotonic_anchor_millis: i64,
+    /// How fast the displayed time advances relative to real time.
+    rate: f64,
+}
+
+/// Starts simulating from `start` (RFC 3339) at `speed` times real time,
+/// replacing any simulation already running.
+#[tauri::command]
+pub fn simulate_time(
+    app: AppHandle,
+    state: State<'_, TimeState>,
+    start: String,
+    speed: Option<f64>,
+) -> Result<(), TimeSyncError> {
+    let simulation = Simulation::new(parse_start(&start)?, speed.unwrap_or(1.0))?;
+    state.set_simulation(Some(simulation));
+    notify(&app, &state);
+    Ok(())
+}
+
+/// Returns the display to the synced time.
+#[tauri::command]
+pub fn stop_simulation(app: AppHandle, state: State<'_, TimeState>) {
+    state.set_simulation(None);
+    notify(&app, &state);
+}
+
+fn notify(app: &AppHandle, state: &TimeState) {
+    let payload = SimulationChanged {
+        active: state.simulation().is_some(),
+        epoch_millis: state.display_now_millis(),
+        monotonic_anchor_millis: clock::monotonic_millis(),
+        rate: state.display_rate(),
+    };
+    if let Err(err) = app.emit(EVENT_SIMULATION_CHANGED, payload) {
+        eprintln!("failed to emit {EVENT_SIMULATION_CHANGED}: {err}");
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{dnd, settings::SkewWarningSettings};
+
+use super::MILLIS_PER_SECOND;
+
+pub const EVENT_SYSTEM_CLOCK_SKEW: &str = "system-clock-skew";
+
+/// Set while the skew is above the threshold, so the OS notification is shown
+/// once per excursion rather than on every sync.
+static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+/// Payload of the `system-clock-skew` event.
+#[derive(Clone, Serialize)]
+struct SystemClockSkew {
+    /// Synced time minus system time; positive when the system clock is behind.
+    offset_millis: i64,
+    threshold_millis: i64,
+}
+
+/// Warns when a fresh sample shows the system clock is off by more than the
+/// configured threshold.
+pub fn check(app: &AppHandle, offset_millis: i64, settings: &SkewWarningSettings) {
+    if offset_millis.abs() <= settings.threshold_millis {
+        NOTIFIED.store(false, Ordering::Relaxed);
+        return;
+    }
+
+    let payload = SystemClockSkew {
+        offset_millis,
+        threshold_millis: settings.threshold_millis,
+    };
+    if let Err(err) = app.emit(EVENT_SYSTEM_CLOCK_SKEW, payload) {
+        eprintln!("failed to emit {EVENT_SYSTEM_CLOCK_SKEW}: {err}");
+    }
+
+    if settings.notify && !dnd::suppresses(app) && !NOTIFIED.swap(true, Ordering::Relaxed) {
+        let direction = if offset_millis > 0 {
+            "behind"
+        } else {
+            "ahead of"
+        };
+        let seconds = offset_millis.abs() as f64 / MILLIS_PER_SECOND as f64;
+        let body = format!("Your system clock is {seconds:.1} s {direction} the synced time.");
+        if let Err(err) = app
+            .notification()
+            .builder()
+            .title("System clock is off")
+            .body(body)
+            .show()
+        {
+            eprintln!("failed to show clock skew notification: {err}");
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{clock, state::TimeState};
+
+pub const EVENT_OFFSET_ADJUSTING: &str = "offset-adjusting";
+
+/// How often `offset-adjusting` is emitted while a correction is in progress.
+const PROGRESS_PERIOD: Duration = Duration::from_millis(250);
+
+/// A correction being spread linearly over a window rather than applied at
+/// once, so the displayed time glides to the synced time.
+#[derive(Debug, Clone, Copy)]
+pub struct Slew {
+    started_monotonic_millis: i64,
+    duration_millis: i64,
+    /// Displayed time minus synced time when the slew began.
+    correction_millis: i64,
+}
+
+impl Slew {
+    pub fn new(correction_millis: i64, duration_millis: i64) -> Self {
+        Self {
+            started_monotonic_millis: clock::monotonic_millis(),
+            duration_millis: duration_millis.max(1),
+            correction_millis,
+        }
+    }
+
+    /// Part of the correction not yet applied at `monotonic_millis`.
+    pub fn remaining_at(&self, monotonic_millis: i64) -> i64 {
+        let elapsed =
+            (monotonic_millis - self.started_monotonic_millis).clamp(0, self.duration_millis);
+        let left = self.duration_millis - elapsed;
+        (self.correction_millis as f64 * left as f64 / self.duration_millis as f64).round() as i64
+    }
+}
+
+/// Payload of the `offset-adjusting` event.
+#[derive(Clone, Serialize)]
+struct OffsetAdjusting {
+    /// Time to display now, partway between the old and the synced time.
+    epoch_millis: i64,
+    monotonic_anchor_millis: i64,
+    /// Correction still to be applied; zero on the final event.
+    remaining_millis: i64,
+}
+
+/// Emits `offset-adjusting` until the active slew completes.
+pub fn spawn_progress(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<TimeState>();
+            let remaining_millis = state.slew_remaining_millis();
+            let payload = OffsetAdjusting {
+                epoch_millis: state.display_now_millis(),
+                monotonic_anchor_millis: clock::monotonic_millis(),
+                remaining_millis,
+            };
+            if let Err(err) = app.emit(EVENT_OFFSET_ADJUSTING, payload) {
+                eprintln!("failed to emit {EVENT_OFFSET_ADJUSTING}: {err}");
+            }
+            if remaining_millis == 0 {
+                break;
+            }
+            tokio::time::sleep(PROGRESS_PERIOD).await;
+        }
+    });
+}
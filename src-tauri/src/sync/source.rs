@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{net::SharedClient, settings::SyncSettings};
+
+use super::{
+    TimeSyncError,
+    http::{CustomSource, DateHeaderSource, TimeApiSource, WorldTimeApiSource},
+    ntp::{self, NtpSource},
+    roughtime::RoughtimeSource,
+    state::TimeState,
+};
+
+/// Identifies a time provider in the sync priority list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Ntp,
+    TimeApi,
+    WorldTimeApi,
+    /// Signed answers from `sync.roughtime_servers`.
+    Roughtime,
+    /// `Date` header of a HEAD request to `sync.date_header_hosts`.
+    HttpDate,
+    /// Endpoints registered in `sync.custom_endpoints`.
+    Custom,
+    System,
+}
+
+/// A single measurement of the local clock against a provider.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncSample {
+    pub provider: ProviderKind,
+    /// Milliseconds to add to the local system clock to obtain provider time.
+    pub offset_millis: i64,
+    /// Measured network round-trip time of the exchange.
+    pub rtt_millis: i64,
+    /// Requests sent before the provider answered, including retries.
+    pub attempts: u32,
+}
+
+pub trait TimeSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError>;
+}
+
+/// Reports the local system clock as-is; useful as the last entry of a
+/// priority list so a sync always produces a sample.
+pub struct SystemClockSource;
+
+impl TimeSource for SystemClockSource {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        Ok(SyncSample {
+            provider: ProviderKind::System,
+            offset_millis: 0,
+            rtt_millis: 0,
+            attempts: 0,
+        })
+    }
+}
+
+/// Everything a provider needs to issue its request.
+pub struct ProviderContext {
+    pub zone: String,
+    pub settings: SyncSettings,
+    pub clients: SharedClient,
+    pub app: AppHandle,
+}
+
+impl ProviderContext {
+    /// HTTP client for `provider`, resolving over DoH when configured.
+    pub fn client(&self, provider: ProviderKind) -> reqwest::Client {
+        self.clients.for_provider(provider)
+    }
+}
+
+pub enum TimeProvider {
+    Ntp(NtpSource),
+    TimeApi(TimeApiSource),
+    WorldTimeApi(WorldTimeApiSource),
+    Roughtime(RoughtimeSource),
+    HttpDate(DateHeaderSource),
+    Custom(CustomSource),
+    System(SystemClockSource),
+}
+
+impl TimeProvider {
+    pub fn new(kind: ProviderKind, context: &ProviderContext) -> Self {
+        match kind {
+            ProviderKind::Ntp => Self::Ntp(NtpSource::new(ntp::DEFAULT_NTP_SERVER, context)),
+            ProviderKind::TimeApi => Self::TimeApi(TimeApiSource::new(context)),
+            ProviderKind::WorldTimeApi => Self::WorldTimeApi(WorldTimeApiSource::new(context)),
+            ProviderKind::Roughtime => Self::Roughtime(RoughtimeSource::new(context)),
+            ProviderKind::HttpDate => Self::HttpDate(DateHeaderSource::new(context)),
+            ProviderKind::Custom => Self::Custom(CustomSource::new(context)),
+            ProviderKind::System => Self::System(SystemClockSource),
+        }
+    }
+
+    pub fn kind(&self) -> ProviderKind {
+        match self {
+            Self::Ntp(_) => ProviderKind::Ntp,
+            Self::TimeApi(_) => ProviderKind::TimeApi,
+            Self::WorldTimeApi(_) => ProviderKind::WorldTimeApi,
+            Self::Roughtime(_) => ProviderKind::Roughtime,
+            Self::HttpDate(_) => ProviderKind::HttpDate,
+            Self::Custom(_) => ProviderKind::Custom,
+            Self::System(_) => ProviderKind::System,
+        }
+    }
+
+    /// Like `now`, but skips a provider that is still backing off from a
+    /// rate limit and starts the back-off when it reports one.
+    pub async fn query(&self, context: &ProviderContext) -> Result<SyncSample, TimeSyncError> {
+        let kind = self.kind();
+        let state = context.app.state::<TimeState>();
+        if let Some(remaining) = state.rate_limits().remaining(kind) {
+            return Err(TimeSyncError::RateLimited {
+                retry_after: Some(remaining),
+            });
+        }
+        self.now()
+            .await
+            .inspect_err(|err| state.rate_limits().record(kind, err))
+    }
+}
+
+impl TimeSource for TimeProvider {
+    async fn now(&self) -> Result<SyncSample, TimeSyncError> {
+        match self {
+            Self::Ntp(source) => source.now().await,
+            Self::TimeApi(source) => source.now().await,
+            Self::WorldTimeApi(source) => source.now().await,
+            Self::Roughtime(source) => source.now().await,
+            Self::HttpDate(source) => source.now().await,
+            Self::Custom(source) => source.now().await,
+            Self::System(source) => source.now().await,
+        }
+    }
+}
+
+/// Local send time and elapsed monotonic time of a request/response exchange.
+pub(super) struct RoundTrip {
+    sent_at_millis: i64,
+    started: Instant,
+    rtt_millis: i64,
+}
+
+impl RoundTrip {
+    pub fn start() -> Self {
+        Self {
+            sent_at_millis: Utc::now().timestamp_millis(),
+            started: Instant::now(),
+            rtt_millis: 0,
+        }
+    }
+
+    /// Marks the moment the response arrived.
+    pub fn finish(mut self) -> Self {
+        self.rtt_millis = i64::try_from(self.started.elapsed().as_millis()).unwrap_or(i64::MAX);
+        self
+    }
+
+    /// Converts a provider timestamp into an offset against the local clock,
+    /// assuming the server stamped it halfway through the round trip.
+    pub fn sample(
+        &self,
+        provider: ProviderKind,
+        remote_epoch_millis: i64,
+        attempts: u32,
+    ) -> SyncSample {
+        let local_midpoint = self.sent_at_millis + self.rtt_millis / 2;
+        SyncSample {
+            provider,
+            offset_millis: remote_epoch_millis - local_midpoint,
+            rtt_millis: self.rtt_millis,
+            attempts,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicI64, Ordering},
+};
+
+use chrono::Utc;
+
+use crate::settings::SlewSettings;
+
+use super::{
+    ProviderKind, TimeSyncError, clock,
+    drift::{DriftModel, PPM},
+    ratelimit::RateLimits,
+    simulate::Simulation,
+    slew::Slew,
+};
+
+/// How fast the error bound widens while the time is extrapolated on the
+/// local oscillator, on top of whatever drift has been corrected for.
+const UNCERTAINTY_GROWTH_PPM: f64 = 20.0;
+
+/// The most recent accepted sync, pinned to the monotonic clock.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncAnchor {
+    pub epoch_millis: i64,
+    pub monotonic_millis: i64,
+    pub offset_millis: i64,
+    pub rtt_millis: i64,
+    /// Error bound at the moment of the sync.
+    pub uncertainty_millis: i64,
+    pub provider: ProviderKind,
+    /// Corrected wall-clock time at which the sync completed.
+    pub synced_at_millis: i64,
+}
+
+impl SyncAnchor {
+    /// Error bound at `now_millis`: the bound measured at sync time, widened
+    /// by how far the local oscillator may have wandered since.
+    pub fn uncertainty_at(&self, now_millis: i64) -> i64 {
+        let age = (now_millis - self.synced_at_millis).max(0) as f64;
+        self.uncertainty_millis + (age * UNCERTAINTY_GROWTH_PPM / PPM).round() as i64
+    }
+}
+
+/// Bookkeeping for the most recent sync attempt, successful or not.
+pub struct LastAttempt {
+    pub finished_millis: i64,
+    pub error: Option<TimeSyncError>,
+}
+
+/// Running statistics about sync health, surfaced by `get_sync_status`.
+#[derive(Debug, Clone, Default)]
+pub struct SyncHealth {
+    pub consecutive_failures: u32,
+    /// Samples discarded by the plausibility check since startup.
+    pub rejected_samples: u32,
+    pub last_rejection: Option<TimeSyncError>,
+    /// Corrected wall-clock time at which the background task syncs next.
+    pub next_sync_millis: Option<i64>,
+    pub last_error: Option<TimeSyncError>,
+}
+
+/// Shared sync state, managed by Tauri so every command sees the same offset.
+#[derive(Default)]
+pub struct TimeState {
+    anchor: Mutex<Option<SyncAnchor>>,
+    health: Mutex<SyncHealth>,
+    drift: Mutex<DriftModel>,
+    slew: Mutex<Option<Slew>>,
+    /// Providers ordered best first by the last benchmark.
+    ranking: Mutex<Vec<ProviderKind>>,
+    /// Fake clock shown instead of the synced time, if one is running.
+    simulation: Mutex<Option<Simulation>>,
+    /// Artificial shift of the displayed time set by `set_debug_offset`.
+    debug_offset_millis: AtomicI64,
+    rate_limits: RateLimits,
+    /// Held for the duration of a sync so concurrent callers share one request.
+    pub(super) sync_guard: tokio::sync::Mutex<Option<LastAttempt>>,
+}
+
+impl TimeState {
+    pub fn record(&self, anchor: SyncAnchor) {
+        if let Ok(mut current) = self.anchor.lock() {
+            *current = Some(anchor);
+        }
+    }
+
+    /// Records a successful sync and feeds it to the drift model. If the
+    /// displayed time would jump by more than `settings.threshold_millis`, the
+    /// jump is slewed over `settings.window_millis` instead and `true` is
+    /// returned.
+    pub fn record_success(&self, anchor: SyncAnchor, settings: &SlewSettings) -> bool {
+        let displayed = self.latest().map(|_| self.now_millis());
+        self.record(anchor);
+
+        if let Ok(mut drift) = self.drift.lock() {
+            drift.add(anchor.synced_at_millis, anchor.offset_millis);
+        }
+        if let Ok(mut health) = self.health.lock() {
+            health.consecutive_failures = 0;
+            health.last_error = None;
+        }
+
+        let Ok(mut slew) = self.slew.lock() else {
+            return false;
+        };
+        *slew = None;
+        let Some(displayed) = displayed else {
+            return false;
+        };
+        let correction_millis = displayed - self.synced_now_millis();
+        if !settings.enabled || correction_millis.abs() <= settings.threshold_millis {
+            return false;
+        }
+        *slew = Some(Slew::new(
+            correction_millis,
+            i64::try_from(settings.window_millis).unwrap_or(i64::MAX),
+        ));
+        true
+    }
+
+    /// Correction the displayed time still has to absorb.
+    pub fn slew_remaining_millis(&self) -> i64 {
+        let now = clock::monotonic_millis();
+        self.slew
+            .lock()
+            .ok()
+            .and_then(|slew| *slew)
+            .map_or(0, |slew| slew.remaining_at(now))
+    }
+
+    /// Current drift estimate, in parts per million.
+    pub fn drift_ppm(&self) -> Option<f64> {
+        self.drift.lock().ok().and_then(|drift| drift.ppm())
+    }
+
+    pub fn record_failure(&self, error: Option<TimeSyncError>) {
+        if let Ok(mut health) = self.health.lock() {
+            health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+            health.last_error = error;
+        }
+    }
+
+    pub fn record_rejection(&self, error: TimeSyncError) {
+        if let Ok(mut health) = self.health.lock() {
+            health.rejected_samples = health.rejected_samples.saturating_add(1);
+            health.last_rejection = Some(error);
+        }
+    }
+
+    pub fn schedule_next(&self, next_sync_millis: i64) {
+        if let Ok(mut health) = self.health.lock() {
+            health.next_sync_millis = Some(next_sync_millis);
+        }
+    }
+
+    pub fn health(&self) -> SyncHealth {
+        self.health
+            .lock()
+            .map(|health| health.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_ranking(&self, ranking: Vec<ProviderKind>) {
+        if let Ok(mut current) = self.ranking.lock() {
+            *current = ranking;
+        }
+    }
+
+    pub fn ranking(&self) -> Vec<ProviderKind> {
+        self.ranking
+            .lock()
+            .map(|ranking| ranking.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_simulation(&self, simulation: Option<Simulation>) {
+        if let Ok(mut current) = self.simulation.lock() {
+            *current = simulation;
+        }
+    }
+
+    pub fn simulation(&self) -> Option<Simulation> {
+        self.simulation
+            .lock()
+            .ok()
+            .and_then(|simulation| *simulation)
+    }
+
+    pub fn set_debug_offset(&self, millis: i64) {
+        self.debug_offset_millis.store(millis, Ordering::Relaxed);
+    }
+
+    pub fn debug_offset_millis(&self) -> i64 {
+        self.debug_offset_millis.load(Ordering::Relaxed)
+    }
+
+    /// Time the clock face shows: the simulated time while a simulation runs,
+    /// `now_millis` otherwise, shifted by the debug offset.
+    pub fn display_now_millis(&self) -> i64 {
+        self.simulation()
+            .map_or_else(|| self.now_millis(), |simulation| simulation.now_millis())
+            + self.debug_offset_millis()
+    }
+
+    /// Time alarms, reminders, chimes, and timers are scheduled against: the
+    /// displayed time, so a simulation or debug offset moves them along with
+    /// the clock face.
+    pub fn schedule_now_millis(&self) -> i64 {
+        self.display_now_millis()
+    }
+
+    /// How fast the displayed time advances relative to real time.
+    pub fn display_rate(&self) -> f64 {
+        self.simulation()
+            .map_or(1.0, |simulation| simulation.rate())
+    }
+
+    pub fn rate_limits(&self) -> &RateLimits {
+        &self.rate_limits
+    }
+
+    pub fn latest(&self) -> Option<SyncAnchor> {
+        self.anchor.lock().ok().and_then(|anchor| *anchor)
+    }
+
+    /// Time to display: the corrected current time, plus whatever part of a
+    /// slewed correction has not been applied yet.
+    pub fn now_millis(&self) -> i64 {
+        self.synced_now_millis() + self.slew_remaining_millis()
+    }
+
+    /// Corrected current time: the last synced instant advanced by monotonic
+    /// elapsed time, scaled by the estimated drift of the local oscillator,
+    /// or the system clock if nothing has been synced yet.
+    pub fn synced_now_millis(&self) -> i64 {
+        match self.latest() {
+            Some(anchor) => {
+                let elapsed = clock::monotonic_millis() - anchor.monotonic_millis;
+                let correction = self
+                    .drift_ppm()
+                    .map_or(0, |ppm| (elapsed as f64 * ppm / PPM).round() as i64);
+                anchor.epoch_millis + elapsed + correction
+            }
+            None => Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Compensates for the system clock having jumped by `delta_millis`.
+    /// The monotonic-based time is unaffected, so only the offset moves.
+    pub fn rebase_system_clock(&self, delta_millis: i64) -> Option<SyncAnchor> {
+        let mut current = self.anchor.lock().ok()?;
+        let anchor = current.as_mut()?;
+        anchor.offset_millis -= delta_millis;
+        if let Ok(mut drift) = self.drift.lock() {
+            drift.shift(delta_millis);
+        }
+        Some(*anchor)
+    }
+
+    /// Accounts for `slept_millis` the monotonic clock missed while the
+    /// machine was suspended, so the corrected time does not lag behind by
+    /// the length of the sleep.
+    pub fn add_sleep(&self, slept_millis: i64) {
+        if let Ok(mut current) = self.anchor.lock()
+            && let Some(anchor) = current.as_mut()
+        {
+            anchor.monotonic_millis -= slept_millis;
+        }
+    }
+
+    /// Current error bound of the corrected time, if a sync has happened.
+    pub fn uncertainty_millis(&self) -> Option<i64> {
+        self.latest()
+            .map(|anchor| anchor.uncertainty_at(self.synced_now_millis()))
+    }
+
+    /// How long ago the current offset was measured.
+    pub fn offset_age_millis(&self) -> Option<i64> {
+        self.latest()
+            .map(|anchor| (self.now_millis() - anchor.synced_at_millis).max(0))
+    }
+}
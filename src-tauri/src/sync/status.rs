@@ -0,0 +1,48 @@
+use serde::Serialize;
+use tauri::State;
+
+use super::{ProviderKind, TimeSyncError, state::TimeState};
+
+/// Snapshot of the backend's sync state for diagnostics.
+#[derive(Serialize)]
+pub struct SyncStatus {
+    /// Corrected wall-clock time of the last successful sync.
+    last_sync_millis: Option<i64>,
+    provider: Option<ProviderKind>,
+    offset_millis: Option<i64>,
+    rtt_millis: Option<i64>,
+    /// Error bound of the corrected time right now.
+    uncertainty_millis: Option<i64>,
+    /// Parts per million; positive when the system clock runs slow.
+    drift_ppm: Option<f64>,
+    /// Syncs that fell back to a cached or system time since the last success.
+    consecutive_failures: u32,
+    last_error: Option<TimeSyncError>,
+    /// Samples discarded as implausible since startup.
+    rejected_samples: u32,
+    last_rejection: Option<TimeSyncError>,
+    next_sync_millis: Option<i64>,
+    /// Shift applied to the displayed time by `set_debug_offset`.
+    debug_offset_millis: i64,
+}
+
+#[tauri::command]
+pub fn get_sync_status(state: State<'_, TimeState>) -> SyncStatus {
+    let anchor = state.latest();
+    let health = state.health();
+
+    SyncStatus {
+        last_sync_millis: anchor.map(|anchor| anchor.synced_at_millis),
+        provider: anchor.map(|anchor| anchor.provider),
+        offset_millis: anchor.map(|anchor| anchor.offset_millis),
+        rtt_millis: anchor.map(|anchor| anchor.rtt_millis),
+        uncertainty_millis: state.uncertainty_millis(),
+        drift_ppm: state.drift_ppm(),
+        consecutive_failures: health.consecutive_failures,
+        last_error: health.last_error,
+        rejected_samples: health.rejected_samples,
+        last_rejection: health.last_rejection,
+        next_sync_millis: health.next_sync_millis,
+        debug_offset_millis: state.debug_offset_millis(),
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{MAIN_WINDOW_LABEL, power::PowerState, settings::SettingsStore};
+
+use super::{
+    DEFAULT_TIME_ZONE, EVENT_SYSTEM_CLOCK_CHANGED, SyncMode, clock, persist, run_sync,
+    scheduler::Scheduler, state::TimeState,
+};
+
+/// How often the wall clock is compared against the monotonic clock.
+const CLOCK_CHECK_PERIOD: Duration = Duration::from_secs(2);
+/// Divergence below this is scheduling noise rather than a clock change.
+const CLOCK_JUMP_THRESHOLD_MILLIS: i64 = 1000;
+/// Time asleep beyond this is a wake from sleep on platforms whose monotonic
+/// clock stops while suspended.
+#[cfg(not(windows))]
+const SLEEP_GAP_THRESHOLD_MILLIS: i64 = 10_000;
+
+/// Payload of the `system-clock-changed` event.
+#[derive(Clone, Serialize)]
+struct SystemClockChanged {
+    /// How far the system clock jumped; positive when it moved forward.
+    delta_millis: i64,
+    /// Offset after compensating for the jump, if a sync has happened.
+    offset_millis: Option<i64>,
+}
+
+/// Spawns the background loop that keeps the offset fresh and pushes every
+/// result to the frontend, so the webview never has to poll, plus the
+/// monitor that keeps the offset valid when the system clock is changed.
+///
+/// On battery the loop syncs less often and skips ticks entirely while the
+/// window is hidden.
+pub fn spawn(app: AppHandle) {
+    spawn_clock_monitor(app.clone());
+    tauri::async_runtime::spawn(async move {
+        let mut scheduler = Scheduler::new(&app.state::<SettingsStore>().get().sync);
+        loop {
+            let on_battery = app.state::<PowerState>().on_battery();
+            if !(on_battery && main_window_hidden(&app)) {
+                let force = scheduler.in_burst();
+                run_sync(&app, DEFAULT_TIME_ZONE, None, SyncMode::default(), force).await;
+            }
+            scheduler.wait(&app, on_battery).await;
+        }
+    });
+}
+
+fn main_window_hidden(app: &AppHandle) -> bool {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .is_some_and(|visible| !visible)
+}
+
+/// Watches for the system clock drifting away from the monotonic clock. When
+/// it jumps, the offset is re-anchored so the displayed time stays put.
+///
+/// Where the monotonic clock stops while suspended, a wake looks like a
+/// forward jump too. The time spent asleep is measured on a clock that keeps
+/// counting and taken out of the jump, so a clock change made across a sleep
+/// is still noticed. The corrected time is moved on by the time asleep, and
+/// a long enough sleep is reported as a resume.
+fn spawn_clock_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut baseline = wall_minus_monotonic();
+        #[cfg(not(windows))]
+        let mut asleep_baseline = asleep_millis();
+        loop {
+            tokio::time::sleep(CLOCK_CHECK_PERIOD).await;
+            let current = wall_minus_monotonic();
+            let delta_millis = current - baseline;
+            baseline = current;
+
+            #[cfg(not(windows))]
+            let slept_millis = {
+                let current = asleep_millis();
+                let slept = current - asleep_baseline;
+                asleep_baseline = current;
+                slept
+            };
+            #[cfg(not(windows))]
+            let delta_millis = delta_millis - slept_millis;
+
+            if delta_millis.abs() >= CLOCK_JUMP_THRESHOLD_MILLIS {
+                system_clock_changed(&app, delta_millis);
+            }
+            #[cfg(not(windows))]
+            if slept_millis >= CLOCK_JUMP_THRESHOLD_MILLIS {
+                app.state::<TimeState>().add_sleep(slept_millis);
+                if slept_millis > SLEEP_GAP_THRESHOLD_MILLIS {
+                    crate::power::resumed(&app);
+                }
+            }
+        }
+    });
+}
+
+fn system_clock_changed(app: &AppHandle, delta_millis: i64) {
+    let anchor = app.state::<TimeState>().rebase_system_clock(delta_millis);
+    if let Some(anchor) = &anchor {
+        persist::save(app, anchor);
+    }
+
+    let payload = SystemClockChanged {
+        delta_millis,
+        offset_millis: anchor.map(|anchor| anchor.offset_millis),
+    };
+    if let Err(err) = app.emit(EVENT_SYSTEM_CLOCK_CHANGED, payload) {
+        eprintln!("failed to emit {EVENT_SYSTEM_CLOCK_CHANGED}: {err}");
+    }
+}
+
+fn wall_minus_monotonic() -> i64 {
+    Utc::now().timestamp_millis() - clock::monotonic_millis()
+}
+
+/// Time the monotonic clock has missed while suspended, since an arbitrary
+/// point.
+#[cfg(not(windows))]
+fn asleep_millis() -> i64 {
+    clock::boot_millis() - clock::monotonic_millis()
+}
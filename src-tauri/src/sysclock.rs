@@ -0,0 +1,128 @@
+use std::{io, process::Command};
+
+use chrono::Utc;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+#[cfg(unix)]
+use crate::sync::MILLIS_PER_SECOND;
+use crate::sync::state::TimeState;
+
+#[derive(Debug, Error)]
+pub enum SetClockError {
+    #[error("no successful sync to correct the clock with")]
+    NotSynced,
+    #[error("cancelled by the user")]
+    Cancelled,
+    #[error("failed to launch the elevation helper: {0}")]
+    Spawn(#[from] io::Error),
+    #[error("the elevated command failed: {0}")]
+    Failed(String),
+}
+
+impl SetClockError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotSynced => "not_synced",
+            Self::Cancelled => "cancelled",
+            Self::Spawn(_) => "spawn",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+impl Serialize for SetClockError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SetClockError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Asks the user to confirm, then steps the OS clock by the current offset
+/// through the platform's elevation prompt. Returns the applied correction.
+///
+/// The helper adjusts relative to its own reading of the clock, so time spent
+/// in the password prompt does not make the correction stale.
+#[tauri::command]
+pub async fn set_system_clock_to_synced(app: AppHandle) -> Result<i64, SetClockError> {
+    if app.state::<TimeState>().latest().is_none() {
+        return Err(SetClockError::NotSynced);
+    }
+    if !confirm(&app).await {
+        return Err(SetClockError::Cancelled);
+    }
+
+    let offset_millis =
+        app.state::<TimeState>().synced_now_millis() - Utc::now().timestamp_millis();
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        elevated_adjust_command(offset_millis).output()
+    })
+    .await
+    .map_err(|err| SetClockError::Failed(err.to_string()))??;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(SetClockError::Failed(if stderr.is_empty() {
+            output.status.to_string()
+        } else {
+            stderr
+        }));
+    }
+    Ok(offset_millis)
+}
+
+async fn confirm(app: &AppHandle) -> bool {
+    let (sender, receiver) = oneshot::channel();
+    app.dialog()
+        .message("Set the system clock to the synced time? Administrator rights are required.")
+        .title("Correct system clock")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancel)
+        .show(move |confirmed| {
+            let _ = sender.send(confirmed);
+        });
+    receiver.await.unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn elevated_adjust_command(offset_millis: i64) -> Command {
+    let offset_seconds = offset_millis as f64 / MILLIS_PER_SECOND as f64;
+    let script = format!(
+        "date -u -s \"$(date +%s.%N | awk '{{printf \"@%.3f\", $1 + ({offset_seconds:.3})}}')\""
+    );
+    let mut command = Command::new("pkexec");
+    command.args(["sh", "-c", &script]);
+    command
+}
+
+/// BSD `date` only sets whole seconds, so the offset is rounded.
+#[cfg(target_os = "macos")]
+fn elevated_adjust_command(offset_millis: i64) -> Command {
+    let offset_seconds = (offset_millis as f64 / MILLIS_PER_SECOND as f64).round() as i64;
+    let script = format!(
+        "do shell script \"date -u $(date -u -v{offset_seconds:+}S +%m%d%H%M%Y.%S)\" with administrator privileges"
+    );
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]);
+    command
+}
+
+/// `Start-Process` succeeds whatever the elevated process does, so its exit
+/// code is passed on; a declined UAC prompt makes `Start-Process` throw.
+#[cfg(windows)]
+fn elevated_adjust_command(offset_millis: i64) -> Command {
+    let inner = format!(
+        "Set-Date -Adjust ([TimeSpan]::FromMilliseconds({offset_millis})) -ErrorAction Stop"
+    );
+    let outer = format!(
+        "$p = Start-Process powershell -Verb RunAs -Wait -PassThru -WindowStyle Hidden -ArgumentList '-NoProfile','-Command','{inner}'; exit $p.ExitCode"
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-Command", &outer]);
+    command
+}
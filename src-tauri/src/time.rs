@@ -0,0 +1,46 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::{
+    sync::{ProviderKind, TimeSyncError, state::TimeState},
+    zone::{LocalComponents, default_zone_name, parse_zone},
+};
+
+#[derive(Serialize)]
+pub struct CurrentTime {
+    epoch_millis: i64,
+    /// Correction applied to the system clock; zero until the first sync.
+    offset_millis: i64,
+    /// Error bound of `epoch_millis`, growing with `offset_age_millis`.
+    uncertainty_millis: Option<i64>,
+    /// How long ago the offset in use was measured, possibly in a previous session.
+    offset_age_millis: Option<i64>,
+    /// Provider of the last sync, or `None` if the system clock is used as-is.
+    provider: Option<ProviderKind>,
+    /// How fast `epoch_millis` advances relative to real time; only differs
+    /// from 1 while a simulation runs.
+    rate: f64,
+    local: LocalComponents,
+}
+
+/// Returns the corrected current time and its wall-clock fields in `time_zone`.
+#[tauri::command]
+pub fn get_current_time(
+    state: State<'_, TimeState>,
+    time_zone: Option<String>,
+) -> Result<CurrentTime, TimeSyncError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let epoch_millis = state.display_now_millis();
+    let local = LocalComponents::at(epoch_millis, zone).ok_or(TimeSyncError::Parse)?;
+    let anchor = state.latest();
+
+    Ok(CurrentTime {
+        epoch_millis,
+        offset_millis: anchor.map_or(0, |anchor| anchor.offset_millis),
+        uncertainty_millis: state.uncertainty_millis(),
+        offset_age_millis: state.offset_age_millis(),
+        provider: anchor.map(|anchor| anchor.provider),
+        rate: state.display_rate(),
+        local,
+    })
+}
@@ -0,0 +1,529 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{
+    AppHandle, Emitter, Manager, State, Wry,
+    menu::{MenuItemBuilder, Submenu, SubmenuBuilder},
+};
+use tauri_plugin_notification::NotificationExt;
+use thiserror::Error;
+
+use crate::{
+    audio::{AudioPlayer, Playback, tones},
+    dnd,
+    settings::{SettingsStore, WebhookEvent},
+    store::{self, StoreError},
+    sync::{clock, state::TimeState},
+    wakelock, webhook,
+    zone::natural,
+};
+
+pub const EVENT_TIMER_TICK: &str = "timer-tick";
+pub const EVENT_TIMER_FINISHED: &str = "timer-finished";
+
+const TIMERS_FILE_NAME: &str = "timers.json";
+/// Longest gap between `timer-tick` events while a timer runs.
+const TICK_PERIOD: Duration = Duration::from_secs(1);
+const MENU_ID_TIMER_PREFIX: &str = "tray-timer:";
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+/// How often running timers are saved even without changes, so the saved
+/// end times follow corrections of the synced clock.
+const CHECKPOINT_PERIOD: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum TimerError {
+    #[error("timer duration must be positive")]
+    InvalidDuration,
+    #[error("no timer with id {0}")]
+    NotFound(u64),
+    #[error("no duration found in \"{0}\"")]
+    UnrecognizedText(String),
+}
+
+impl TimerError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidDuration => "invalid_duration",
+            Self::NotFound(_) => "timer_not_found",
+            Self::UnrecognizedText(_) => "unrecognized_timer_text",
+        }
+    }
+}
+
+impl Serialize for TimerError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TimerError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Run {
+    Running { ends_at_monotonic_millis: i64 },
+    Paused { remaining_millis: i64 },
+}
+
+#[derive(Debug, Clone)]
+struct Timer {
+    id: u64,
+    label: String,
+    duration_millis: i64,
+    run: Run,
+}
+
+impl Timer {
+    fn remaining_millis(&self, now_monotonic_millis: i64) -> i64 {
+        match self.run {
+            Run::Running {
+                ends_at_monotonic_millis,
+            } => (ends_at_monotonic_millis - now_monotonic_millis).max(0),
+            Run::Paused { remaining_millis } => remaining_millis,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.run, Run::Running { .. })
+    }
+
+    fn info(&self, now_monotonic_millis: i64) -> TimerInfo {
+        TimerInfo {
+            id: self.id,
+            label: self.label.clone(),
+            duration_millis: self.duration_millis,
+            remaining_millis: self.remaining_millis(now_monotonic_millis),
+            running: self.is_running(),
+        }
+    }
+}
+
+/// A countdown as the frontend sees it. Also the payload of the
+/// `timer-finished` event; `timer-tick` carries a list of them.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerInfo {
+    id: u64,
+    label: String,
+    duration_millis: i64,
+    remaining_millis: i64,
+    running: bool,
+}
+
+/// A timer as saved to `timers.json`. Running timers keep their end as a
+/// wall-clock instant, since the monotonic clock restarts with the process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedTimer {
+    id: u64,
+    label: String,
+    duration_millis: i64,
+    #[serde(flatten)]
+    run: SavedRun,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum SavedRun {
+    Running { ends_at_epoch_millis: i64 },
+    Paused { remaining_millis: i64 },
+}
+
+/// Running and paused countdowns. They count down on the monotonic clock,
+/// so adjusting the system time does not stretch or cut them short, and
+/// are saved to `timers.json` in the app data directory so they survive a
+/// restart. A timer that ran out while the app was closed finishes as soon
+/// as the app starts.
+pub struct TimerStore {
+    timers: Mutex<Vec<Timer>>,
+    next_id: AtomicU64,
+    path: Option<PathBuf>,
+    /// Titles of the timers in the tray menu, to rebuild it only when one
+    /// of them changes.
+    tray_titles: Mutex<Vec<String>>,
+}
+
+impl TimerStore {
+    /// Reads the saved timers. Needs `TimeState` to convert their saved end
+    /// times back to the monotonic clock.
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, TIMERS_FILE_NAME);
+        let saved: Vec<SavedTimer> = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        let now_epoch_millis = app.state::<TimeState>().schedule_now_millis();
+        let now = clock::monotonic_millis();
+        let timers: Vec<Timer> = saved
+            .into_iter()
+            .map(|timer| Timer {
+                id: timer.id,
+                label: timer.label,
+                duration_millis: timer.duration_millis,
+                run: match timer.run {
+                    SavedRun::Running {
+                        ends_at_epoch_millis,
+                    } => Run::Running {
+                        ends_at_monotonic_millis: now + (ends_at_epoch_millis - now_epoch_millis),
+                    },
+                    SavedRun::Paused { remaining_millis } => Run::Paused { remaining_millis },
+                },
+            })
+            .collect();
+        let last_id = timers.iter().map(|timer| timer.id).max().unwrap_or(0);
+        Self {
+            timers: Mutex::new(timers),
+            next_id: AtomicU64::new(last_id),
+            path,
+            tray_titles: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn save(&self, now_epoch_millis: i64) -> Result<(), StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let now = clock::monotonic_millis();
+        let saved: Vec<SavedTimer> = self.with(|timers| {
+            timers
+                .iter()
+                .map(|timer| SavedTimer {
+                    id: timer.id,
+                    label: timer.label.clone(),
+                    duration_millis: timer.duration_millis,
+                    run: match timer.run {
+                        Run::Running {
+                            ends_at_monotonic_millis,
+                        } => SavedRun::Running {
+                            ends_at_epoch_millis: now_epoch_millis
+                                + (ends_at_monotonic_millis - now),
+                        },
+                        Run::Paused { remaining_millis } => SavedRun::Paused { remaining_millis },
+                    },
+                })
+                .collect()
+        });
+        store::write_json(path, &saved)
+    }
+
+    fn has_running(&self) -> bool {
+        self.with(|timers| timers.iter().any(Timer::is_running))
+    }
+
+    fn with<T>(&self, change: impl FnOnce(&mut Vec<Timer>) -> T) -> T {
+        change(&mut self.timers.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn list(&self) -> Vec<TimerInfo> {
+        let now = clock::monotonic_millis();
+        self.with(|timers| timers.iter().map(|timer| timer.info(now)).collect())
+    }
+
+    fn allocate_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Removes and returns the timers that have run out.
+    fn take_finished(&self) -> Vec<TimerInfo> {
+        let now = clock::monotonic_millis();
+        self.with(|timers| {
+            let (finished, remaining): (Vec<_>, Vec<_>) = timers
+                .drain(..)
+                .partition(|timer| timer.remaining_millis(now) == 0 && timer.is_running());
+            *timers = remaining;
+            finished.iter().map(|timer| timer.info(now)).collect()
+        })
+    }
+
+    /// Milliseconds until the next running timer ends, if any is running.
+    fn next_deadline(&self) -> Option<i64> {
+        let now = clock::monotonic_millis();
+        self.with(|timers| {
+            timers
+                .iter()
+                .filter_map(|timer| match timer.run {
+                    Run::Running {
+                        ends_at_monotonic_millis,
+                    } => Some(ends_at_monotonic_millis - now),
+                    Run::Paused { .. } => None,
+                })
+                .min()
+        })
+    }
+}
+
+/// Starts a countdown of `duration_millis`, alongside any already running.
+#[tauri::command]
+pub fn start_timer(
+    app: AppHandle,
+    timers: State<'_, TimerStore>,
+    duration_millis: i64,
+    label: Option<String>,
+) -> Result<TimerInfo, TimerError> {
+    if duration_millis <= 0 {
+        return Err(TimerError::InvalidDuration);
+    }
+    let now = clock::monotonic_millis();
+    let timer = Timer {
+        id: timers.allocate_id(),
+        label: label.unwrap_or_default(),
+        duration_millis,
+        run: Run::Running {
+            ends_at_monotonic_millis: now.saturating_add(duration_millis),
+        },
+    };
+    let info = timer.info(now);
+    timers.with(|list| list.push(timer));
+    changed(&app, &timers);
+    Ok(info)
+}
+
+/// Starts a timer from quick-entry text such as "pasta 11 min", labeled
+/// with the words around the duration.
+#[tauri::command]
+pub fn start_timer_from_text(
+    app: AppHandle,
+    timers: State<'_, TimerStore>,
+    text: String,
+) -> Result<TimerInfo, TimerError> {
+    let (duration, label) =
+        natural::split_duration(&text).ok_or(TimerError::UnrecognizedText(text))?;
+    start_timer(app, timers, duration.num_milliseconds(), Some(label))
+}
+
+#[tauri::command]
+pub fn pause_timer(
+    app: AppHandle,
+    timers: State<'_, TimerStore>,
+    id: u64,
+) -> Result<TimerInfo, TimerError> {
+    let info = modify(&timers, id, |timer, now| {
+        timer.run = Run::Paused {
+            remaining_millis: timer.remaining_millis(now),
+        };
+    })?;
+    changed(&app, &timers);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn resume_timer(
+    app: AppHandle,
+    timers: State<'_, TimerStore>,
+    id: u64,
+) -> Result<TimerInfo, TimerError> {
+    let info = modify(&timers, id, |timer, now| {
+        timer.run = Run::Running {
+            ends_at_monotonic_millis: now.saturating_add(timer.remaining_millis(now)),
+        };
+    })?;
+    changed(&app, &timers);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn cancel_timer(
+    app: AppHandle,
+    timers: State<'_, TimerStore>,
+    id: u64,
+) -> Result<(), TimerError> {
+    timers.with(|list| {
+        let index = list
+            .iter()
+            .position(|timer| timer.id == id)
+            .ok_or(TimerError::NotFound(id))?;
+        list.remove(index);
+        Ok(())
+    })?;
+    changed(&app, &timers);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_timers(timers: State<'_, TimerStore>) -> Vec<TimerInfo> {
+    timers.list()
+}
+
+/// A submenu for each running timer, titled with its label and the
+/// minutes left, holding an entry that cancels it.
+pub fn tray_submenus(app: &AppHandle) -> tauri::Result<Vec<Submenu<Wry>>> {
+    running(&app.state::<TimerStore>())
+        .iter()
+        .map(|timer| {
+            let item =
+                MenuItemBuilder::with_id(format!("{MENU_ID_TIMER_PREFIX}{}", timer.id), "Cancel")
+                    .build(app)?;
+            SubmenuBuilder::new(app, tray_title(timer))
+                .item(&item)
+                .build()
+        })
+        .collect()
+}
+
+/// Handles clicks on the entries of `tray_submenus`; other ids are ignored.
+pub fn handle_menu_event(app: &AppHandle, id: &str) {
+    let Some(id) = id
+        .strip_prefix(MENU_ID_TIMER_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        return;
+    };
+    if let Err(err) = cancel_timer(app.clone(), app.state(), id) {
+        eprintln!("failed to cancel timer {id} from the tray: {err}");
+    }
+}
+
+fn running(timers: &TimerStore) -> Vec<TimerInfo> {
+    timers
+        .list()
+        .into_iter()
+        .filter(|timer| timer.running)
+        .collect()
+}
+
+/// "Pasta · 11 min left", counting a started minute as a whole one.
+fn tray_title(timer: &TimerInfo) -> String {
+    let minutes = (timer.remaining_millis + MILLIS_PER_MINUTE - 1) / MILLIS_PER_MINUTE;
+    let label = if timer.label.is_empty() {
+        "Timer"
+    } else {
+        &timer.label
+    };
+    format!("{label} · {minutes} min left")
+}
+
+fn modify(
+    timers: &TimerStore,
+    id: u64,
+    change: impl FnOnce(&mut Timer, i64),
+) -> Result<TimerInfo, TimerError> {
+    let now = clock::monotonic_millis();
+    timers.with(|list| {
+        let timer = list
+            .iter_mut()
+            .find(|timer| timer.id == id)
+            .ok_or(TimerError::NotFound(id))?;
+        change(timer, now);
+        Ok(timer.info(now))
+    })
+}
+
+/// Emits `timer-tick` about once a second while any timer runs, and
+/// `timer-finished` as each one ends, with a sound and a notification.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_checkpoint = Instant::now();
+        loop {
+            let timers = app.state::<TimerStore>();
+            let finished = timers.take_finished();
+            let any_finished = !finished.is_empty();
+            if any_finished
+                || (timers.has_running() && last_checkpoint.elapsed() >= CHECKPOINT_PERIOD)
+            {
+                save(&app, &timers);
+                last_checkpoint = Instant::now();
+            }
+            for timer in finished {
+                finish(&app, timer);
+            }
+            keep_awake(&app, &timers);
+            let delay = match timers.next_deadline() {
+                Some(millis_left) => {
+                    tick(&app, &timers);
+                    Duration::from_millis(u64::try_from(millis_left).unwrap_or(0)).min(TICK_PERIOD)
+                }
+                None => {
+                    // The last running timer just ended; drop it from the
+                    // lists.
+                    if any_finished {
+                        tick(&app, &timers);
+                    }
+                    TICK_PERIOD
+                }
+            };
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+/// Holds the wake lock while a timer runs, unless turned off in the
+/// settings.
+fn keep_awake(app: &AppHandle, timers: &TimerStore) {
+    let reason = (timers.has_running()
+        && app
+            .state::<SettingsStore>()
+            .get()
+            .wake_lock
+            .while_timers_run)
+        .then(|| "A timer is running".to_string());
+    if let Err(err) = wakelock::set(app, wakelock::HOLDER_TIMERS, reason) {
+        eprintln!("failed to update the wake lock for timers: {err}");
+    }
+}
+
+/// Announces and saves a change made by a command.
+fn changed(app: &AppHandle, timers: &TimerStore) {
+    tick(app, timers);
+    save(app, timers);
+}
+
+fn save(app: &AppHandle, timers: &TimerStore) {
+    if let Err(err) = timers.save(app.state::<TimeState>().schedule_now_millis()) {
+        eprintln!("failed to save timers: {err}");
+    }
+}
+
+/// Emits `timer-tick`, and rebuilds the tray menu when the minutes shown
+/// there change.
+fn tick(app: &AppHandle, timers: &TimerStore) {
+    if let Err(err) = app.emit(EVENT_TIMER_TICK, timers.list()) {
+        eprintln!("failed to emit {EVENT_TIMER_TICK}: {err}");
+    }
+    let titles: Vec<String> = running(timers).iter().map(tray_title).collect();
+    let mut shown = timers
+        .tray_titles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if *shown != titles {
+        *shown = titles;
+        drop(shown);
+        crate::refresh_tray_menu(app);
+    }
+}
+
+fn finish(app: &AppHandle, timer: TimerInfo) {
+    let playback = Playback {
+        repeat: true,
+        ..Playback::once(1.0)
+    };
+    if let Err(err) = app
+        .state::<AudioPlayer>()
+        .play(tones::DEFAULT_TONE, &playback)
+    {
+        eprintln!("failed to play timer sound: {err}");
+    }
+    let title = if timer.label.is_empty() {
+        "Timer finished"
+    } else {
+        &timer.label
+    };
+    if !dnd::suppresses(app)
+        && let Err(err) = app
+            .notification()
+            .builder()
+            .title(title)
+            .body("Time is up.")
+            .show()
+    {
+        eprintln!("failed to show timer notification: {err}");
+    }
+    crate::alert_main_window(app);
+    webhook::fire(app, WebhookEvent::TimerFinished, &timer.label, Vec::new());
+    if let Err(err) = app.emit(EVENT_TIMER_FINISHED, timer) {
+        eprintln!("failed to emit {EVENT_TIMER_FINISHED}: {err}");
+    }
+}
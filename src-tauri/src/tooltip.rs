@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use chrono::DateTime;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    TRAY_ID_MAIN, alarm,
+    hour_cycle::{self, HourCycle},
+    sync::state::TimeState,
+    zone::active::active_tz,
+};
+
+/// Longest time the tooltip may lag behind the alarms or the sync status.
+/// Minute changes are caught on time regardless.
+const TOOLTIP_REFRESH_PERIOD: Duration = Duration::from_secs(5);
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+
+/// Keeps the tray icon's tooltip showing the displayed time, the next
+/// alarm, and a warning while syncing fails.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut shown = String::new();
+        loop {
+            let text = tooltip(&app);
+            if text != shown {
+                if let Some(tray) = app.tray_by_id(TRAY_ID_MAIN)
+                    && let Err(err) = tray.set_tooltip(Some(&text))
+                {
+                    eprintln!("failed to update tray tooltip: {err}");
+                }
+                shown = text;
+            }
+            let now = app.state::<TimeState>().display_now_millis();
+            let until_next_minute = MILLIS_PER_MINUTE - now.rem_euclid(MILLIS_PER_MINUTE);
+            let delay = Duration::from_millis(u64::try_from(until_next_minute).unwrap_or(0));
+            tokio::time::sleep(delay.min(TOOLTIP_REFRESH_PERIOD)).await;
+        }
+    });
+}
+
+/// "14:32 Asia/Tokyo", then "Next alarm: Wed 07:00 Wake up" and "⚠ Last
+/// sync failed" on lines of their own when they apply.
+fn tooltip(app: &AppHandle) -> String {
+    let state = app.state::<TimeState>();
+    let mut lines = Vec::with_capacity(3);
+    if let Ok(zone) = active_tz(app)
+        && let Some(now) = DateTime::from_timestamp_millis(state.display_now_millis())
+    {
+        let pattern = match hour_cycle::current() {
+            HourCycle::H12 => "%-I:%M %p",
+            HourCycle::H23 => "%H:%M",
+        };
+        let now = now.with_timezone(&zone);
+        lines.push(format!("{} {}", now.format(pattern), zone.name()));
+    }
+    if let Some((when, next)) = alarm::tray::upcoming(app, 1).into_iter().next() {
+        let label = if next.spec.label.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", next.spec.label)
+        };
+        lines.push(format!("Next alarm: {when}{label}"));
+    }
+    if state.health().consecutive_failures > 0 {
+        lines.push("⚠ Last sync failed".to_string());
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager, State};
+use thiserror::Error;
+
+use crate::{
+    settings::SettingsStore,
+    stopwatch::{self, StopwatchStore},
+    store::StoreError,
+};
+
+pub const EVENT_WAKE_LOCK_CHANGED: &str = "wake-lock-changed";
+
+/// Holder set by `prevent_sleep`.
+const HOLDER_USER: &str = "user";
+/// Holder set while timers run, if `wake_lock.while_timers_run` is on.
+pub const HOLDER_TIMERS: &str = "timers";
+/// Holder set while the stopwatch runs, if `wake_lock.while_stopwatch_runs`
+/// is on.
+pub const HOLDER_STOPWATCH: &str = "stopwatch";
+
+#[derive(Debug, Error)]
+pub enum WakeLockError {
+    #[error("failed to keep the system awake: {0}")]
+    Unavailable(String),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl WakeLockError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unavailable(_) => "wake_lock_unavailable",
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for WakeLockError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("WakeLockError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Payload of the `wake-lock-changed` event and result of `get_wake_lock`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeLockStatus {
+    active: bool,
+    /// Why the display is kept on, one entry per holder.
+    reasons: Vec<String>,
+}
+
+/// Keeps the display and the system from sleeping while anything holds the
+/// lock: `SetThreadExecutionState` on Windows, an IOKit power assertion on
+/// macOS, and `systemd-inhibit` elsewhere.
+#[derive(Default)]
+pub struct WakeLockState {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Reason given by each holder.
+    holders: BTreeMap<&'static str, String>,
+    guard: Option<platform::Guard>,
+}
+
+impl WakeLockState {
+    fn status(&self) -> WakeLockStatus {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        WakeLockStatus {
+            active: inner.guard.is_some(),
+            reasons: inner.holders.values().cloned().collect(),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_wake_lock(wake_lock: State<'_, WakeLockState>) -> WakeLockStatus {
+    wake_lock.status()
+}
+
+/// Keeps the display on and the system awake until `allow_sleep`, showing
+/// `reason` to the OS where it asks for one.
+#[tauri::command]
+pub fn prevent_sleep(app: AppHandle, reason: String) -> Result<WakeLockStatus, WakeLockError> {
+    set(&app, HOLDER_USER, Some(reason))?;
+    Ok(app.state::<WakeLockState>().status())
+}
+
+/// Drops the lock taken by `prevent_sleep`. Running timers may still hold
+/// their own.
+#[tauri::command]
+pub fn allow_sleep(app: AppHandle) -> Result<WakeLockStatus, WakeLockError> {
+    set(&app, HOLDER_USER, None)?;
+    Ok(app.state::<WakeLockState>().status())
+}
+
+/// Chooses whether running timers keep the display on.
+#[tauri::command]
+pub fn set_wake_lock_for_timers(app: AppHandle, enabled: bool) -> Result<(), WakeLockError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.wake_lock.while_timers_run = enabled)?;
+    Ok(())
+}
+
+/// Chooses whether a running stopwatch keeps the display on.
+#[tauri::command]
+pub fn set_wake_lock_for_stopwatch(app: AppHandle, enabled: bool) -> Result<(), WakeLockError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.wake_lock.while_stopwatch_runs = enabled)?;
+    stopwatch::keep_awake(&app, app.state::<StopwatchStore>().is_running());
+    Ok(())
+}
+
+/// Sets or, with `None`, clears the reason `holder` keeps the system awake
+/// for, taking or releasing the OS lock when the first holder arrives or the
+/// last one leaves. Emits `wake-lock-changed` on any change. A lock the OS
+/// refused is not retried until the holders change again.
+pub fn set(
+    app: &AppHandle,
+    holder: &'static str,
+    reason: Option<String>,
+) -> Result<(), WakeLockError> {
+    let state = app.state::<WakeLockState>();
+    let acquired = {
+        let mut inner = state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.holders.get(holder) == reason.as_ref() {
+            return Ok(());
+        }
+        match reason {
+            Some(reason) => inner.holders.insert(holder, reason),
+            None => inner.holders.remove(holder),
+        };
+        // Taken afresh so the reason the OS shows stays current.
+        inner.guard = None;
+        if inner.holders.is_empty() {
+            Ok(())
+        } else {
+            let reason = inner
+                .holders
+                .values()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("; ");
+            platform::Guard::acquire(&reason).map(|guard| inner.guard = Some(guard))
+        }
+    };
+    if let Err(err) = app.emit(EVENT_WAKE_LOCK_CHANGED, state.status()) {
+        eprintln!("failed to emit {EVENT_WAKE_LOCK_CHANGED}: {err}");
+    }
+    acquired.map_err(WakeLockError::Unavailable)
+}
+
+/// The execution state belongs to the thread that set it, so a thread of
+/// its own holds it until the guard is dropped.
+#[cfg(windows)]
+mod platform {
+    use std::{sync::mpsc, thread};
+
+    use windows_sys::Win32::System::Power::{
+        ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED, SetThreadExecutionState,
+    };
+
+    pub struct Guard {
+        /// Dropping it ends the holding thread.
+        _stop: mpsc::Sender<()>,
+    }
+
+    impl Guard {
+        pub fn acquire(_reason: &str) -> Result<Self, String> {
+            let (stop, stopped) = mpsc::channel::<()>();
+            let (started, result) = mpsc::channel();
+            thread::Builder::new()
+                .name("wake-lock".to_string())
+                .spawn(move || {
+                    let previous = unsafe {
+                        SetThreadExecutionState(
+                            ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED,
+                        )
+                    };
+                    let _ = started.send(previous != 0);
+                    if previous == 0 {
+                        return;
+                    }
+                    // Returns once the sender is dropped.
+                    let _ = stopped.recv();
+                    unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+                })
+                .map_err(|err| err.to_string())?;
+            match result.recv() {
+                Ok(true) => Ok(Self { _stop: stop }),
+                _ => Err("SetThreadExecutionState failed".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{CString, c_char, c_void};
+
+    type CFStringRef = *const c_void;
+
+    const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    /// kIOPMAssertionLevelOn.
+    const ASSERTION_LEVEL_ON: u32 = 255;
+    /// kIOPMAssertPreventUserIdleDisplaySleep, which keeps the system
+    /// awake too.
+    const ASSERTION_TYPE: &str = "PreventUserIdleDisplaySleep";
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFStringCreateWithCString(
+            allocator: *const c_void,
+            text: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFRelease(object: *const c_void);
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IOPMAssertionCreateWithName(
+            assertion_type: CFStringRef,
+            level: u32,
+            name: CFStringRef,
+            assertion_id: *mut u32,
+        ) -> i32;
+        fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+    }
+
+    pub struct Guard {
+        assertion_id: u32,
+    }
+
+    impl Guard {
+        pub fn acquire(reason: &str) -> Result<Self, String> {
+            let assertion_type = cf_string(ASSERTION_TYPE)?;
+            let name = match cf_string(reason) {
+                Ok(name) => name,
+                Err(err) => {
+                    unsafe { CFRelease(assertion_type) };
+                    return Err(err);
+                }
+            };
+            let mut assertion_id = 0;
+            let status = unsafe {
+                IOPMAssertionCreateWithName(
+                    assertion_type,
+                    ASSERTION_LEVEL_ON,
+                    name,
+                    &mut assertion_id,
+                )
+            };
+            unsafe {
+                CFRelease(name);
+                CFRelease(assertion_type);
+            }
+            if status != 0 {
+                return Err(format!("IOPMAssertionCreateWithName failed: {status:#x}"));
+            }
+            Ok(Self { assertion_id })
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            unsafe { IOPMAssertionRelease(self.assertion_id) };
+        }
+    }
+
+    fn cf_string(text: &str) -> Result<CFStringRef, String> {
+        let text = CString::new(text.replace('\0', "")).map_err(|err| err.to_string())?;
+        let string = unsafe {
+            CFStringCreateWithCString(std::ptr::null(), text.as_ptr(), K_CF_STRING_ENCODING_UTF8)
+        };
+        if string.is_null() {
+            return Err("CFStringCreateWithCString failed".to_string());
+        }
+        Ok(string)
+    }
+}
+
+/// logind holds the inhibitor while `systemd-inhibit` runs its command,
+/// which waits for the app to exit so a crash cannot leave it behind.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use std::process::{Child, Command, Stdio};
+
+    pub struct Guard {
+        child: Child,
+    }
+
+    impl Guard {
+        pub fn acquire(reason: &str) -> Result<Self, String> {
+            let child = Command::new("systemd-inhibit")
+                .args([
+                    "--what=idle:sleep",
+                    "--who=Adaptive Clock",
+                    &format!("--why={reason}"),
+                    "--mode=block",
+                    "tail",
+                    &format!("--pid={}", std::process::id()),
+                    "-f",
+                    "/dev/null",
+                ])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|err| format!("failed to run systemd-inhibit: {err}"))?;
+            Ok(Self { child })
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, SecondsFormat};
+use reqwest::Method;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+use crate::{
+    net::SharedClient,
+    settings::{SettingsStore, Webhook, WebhookEvent},
+    store::StoreError,
+    sync::state::TimeState,
+};
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("invalid webhook url: {0}")]
+    InvalidUrl(String),
+    #[error("invalid webhook method: {0}")]
+    InvalidMethod(String),
+    #[error("webhook request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl WebhookError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidUrl(_) => "invalid_webhook_url",
+            Self::InvalidMethod(_) => "invalid_webhook_method",
+            Self::Request(_) => "webhook_failed",
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for WebhookError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("WebhookError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[tauri::command]
+pub fn list_webhooks(app: AppHandle) -> Vec<Webhook> {
+    app.state::<SettingsStore>().get().webhooks
+}
+
+/// Replaces the configured webhooks after checking each URL and method.
+#[tauri::command]
+pub fn set_webhooks(app: AppHandle, webhooks: Vec<Webhook>) -> Result<(), WebhookError> {
+    for webhook in &webhooks {
+        url(webhook)?;
+        method(webhook)?;
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.webhooks = webhooks)?;
+    Ok(())
+}
+
+/// Sends `webhook` once with sample values, whatever its events, and
+/// returns the response status.
+#[tauri::command]
+pub async fn test_webhook(app: AppHandle, webhook: Webhook) -> Result<u16, WebhookError> {
+    let vars = variables(&app, "test", "Test", Vec::new());
+    send(&app.state::<SharedClient>().get(), &webhook, &vars).await
+}
+
+/// Calls every enabled webhook subscribed to `event` in the background.
+/// Besides `extra`, templates can use `{{event}}`, `{{label}}`, and
+/// `{{time}}`, the corrected time in RFC 3339.
+pub fn fire(app: &AppHandle, event: WebhookEvent, label: &str, extra: Vec<(&'static str, String)>) {
+    let webhooks: Vec<Webhook> = app
+        .state::<SettingsStore>()
+        .get()
+        .webhooks
+        .into_iter()
+        .filter(|webhook| webhook.enabled && webhook.events.contains(&event))
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+    let name = match event {
+        WebhookEvent::AlarmFired => "alarm_fired",
+        WebhookEvent::TimerFinished => "timer_finished",
+        WebhookEvent::PomodoroPhaseEnded => "pomodoro_phase_ended",
+    };
+    let vars = variables(app, name, label, extra);
+    let client = app.state::<SharedClient>().get();
+    for webhook in webhooks {
+        let client = client.clone();
+        let vars = vars.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = send(&client, &webhook, &vars).await {
+                eprintln!("webhook {} for {name} failed: {err}", webhook.url);
+            }
+        });
+    }
+}
+
+fn variables(
+    app: &AppHandle,
+    event: &str,
+    label: &str,
+    extra: Vec<(&'static str, String)>,
+) -> BTreeMap<&'static str, String> {
+    let time = DateTime::from_timestamp_millis(app.state::<TimeState>().now_millis())
+        .map(|now| now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default();
+    let mut vars = BTreeMap::from([
+        ("event", event.to_string()),
+        ("label", label.to_string()),
+        ("time", time),
+    ]);
+    vars.extend(extra);
+    vars
+}
+
+async fn send(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    vars: &BTreeMap<&'static str, String>,
+) -> Result<u16, WebhookError> {
+    let method = method(webhook)?;
+    let mut request = client.request(method.clone(), url(webhook)?);
+    if method != Method::GET && method != Method::HEAD {
+        request = request
+            .header(reqwest::header::CONTENT_TYPE, &webhook.content_type)
+            .body(body(webhook, vars));
+    }
+    let response = request.send().await?.error_for_status()?;
+    Ok(response.status().as_u16())
+}
+
+/// The body template with its variables filled in, in one pass so values
+/// are never read as placeholders. Unknown names are left as written.
+fn body(webhook: &Webhook, vars: &BTreeMap<&'static str, String>) -> String {
+    let Some(template) = &webhook.body else {
+        return serde_json::to_string(vars).unwrap_or_default();
+    };
+    let json = webhook.content_type.contains("json");
+    let escape = |value: &String| {
+        if !json {
+            return value.clone();
+        }
+        // The string's JSON form without its quotes.
+        let quoted = serde_json::to_string(value).unwrap_or_default();
+        quoted
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .unwrap_or_default()
+            .to_string()
+    };
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after
+            .find("}}")
+            .and_then(|end| Some((end, vars.get(after[..end].trim())?)))
+        {
+            Some((end, value)) => {
+                filled.push_str(&escape(value));
+                rest = &after[end + 2..];
+            }
+            None => {
+                filled.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn url(webhook: &Webhook) -> Result<reqwest::Url, WebhookError> {
+    reqwest::Url::parse(webhook.url.trim())
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| WebhookError::InvalidUrl(webhook.url.clone()))
+}
+
+fn method(webhook: &Webhook) -> Result<Method, WebhookError> {
+    Method::from_bytes(webhook.method.trim().to_ascii_uppercase().as_bytes())
+        .map_err(|_| WebhookError::InvalidMethod(webhook.method.clone()))
+}
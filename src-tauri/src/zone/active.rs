@@ -0,0 +1,125 @@
+use chrono_tz::Tz;
+use serde::Serialize;
+use tauri::{
+    AppHandle, Emitter, Manager, Wry,
+    menu::{CheckMenuItemBuilder, Submenu, SubmenuBuilder},
+};
+
+use crate::{
+    alarm, countdown, reminder,
+    settings::{Settings, SettingsStore},
+    sync::TimeSyncError,
+};
+
+use super::{ZoneError, default_zone_name, parse_zone};
+
+pub const EVENT_ACTIVE_ZONE_CHANGED: &str = "active-zone-changed";
+
+const MENU_ID_SYSTEM_ZONE: &str = "tray-zone-system";
+const MENU_ID_ZONE_PREFIX: &str = "tray-zone:";
+
+/// The zone the main clock shows. Also the payload of the
+/// `active-zone-changed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveZone {
+    time_zone: String,
+    /// Whether `time_zone` is the OS zone and moves with it.
+    follows_system: bool,
+}
+
+impl ActiveZone {
+    fn from_settings(settings: &Settings) -> Self {
+        match &settings.active_zone {
+            Some(time_zone) => Self {
+                time_zone: time_zone.clone(),
+                follows_system: false,
+            },
+            None => Self {
+                time_zone: default_zone_name(),
+                follows_system: true,
+            },
+        }
+    }
+}
+
+/// The zone the main clock shows, which also places alarms and other
+/// wall-clock schedules.
+pub fn active_tz(app: &AppHandle) -> Result<Tz, TimeSyncError> {
+    let settings = app.state::<SettingsStore>().get();
+    parse_zone(&settings.active_zone.unwrap_or_else(default_zone_name))
+}
+
+#[tauri::command]
+pub fn get_active_zone(app: AppHandle) -> ActiveZone {
+    ActiveZone::from_settings(&app.state::<SettingsStore>().get())
+}
+
+/// Makes the main clock show `time_zone`, or follow the OS zone when `None`,
+/// and persists the choice.
+#[tauri::command]
+pub fn set_active_zone(app: AppHandle, time_zone: Option<String>) -> Result<ActiveZone, ZoneError> {
+    let name = time_zone
+        .map(|name| parse_zone(&name).map(|zone| zone.name().to_string()))
+        .transpose()?;
+    let settings = app
+        .state::<SettingsStore>()
+        .update(|settings| settings.active_zone = name)?;
+
+    let active = ActiveZone::from_settings(&settings);
+    if let Err(err) = app.emit(EVENT_ACTIVE_ZONE_CHANGED, &active) {
+        eprintln!("failed to emit {EVENT_ACTIVE_ZONE_CHANGED}: {err}");
+    }
+    crate::refresh_tray_menu(&app);
+    reschedule(&app);
+    Ok(active)
+}
+
+/// Moves the alarms, reminders, and countdowns that follow the main clock
+/// on to its current zone.
+pub fn reschedule(app: &AppHandle) {
+    if let Err(err) = alarm::rezone(app) {
+        eprintln!("failed to reschedule alarms for the new time zone: {err}");
+    }
+    if let Err(err) = reminder::rezone(app) {
+        eprintln!("failed to reschedule reminders for the new time zone: {err}");
+    }
+    if let Err(err) = countdown::rezone(app) {
+        eprintln!("failed to reschedule countdowns for the new time zone: {err}");
+    }
+}
+
+/// The tray's "Timezone" submenu: the OS zone followed by the favorites,
+/// with the active one checked.
+pub fn tray_submenu(app: &AppHandle, settings: &Settings) -> tauri::Result<Submenu<Wry>> {
+    let system = CheckMenuItemBuilder::with_id(MENU_ID_SYSTEM_ZONE, "System time zone")
+        .checked(settings.active_zone.is_none())
+        .build(app)?;
+    let mut submenu = SubmenuBuilder::new(app, "Timezone").item(&system);
+    if !settings.favorite_zones.is_empty() {
+        submenu = submenu.separator();
+    }
+    for time_zone in &settings.favorite_zones {
+        let item = CheckMenuItemBuilder::with_id(
+            format!("{MENU_ID_ZONE_PREFIX}{time_zone}"),
+            time_zone.replace('_', " "),
+        )
+        .checked(settings.active_zone.as_ref() == Some(time_zone))
+        .build(app)?;
+        submenu = submenu.item(&item);
+    }
+    submenu.build()
+}
+
+/// Handles clicks on the entries of `tray_submenu`; other ids are ignored.
+pub fn handle_menu_event(app: &AppHandle, id: &str) {
+    let time_zone = if id == MENU_ID_SYSTEM_ZONE {
+        None
+    } else if let Some(name) = id.strip_prefix(MENU_ID_ZONE_PREFIX) {
+        Some(name.to_string())
+    } else {
+        return;
+    };
+    if let Err(err) = set_active_zone(app.clone(), time_zone) {
+        eprintln!("failed to switch time zone from the tray: {err}");
+    }
+}
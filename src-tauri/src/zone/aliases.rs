@@ -0,0 +1,220 @@
+/// Place names the IANA names do not cover, such as cities sharing another
+/// city's zone and older spellings, with the zone they observe.
+pub const CITY_ALIASES: &[(&str, &str)] = &[
+    ("UTC", "Etc/UTC"),
+    ("GMT", "Etc/GMT"),
+    ("Osaka", "Asia/Tokyo"),
+    ("Kyoto", "Asia/Tokyo"),
+    ("Nagoya", "Asia/Tokyo"),
+    ("Sapporo", "Asia/Tokyo"),
+    ("Fukuoka", "Asia/Tokyo"),
+    ("Busan", "Asia/Seoul"),
+    ("Beijing", "Asia/Shanghai"),
+    ("Peking", "Asia/Shanghai"),
+    ("Shenzhen", "Asia/Shanghai"),
+    ("Guangzhou", "Asia/Shanghai"),
+    ("Canton", "Asia/Shanghai"),
+    ("Chengdu", "Asia/Shanghai"),
+    ("Macau", "Asia/Macau"),
+    ("Mumbai", "Asia/Kolkata"),
+    ("Bombay", "Asia/Kolkata"),
+    ("Delhi", "Asia/Kolkata"),
+    ("New Delhi", "Asia/Kolkata"),
+    ("Bangalore", "Asia/Kolkata"),
+    ("Bengaluru", "Asia/Kolkata"),
+    ("Chennai", "Asia/Kolkata"),
+    ("Madras", "Asia/Kolkata"),
+    ("Hyderabad", "Asia/Kolkata"),
+    ("Calcutta", "Asia/Kolkata"),
+    ("Saigon", "Asia/Ho_Chi_Minh"),
+    ("Hanoi", "Asia/Bangkok"),
+    ("Abu Dhabi", "Asia/Dubai"),
+    ("Doha", "Asia/Qatar"),
+    ("Tel Aviv", "Asia/Jerusalem"),
+    ("Washington", "America/New_York"),
+    ("Boston", "America/New_York"),
+    ("Philadelphia", "America/New_York"),
+    ("Atlanta", "America/New_York"),
+    ("Miami", "America/New_York"),
+    ("Dallas", "America/Chicago"),
+    ("Houston", "America/Chicago"),
+    ("Austin", "America/Chicago"),
+    ("Minneapolis", "America/Chicago"),
+    ("Salt Lake City", "America/Denver"),
+    ("San Francisco", "America/Los_Angeles"),
+    ("San Jose", "America/Los_Angeles"),
+    ("San Diego", "America/Los_Angeles"),
+    ("Seattle", "America/Los_Angeles"),
+    ("Portland", "America/Los_Angeles"),
+    ("Las Vegas", "America/Los_Angeles"),
+    ("Silicon Valley", "America/Los_Angeles"),
+    ("Hawaii", "Pacific/Honolulu"),
+    ("Montreal", "America/Toronto"),
+    ("Ottawa", "America/Toronto"),
+    ("Calgary", "America/Edmonton"),
+    ("Rio de Janeiro", "America/Sao_Paulo"),
+    ("Brasilia", "America/Sao_Paulo"),
+    ("Munich", "Europe/Berlin"),
+    ("Frankfurt", "Europe/Berlin"),
+    ("Hamburg", "Europe/Berlin"),
+    ("Cologne", "Europe/Berlin"),
+    ("Milan", "Europe/Rome"),
+    ("Florence", "Europe/Rome"),
+    ("Naples", "Europe/Rome"),
+    ("Barcelona", "Europe/Madrid"),
+    ("Geneva", "Europe/Zurich"),
+    ("Saint Petersburg", "Europe/Moscow"),
+    ("St Petersburg", "Europe/Moscow"),
+    ("Kiev", "Europe/Kyiv"),
+    ("Edinburgh", "Europe/London"),
+    ("Manchester", "Europe/London"),
+    ("The Hague", "Europe/Amsterdam"),
+    ("Rotterdam", "Europe/Amsterdam"),
+    ("Krakow", "Europe/Warsaw"),
+    ("Cape Town", "Africa/Johannesburg"),
+    ("Canberra", "Australia/Sydney"),
+    ("Wellington", "Pacific/Auckland"),
+];
+
+/// Countries and the zones in them; a country with several zones lists each.
+pub const COUNTRY_ZONES: &[(&str, &str)] = &[
+    ("Japan", "Asia/Tokyo"),
+    ("South Korea", "Asia/Seoul"),
+    ("Korea", "Asia/Seoul"),
+    ("North Korea", "Asia/Pyongyang"),
+    ("China", "Asia/Shanghai"),
+    ("Hong Kong", "Asia/Hong_Kong"),
+    ("Taiwan", "Asia/Taipei"),
+    ("Mongolia", "Asia/Ulaanbaatar"),
+    ("Philippines", "Asia/Manila"),
+    ("Vietnam", "Asia/Ho_Chi_Minh"),
+    ("Thailand", "Asia/Bangkok"),
+    ("Cambodia", "Asia/Phnom_Penh"),
+    ("Malaysia", "Asia/Kuala_Lumpur"),
+    ("Singapore", "Asia/Singapore"),
+    ("Indonesia", "Asia/Jakarta"),
+    ("Indonesia", "Asia/Makassar"),
+    ("Indonesia", "Asia/Jayapura"),
+    ("Myanmar", "Asia/Yangon"),
+    ("Bangladesh", "Asia/Dhaka"),
+    ("India", "Asia/Kolkata"),
+    ("Nepal", "Asia/Kathmandu"),
+    ("Sri Lanka", "Asia/Colombo"),
+    ("Pakistan", "Asia/Karachi"),
+    ("Afghanistan", "Asia/Kabul"),
+    ("Uzbekistan", "Asia/Tashkent"),
+    ("Kazakhstan", "Asia/Almaty"),
+    ("Iran", "Asia/Tehran"),
+    ("Iraq", "Asia/Baghdad"),
+    ("Saudi Arabia", "Asia/Riyadh"),
+    ("United Arab Emirates", "Asia/Dubai"),
+    ("UAE", "Asia/Dubai"),
+    ("Qatar", "Asia/Qatar"),
+    ("Kuwait", "Asia/Kuwait"),
+    ("Israel", "Asia/Jerusalem"),
+    ("Jordan", "Asia/Amman"),
+    ("Lebanon", "Asia/Beirut"),
+    ("Syria", "Asia/Damascus"),
+    ("Turkey", "Europe/Istanbul"),
+    ("Georgia", "Asia/Tbilisi"),
+    ("Armenia", "Asia/Yerevan"),
+    ("Azerbaijan", "Asia/Baku"),
+    ("Russia", "Europe/Moscow"),
+    ("Russia", "Asia/Yekaterinburg"),
+    ("Russia", "Asia/Novosibirsk"),
+    ("Russia", "Asia/Vladivostok"),
+    ("Ukraine", "Europe/Kyiv"),
+    ("Belarus", "Europe/Minsk"),
+    ("Poland", "Europe/Warsaw"),
+    ("Czechia", "Europe/Prague"),
+    ("Czech Republic", "Europe/Prague"),
+    ("Slovakia", "Europe/Bratislava"),
+    ("Hungary", "Europe/Budapest"),
+    ("Romania", "Europe/Bucharest"),
+    ("Bulgaria", "Europe/Sofia"),
+    ("Greece", "Europe/Athens"),
+    ("Serbia", "Europe/Belgrade"),
+    ("Croatia", "Europe/Zagreb"),
+    ("Austria", "Europe/Vienna"),
+    ("Germany", "Europe/Berlin"),
+    ("Switzerland", "Europe/Zurich"),
+    ("Italy", "Europe/Rome"),
+    ("France", "Europe/Paris"),
+    ("Belgium", "Europe/Brussels"),
+    ("Netherlands", "Europe/Amsterdam"),
+    ("Holland", "Europe/Amsterdam"),
+    ("Luxembourg", "Europe/Luxembourg"),
+    ("Denmark", "Europe/Copenhagen"),
+    ("Norway", "Europe/Oslo"),
+    ("Sweden", "Europe/Stockholm"),
+    ("Finland", "Europe/Helsinki"),
+    ("Estonia", "Europe/Tallinn"),
+    ("Latvia", "Europe/Riga"),
+    ("Lithuania", "Europe/Vilnius"),
+    ("Iceland", "Atlantic/Reykjavik"),
+    ("Ireland", "Europe/Dublin"),
+    ("United Kingdom", "Europe/London"),
+    ("UK", "Europe/London"),
+    ("Great Britain", "Europe/London"),
+    ("England", "Europe/London"),
+    ("Scotland", "Europe/London"),
+    ("Spain", "Europe/Madrid"),
+    ("Spain", "Atlantic/Canary"),
+    ("Portugal", "Europe/Lisbon"),
+    ("Portugal", "Atlantic/Azores"),
+    ("Morocco", "Africa/Casablanca"),
+    ("Algeria", "Africa/Algiers"),
+    ("Tunisia", "Africa/Tunis"),
+    ("Egypt", "Africa/Cairo"),
+    ("Nigeria", "Africa/Lagos"),
+    ("Ghana", "Africa/Accra"),
+    ("Senegal", "Africa/Dakar"),
+    ("Ethiopia", "Africa/Addis_Ababa"),
+    ("Kenya", "Africa/Nairobi"),
+    ("Tanzania", "Africa/Dar_es_Salaam"),
+    ("South Africa", "Africa/Johannesburg"),
+    ("Australia", "Australia/Sydney"),
+    ("Australia", "Australia/Melbourne"),
+    ("Australia", "Australia/Brisbane"),
+    ("Australia", "Australia/Adelaide"),
+    ("Australia", "Australia/Darwin"),
+    ("Australia", "Australia/Perth"),
+    ("New Zealand", "Pacific/Auckland"),
+    ("Fiji", "Pacific/Fiji"),
+    ("United States", "America/New_York"),
+    ("United States", "America/Chicago"),
+    ("United States", "America/Denver"),
+    ("United States", "America/Phoenix"),
+    ("United States", "America/Los_Angeles"),
+    ("United States", "America/Anchorage"),
+    ("United States", "Pacific/Honolulu"),
+    ("USA", "America/New_York"),
+    ("USA", "America/Chicago"),
+    ("USA", "America/Denver"),
+    ("USA", "America/Los_Angeles"),
+    ("Canada", "America/St_Johns"),
+    ("Canada", "America/Halifax"),
+    ("Canada", "America/Toronto"),
+    ("Canada", "America/Winnipeg"),
+    ("Canada", "America/Edmonton"),
+    ("Canada", "America/Vancouver"),
+    ("Mexico", "America/Mexico_City"),
+    ("Mexico", "America/Tijuana"),
+    ("Guatemala", "America/Guatemala"),
+    ("Costa Rica", "America/Costa_Rica"),
+    ("Panama", "America/Panama"),
+    ("Cuba", "America/Havana"),
+    ("Jamaica", "America/Jamaica"),
+    ("Puerto Rico", "America/Puerto_Rico"),
+    ("Colombia", "America/Bogota"),
+    ("Venezuela", "America/Caracas"),
+    ("Ecuador", "America/Guayaquil"),
+    ("Peru", "America/Lima"),
+    ("Bolivia", "America/La_Paz"),
+    ("Brazil", "America/Sao_Paulo"),
+    ("Brazil", "America/Manaus"),
+    ("Paraguay", "America/Asuncion"),
+    ("Uruguay", "America/Montevideo"),
+    ("Argentina", "America/Argentina/Buenos_Aires"),
+    ("Chile", "America/Santiago"),
+];
@@ -0,0 +1,168 @@
+use chrono::{Datelike, NaiveDate};
+use icu::{
+    calendar::Date,
+    datetime::{DateTimeFormatter, fieldsets::YMDE},
+    locale::{
+        Locale,
+        extensions::unicode::{Value, key},
+    },
+};
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::State;
+use thiserror::Error;
+
+use crate::sync::{TimeSyncError, state::TimeState};
+
+use super::{ZoneError, default_zone_name, info, parse_zone};
+
+/// Calendars `format_in_calendar` accepts, as BCP 47 `ca` values.
+const CALENDARS: [&str; 14] = [
+    "buddhist",
+    "chinese",
+    "coptic",
+    "dangi",
+    "ethiopic",
+    "gregory",
+    "hebrew",
+    "indian",
+    "islamic-civil",
+    "islamic-tbla",
+    "islamic-umalqura",
+    "japanese",
+    "persian",
+    "roc",
+];
+
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    #[error("unsupported calendar: {0}")]
+    Unsupported(String),
+    #[error("unsupported locale: {0}")]
+    InvalidLocale(String),
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+}
+
+impl CalendarError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unsupported(_) => "unsupported_calendar",
+            Self::InvalidLocale(_) => "invalid_locale",
+            Self::InvalidTime(_) => "invalid_time",
+            Self::Zone(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for CalendarError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Self::Zone(err) = self {
+            return err.serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("CalendarError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarInfo {
+    time_zone: String,
+    /// ISO 8601 week-numbering year, which differs from the calendar year
+    /// for a few days around New Year.
+    iso_year: i32,
+    iso_week: u32,
+    /// Week of the month, weeks starting on Monday; the week containing the
+    /// 1st is week 1.
+    week_of_month: u32,
+    day_of_year: u32,
+    quarter: u32,
+    days_in_year: u32,
+    /// Days left in the year after today.
+    days_remaining_in_year: u32,
+}
+
+/// Calendar facts about the displayed date in `time_zone`.
+#[tauri::command]
+pub fn get_calendar_info(
+    state: State<'_, TimeState>,
+    time_zone: Option<String>,
+) -> Result<CalendarInfo, ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let now_millis = state.display_now_millis();
+    let date = info::at(zone, now_millis)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {now_millis}")))?
+        .date_naive();
+
+    let week = date.iso_week();
+    let first_of_month = date.with_day(1).unwrap_or(date);
+    let lead = first_of_month.weekday().num_days_from_monday();
+    let days_in_year = if NaiveDate::from_ymd_opt(date.year(), 2, 29).is_some() {
+        366
+    } else {
+        365
+    };
+
+    Ok(CalendarInfo {
+        time_zone: zone.name().to_string(),
+        iso_year: week.year(),
+        iso_week: week.week(),
+        week_of_month: (date.day() + lead - 1) / 7 + 1,
+        day_of_year: date.ordinal(),
+        quarter: date.month0() / 3 + 1,
+        days_in_year,
+        days_remaining_in_year: days_in_year - date.ordinal(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarDate {
+    calendar: String,
+    /// The locale used, with the calendar as its `-u-ca-` extension.
+    locale: String,
+    text: String,
+}
+
+/// Formats the date of `epoch_millis` (the displayed time by default) in
+/// `time_zone` using `calendar`, e.g. "令和8年10月15日木曜日" for `japanese`
+/// in `ja`. `hijri` is accepted for `islamic-umalqura`.
+#[tauri::command]
+pub fn format_in_calendar(
+    state: State<'_, TimeState>,
+    calendar: String,
+    locale: String,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+) -> Result<CalendarDate, CalendarError> {
+    let calendar = match calendar.trim().to_ascii_lowercase().as_str() {
+        "hijri" | "islamic" => "islamic-umalqura".to_string(),
+        "gregorian" => "gregory".to_string(),
+        other if CALENDARS.contains(&other) => other.to_string(),
+        _ => return Err(CalendarError::Unsupported(calendar)),
+    };
+    let mut locale: Locale = locale
+        .parse()
+        .map_err(|_| CalendarError::InvalidLocale(locale.clone()))?;
+    let value =
+        Value::try_from_str(&calendar).map_err(|_| CalendarError::Unsupported(calendar.clone()))?;
+    locale.extensions.unicode.keywords.set(key!("ca"), value);
+
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let epoch_millis = epoch_millis.unwrap_or_else(|| state.display_now_millis());
+    let local = info::at(zone, epoch_millis)
+        .ok_or_else(|| CalendarError::InvalidTime(format!("out of range: {epoch_millis}")))?;
+    let date = Date::try_new_iso(local.year(), local.month() as u8, local.day() as u8)
+        .map_err(|err| CalendarError::InvalidTime(err.to_string()))?;
+
+    let formatter = DateTimeFormatter::try_new((&locale).into(), YMDE::long())
+        .map_err(|err| CalendarError::InvalidLocale(format!("{locale}: {err}")))?;
+    let text = formatter.format(&date).to_string();
+    Ok(CalendarDate {
+        calendar,
+        locale: locale.to_string(),
+        text,
+    })
+}
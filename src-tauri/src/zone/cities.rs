@@ -0,0 +1,218 @@
+/// A populated place with its main airport and the zone it observes.
+#[derive(Debug, Clone, Copy)]
+pub struct City {
+    pub name: &'static str,
+    pub country: &'static str,
+    /// IATA code of the main airport serving the city.
+    pub airport: Option<&'static str>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub time_zone: &'static str,
+}
+
+const fn city(
+    name: &'static str,
+    country: &'static str,
+    airport: Option<&'static str>,
+    latitude: f64,
+    longitude: f64,
+    time_zone: &'static str,
+) -> City {
+    City {
+        name,
+        country,
+        airport,
+        latitude,
+        longitude,
+        time_zone,
+    }
+}
+
+/// Major cities, roughly the ones people keep world clocks for. Coordinates
+/// are city centres to two decimals, enough for sunrise and sunset.
+#[rustfmt::skip]
+pub const CITIES: &[City] = &[
+    city("Tokyo", "Japan", Some("HND"), 35.68, 139.69, "Asia/Tokyo"),
+    city("Osaka", "Japan", Some("KIX"), 34.69, 135.50, "Asia/Tokyo"),
+    city("Nagoya", "Japan", Some("NGO"), 35.18, 136.91, "Asia/Tokyo"),
+    city("Sapporo", "Japan", Some("CTS"), 43.06, 141.35, "Asia/Tokyo"),
+    city("Fukuoka", "Japan", Some("FUK"), 33.59, 130.40, "Asia/Tokyo"),
+    city("Seoul", "South Korea", Some("ICN"), 37.57, 126.98, "Asia/Seoul"),
+    city("Busan", "South Korea", Some("PUS"), 35.18, 129.08, "Asia/Seoul"),
+    city("Beijing", "China", Some("PEK"), 39.90, 116.41, "Asia/Shanghai"),
+    city("Shanghai", "China", Some("PVG"), 31.23, 121.47, "Asia/Shanghai"),
+    city("Guangzhou", "China", Some("CAN"), 23.13, 113.26, "Asia/Shanghai"),
+    city("Shenzhen", "China", Some("SZX"), 22.54, 114.06, "Asia/Shanghai"),
+    city("Chengdu", "China", Some("TFU"), 30.57, 104.07, "Asia/Shanghai"),
+    city("Hong Kong", "Hong Kong", Some("HKG"), 22.32, 114.17, "Asia/Hong_Kong"),
+    city("Macau", "Macau", Some("MFM"), 22.20, 113.54, "Asia/Macau"),
+    city("Taipei", "Taiwan", Some("TPE"), 25.03, 121.57, "Asia/Taipei"),
+    city("Ulaanbaatar", "Mongolia", Some("UBN"), 47.89, 106.91, "Asia/Ulaanbaatar"),
+    city("Manila", "Philippines", Some("MNL"), 14.60, 120.98, "Asia/Manila"),
+    city("Ho Chi Minh City", "Vietnam", Some("SGN"), 10.82, 106.63, "Asia/Ho_Chi_Minh"),
+    city("Hanoi", "Vietnam", Some("HAN"), 21.03, 105.85, "Asia/Bangkok"),
+    city("Bangkok", "Thailand", Some("BKK"), 13.76, 100.50, "Asia/Bangkok"),
+    city("Phnom Penh", "Cambodia", Some("PNH"), 11.56, 104.92, "Asia/Phnom_Penh"),
+    city("Kuala Lumpur", "Malaysia", Some("KUL"), 3.14, 101.69, "Asia/Kuala_Lumpur"),
+    city("Singapore", "Singapore", Some("SIN"), 1.35, 103.82, "Asia/Singapore"),
+    city("Jakarta", "Indonesia", Some("CGK"), -6.21, 106.85, "Asia/Jakarta"),
+    city("Denpasar", "Indonesia", Some("DPS"), -8.65, 115.22, "Asia/Makassar"),
+    city("Yangon", "Myanmar", Some("RGN"), 16.87, 96.20, "Asia/Yangon"),
+    city("Dhaka", "Bangladesh", Some("DAC"), 23.81, 90.41, "Asia/Dhaka"),
+    city("Kolkata", "India", Some("CCU"), 22.57, 88.36, "Asia/Kolkata"),
+    city("Delhi", "India", Some("DEL"), 28.61, 77.21, "Asia/Kolkata"),
+    city("Mumbai", "India", Some("BOM"), 19.08, 72.88, "Asia/Kolkata"),
+    city("Bengaluru", "India", Some("BLR"), 12.97, 77.59, "Asia/Kolkata"),
+    city("Chennai", "India", Some("MAA"), 13.08, 80.27, "Asia/Kolkata"),
+    city("Hyderabad", "India", Some("HYD"), 17.39, 78.49, "Asia/Kolkata"),
+    city("Kathmandu", "Nepal", Some("KTM"), 27.72, 85.32, "Asia/Kathmandu"),
+    city("Colombo", "Sri Lanka", Some("CMB"), 6.93, 79.86, "Asia/Colombo"),
+    city("Karachi", "Pakistan", Some("KHI"), 24.86, 67.01, "Asia/Karachi"),
+    city("Lahore", "Pakistan", Some("LHE"), 31.55, 74.34, "Asia/Karachi"),
+    city("Kabul", "Afghanistan", Some("KBL"), 34.56, 69.21, "Asia/Kabul"),
+    city("Tashkent", "Uzbekistan", Some("TAS"), 41.30, 69.24, "Asia/Tashkent"),
+    city("Almaty", "Kazakhstan", Some("ALA"), 43.24, 76.89, "Asia/Almaty"),
+    city("Tehran", "Iran", Some("IKA"), 35.69, 51.39, "Asia/Tehran"),
+    city("Baghdad", "Iraq", Some("BGW"), 33.32, 44.37, "Asia/Baghdad"),
+    city("Riyadh", "Saudi Arabia", Some("RUH"), 24.71, 46.68, "Asia/Riyadh"),
+    city("Jeddah", "Saudi Arabia", Some("JED"), 21.49, 39.19, "Asia/Riyadh"),
+    city("Dubai", "United Arab Emirates", Some("DXB"), 25.20, 55.27, "Asia/Dubai"),
+    city("Abu Dhabi", "United Arab Emirates", Some("AUH"), 24.45, 54.38, "Asia/Dubai"),
+    city("Doha", "Qatar", Some("DOH"), 25.29, 51.53, "Asia/Qatar"),
+    city("Kuwait City", "Kuwait", Some("KWI"), 29.38, 47.99, "Asia/Kuwait"),
+    city("Jerusalem", "Israel", None, 31.77, 35.21, "Asia/Jerusalem"),
+    city("Tel Aviv", "Israel", Some("TLV"), 32.09, 34.78, "Asia/Jerusalem"),
+    city("Amman", "Jordan", Some("AMM"), 31.95, 35.93, "Asia/Amman"),
+    city("Beirut", "Lebanon", Some("BEY"), 33.89, 35.50, "Asia/Beirut"),
+    city("Istanbul", "Turkey", Some("IST"), 41.01, 28.98, "Europe/Istanbul"),
+    city("Ankara", "Turkey", Some("ESB"), 39.93, 32.86, "Europe/Istanbul"),
+    city("Tbilisi", "Georgia", Some("TBS"), 41.72, 44.79, "Asia/Tbilisi"),
+    city("Yerevan", "Armenia", Some("EVN"), 40.18, 44.51, "Asia/Yerevan"),
+    city("Baku", "Azerbaijan", Some("GYD"), 40.41, 49.87, "Asia/Baku"),
+    city("Moscow", "Russia", Some("SVO"), 55.76, 37.62, "Europe/Moscow"),
+    city("Saint Petersburg", "Russia", Some("LED"), 59.93, 30.34, "Europe/Moscow"),
+    city("Yekaterinburg", "Russia", Some("SVX"), 56.84, 60.61, "Asia/Yekaterinburg"),
+    city("Novosibirsk", "Russia", Some("OVB"), 55.01, 82.93, "Asia/Novosibirsk"),
+    city("Vladivostok", "Russia", Some("VVO"), 43.12, 131.89, "Asia/Vladivostok"),
+    city("Kyiv", "Ukraine", Some("KBP"), 50.45, 30.52, "Europe/Kyiv"),
+    city("Minsk", "Belarus", Some("MSQ"), 53.90, 27.56, "Europe/Minsk"),
+    city("Warsaw", "Poland", Some("WAW"), 52.23, 21.01, "Europe/Warsaw"),
+    city("Krakow", "Poland", Some("KRK"), 50.06, 19.94, "Europe/Warsaw"),
+    city("Prague", "Czechia", Some("PRG"), 50.08, 14.44, "Europe/Prague"),
+    city("Bratislava", "Slovakia", Some("BTS"), 48.15, 17.11, "Europe/Bratislava"),
+    city("Budapest", "Hungary", Some("BUD"), 47.50, 19.04, "Europe/Budapest"),
+    city("Bucharest", "Romania", Some("OTP"), 44.43, 26.10, "Europe/Bucharest"),
+    city("Sofia", "Bulgaria", Some("SOF"), 42.70, 23.32, "Europe/Sofia"),
+    city("Athens", "Greece", Some("ATH"), 37.98, 23.73, "Europe/Athens"),
+    city("Belgrade", "Serbia", Some("BEG"), 44.79, 20.45, "Europe/Belgrade"),
+    city("Zagreb", "Croatia", Some("ZAG"), 45.81, 15.98, "Europe/Zagreb"),
+    city("Vienna", "Austria", Some("VIE"), 48.21, 16.37, "Europe/Vienna"),
+    city("Berlin", "Germany", Some("BER"), 52.52, 13.40, "Europe/Berlin"),
+    city("Munich", "Germany", Some("MUC"), 48.14, 11.58, "Europe/Berlin"),
+    city("Frankfurt", "Germany", Some("FRA"), 50.11, 8.68, "Europe/Berlin"),
+    city("Hamburg", "Germany", Some("HAM"), 53.55, 9.99, "Europe/Berlin"),
+    city("Zurich", "Switzerland", Some("ZRH"), 47.38, 8.54, "Europe/Zurich"),
+    city("Geneva", "Switzerland", Some("GVA"), 46.20, 6.14, "Europe/Zurich"),
+    city("Rome", "Italy", Some("FCO"), 41.90, 12.50, "Europe/Rome"),
+    city("Milan", "Italy", Some("MXP"), 45.46, 9.19, "Europe/Rome"),
+    city("Paris", "France", Some("CDG"), 48.86, 2.35, "Europe/Paris"),
+    city("Lyon", "France", Some("LYS"), 45.76, 4.84, "Europe/Paris"),
+    city("Nice", "France", Some("NCE"), 43.70, 7.27, "Europe/Paris"),
+    city("Brussels", "Belgium", Some("BRU"), 50.85, 4.35, "Europe/Brussels"),
+    city("Amsterdam", "Netherlands", Some("AMS"), 52.37, 4.90, "Europe/Amsterdam"),
+    city("Luxembourg", "Luxembourg", Some("LUX"), 49.61, 6.13, "Europe/Luxembourg"),
+    city("Copenhagen", "Denmark", Some("CPH"), 55.68, 12.57, "Europe/Copenhagen"),
+    city("Oslo", "Norway", Some("OSL"), 59.91, 10.75, "Europe/Oslo"),
+    city("Stockholm", "Sweden", Some("ARN"), 59.33, 18.07, "Europe/Stockholm"),
+    city("Helsinki", "Finland", Some("HEL"), 60.17, 24.94, "Europe/Helsinki"),
+    city("Tallinn", "Estonia", Some("TLL"), 59.44, 24.75, "Europe/Tallinn"),
+    city("Riga", "Latvia", Some("RIX"), 56.95, 24.11, "Europe/Riga"),
+    city("Vilnius", "Lithuania", Some("VNO"), 54.69, 25.28, "Europe/Vilnius"),
+    city("Reykjavik", "Iceland", Some("KEF"), 64.15, -21.94, "Atlantic/Reykjavik"),
+    city("Dublin", "Ireland", Some("DUB"), 53.35, -6.26, "Europe/Dublin"),
+    city("London", "United Kingdom", Some("LHR"), 51.51, -0.13, "Europe/London"),
+    city("Manchester", "United Kingdom", Some("MAN"), 53.48, -2.24, "Europe/London"),
+    city("Edinburgh", "United Kingdom", Some("EDI"), 55.95, -3.19, "Europe/London"),
+    city("Madrid", "Spain", Some("MAD"), 40.42, -3.70, "Europe/Madrid"),
+    city("Barcelona", "Spain", Some("BCN"), 41.39, 2.17, "Europe/Madrid"),
+    city("Las Palmas", "Spain", Some("LPA"), 28.12, -15.44, "Atlantic/Canary"),
+    city("Lisbon", "Portugal", Some("LIS"), 38.72, -9.14, "Europe/Lisbon"),
+    city("Ponta Delgada", "Portugal", Some("PDL"), 37.74, -25.67, "Atlantic/Azores"),
+    city("Casablanca", "Morocco", Some("CMN"), 33.57, -7.59, "Africa/Casablanca"),
+    city("Algiers", "Algeria", Some("ALG"), 36.75, 3.06, "Africa/Algiers"),
+    city("Tunis", "Tunisia", Some("TUN"), 36.81, 10.18, "Africa/Tunis"),
+    city("Cairo", "Egypt", Some("CAI"), 30.04, 31.24, "Africa/Cairo"),
+    city("Lagos", "Nigeria", Some("LOS"), 6.52, 3.38, "Africa/Lagos"),
+    city("Accra", "Ghana", Some("ACC"), 5.60, -0.19, "Africa/Accra"),
+    city("Dakar", "Senegal", Some("DSS"), 14.72, -17.47, "Africa/Dakar"),
+    city("Addis Ababa", "Ethiopia", Some("ADD"), 9.03, 38.74, "Africa/Addis_Ababa"),
+    city("Nairobi", "Kenya", Some("NBO"), -1.29, 36.82, "Africa/Nairobi"),
+    city("Dar es Salaam", "Tanzania", Some("DAR"), -6.79, 39.21, "Africa/Dar_es_Salaam"),
+    city("Johannesburg", "South Africa", Some("JNB"), -26.20, 28.05, "Africa/Johannesburg"),
+    city("Cape Town", "South Africa", Some("CPT"), -33.92, 18.42, "Africa/Johannesburg"),
+    city("Sydney", "Australia", Some("SYD"), -33.87, 151.21, "Australia/Sydney"),
+    city("Melbourne", "Australia", Some("MEL"), -37.81, 144.96, "Australia/Melbourne"),
+    city("Brisbane", "Australia", Some("BNE"), -27.47, 153.03, "Australia/Brisbane"),
+    city("Adelaide", "Australia", Some("ADL"), -34.93, 138.60, "Australia/Adelaide"),
+    city("Darwin", "Australia", Some("DRW"), -12.46, 130.84, "Australia/Darwin"),
+    city("Perth", "Australia", Some("PER"), -31.95, 115.86, "Australia/Perth"),
+    city("Canberra", "Australia", Some("CBR"), -35.28, 149.13, "Australia/Sydney"),
+    city("Auckland", "New Zealand", Some("AKL"), -36.85, 174.76, "Pacific/Auckland"),
+    city("Wellington", "New Zealand", Some("WLG"), -41.29, 174.78, "Pacific/Auckland"),
+    city("Suva", "Fiji", Some("SUV"), -18.14, 178.44, "Pacific/Fiji"),
+    city("Honolulu", "United States", Some("HNL"), 21.31, -157.86, "Pacific/Honolulu"),
+    city("Anchorage", "United States", Some("ANC"), 61.22, -149.90, "America/Anchorage"),
+    city("Los Angeles", "United States", Some("LAX"), 34.05, -118.24, "America/Los_Angeles"),
+    city("San Francisco", "United States", Some("SFO"), 37.77, -122.42, "America/Los_Angeles"),
+    city("San Jose", "United States", Some("SJC"), 37.34, -121.89, "America/Los_Angeles"),
+    city("San Diego", "United States", Some("SAN"), 32.72, -117.16, "America/Los_Angeles"),
+    city("Seattle", "United States", Some("SEA"), 47.61, -122.33, "America/Los_Angeles"),
+    city("Portland", "United States", Some("PDX"), 45.52, -122.68, "America/Los_Angeles"),
+    city("Las Vegas", "United States", Some("LAS"), 36.17, -115.14, "America/Los_Angeles"),
+    city("Phoenix", "United States", Some("PHX"), 33.45, -112.07, "America/Phoenix"),
+    city("Denver", "United States", Some("DEN"), 39.74, -104.99, "America/Denver"),
+    city("Salt Lake City", "United States", Some("SLC"), 40.76, -111.89, "America/Denver"),
+    city("Dallas", "United States", Some("DFW"), 32.78, -96.80, "America/Chicago"),
+    city("Houston", "United States", Some("IAH"), 29.76, -95.37, "America/Chicago"),
+    city("Austin", "United States", Some("AUS"), 30.27, -97.74, "America/Chicago"),
+    city("Chicago", "United States", Some("ORD"), 41.88, -87.63, "America/Chicago"),
+    city("Minneapolis", "United States", Some("MSP"), 44.98, -93.27, "America/Chicago"),
+    city("Atlanta", "United States", Some("ATL"), 33.75, -84.39, "America/New_York"),
+    city("Miami", "United States", Some("MIA"), 25.76, -80.19, "America/New_York"),
+    city("Washington", "United States", Some("IAD"), 38.91, -77.04, "America/New_York"),
+    city("Philadelphia", "United States", Some("PHL"), 39.95, -75.17, "America/New_York"),
+    city("New York", "United States", Some("JFK"), 40.71, -74.01, "America/New_York"),
+    city("Boston", "United States", Some("BOS"), 42.36, -71.06, "America/New_York"),
+    city("Detroit", "United States", Some("DTW"), 42.33, -83.05, "America/Detroit"),
+    city("Toronto", "Canada", Some("YYZ"), 43.65, -79.38, "America/Toronto"),
+    city("Montreal", "Canada", Some("YUL"), 45.50, -73.57, "America/Toronto"),
+    city("Ottawa", "Canada", Some("YOW"), 45.42, -75.70, "America/Toronto"),
+    city("Vancouver", "Canada", Some("YVR"), 49.28, -123.12, "America/Vancouver"),
+    city("Calgary", "Canada", Some("YYC"), 51.05, -114.07, "America/Edmonton"),
+    city("Edmonton", "Canada", Some("YEG"), 53.55, -113.49, "America/Edmonton"),
+    city("Winnipeg", "Canada", Some("YWG"), 49.90, -97.14, "America/Winnipeg"),
+    city("Halifax", "Canada", Some("YHZ"), 44.65, -63.58, "America/Halifax"),
+    city("St. John's", "Canada", Some("YYT"), 47.56, -52.71, "America/St_Johns"),
+    city("Mexico City", "Mexico", Some("MEX"), 19.43, -99.13, "America/Mexico_City"),
+    city("Cancun", "Mexico", Some("CUN"), 21.16, -86.85, "America/Cancun"),
+    city("Tijuana", "Mexico", Some("TIJ"), 32.51, -117.04, "America/Tijuana"),
+    city("Guatemala City", "Guatemala", Some("GUA"), 14.63, -90.51, "America/Guatemala"),
+    city("San Jose", "Costa Rica", Some("SJO"), 9.93, -84.08, "America/Costa_Rica"),
+    city("Panama City", "Panama", Some("PTY"), 8.98, -79.52, "America/Panama"),
+    city("Havana", "Cuba", Some("HAV"), 23.11, -82.37, "America/Havana"),
+    city("Kingston", "Jamaica", Some("KIN"), 18.02, -76.80, "America/Jamaica"),
+    city("San Juan", "Puerto Rico", Some("SJU"), 18.47, -66.11, "America/Puerto_Rico"),
+    city("Bogota", "Colombia", Some("BOG"), 4.71, -74.07, "America/Bogota"),
+    city("Caracas", "Venezuela", Some("CCS"), 10.48, -66.90, "America/Caracas"),
+    city("Quito", "Ecuador", Some("UIO"), -0.18, -78.47, "America/Guayaquil"),
+    city("Lima", "Peru", Some("LIM"), -12.05, -77.04, "America/Lima"),
+    city("La Paz", "Bolivia", Some("LPB"), -16.50, -68.15, "America/La_Paz"),
+    city("Sao Paulo", "Brazil", Some("GRU"), -23.55, -46.63, "America/Sao_Paulo"),
+    city("Rio de Janeiro", "Brazil", Some("GIG"), -22.91, -43.17, "America/Sao_Paulo"),
+    city("Brasilia", "Brazil", Some("BSB"), -15.79, -47.88, "America/Sao_Paulo"),
+    city("Manaus", "Brazil", Some("MAO"), -3.12, -60.02, "America/Manaus"),
+    city("Asuncion", "Paraguay", Some("ASU"), -25.26, -57.58, "America/Asuncion"),
+    city("Montevideo", "Uruguay", Some("MVD"), -34.90, -56.16, "America/Montevideo"),
+    city("Buenos Aires", "Argentina", Some("EZE"), -34.60, -58.38, "America/Argentina/Buenos_Aires"),
+    city("Santiago", "Chile", Some("SCL"), -33.45, -70.67, "America/Santiago"),
+];
@@ -0,0 +1,80 @@
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::{LocalComponents, ZoneError, parse_zone};
+
+/// Formats accepted for wall-clock times without an offset.
+const NAIVE_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// An instant as Unix milliseconds, or an ISO 8601 string. Strings with an
+/// offset name an instant; strings without one are read in the source zone.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TimeInput {
+    EpochMillis(i64),
+    Iso(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneConversion {
+    epoch_millis: i64,
+    from: LocalComponents,
+    to: LocalComponents,
+    /// The input wall-clock time occurred twice in the source zone because
+    /// clocks went back; the earlier instant was used.
+    ambiguous: bool,
+}
+
+/// Converts `time` from `from_zone` to `to_zone` using the rules in force at
+/// that instant, so historical dates and DST boundaries come out right.
+#[tauri::command]
+pub fn convert_time(
+    time: TimeInput,
+    from_zone: String,
+    to_zone: String,
+) -> Result<ZoneConversion, ZoneError> {
+    let from_zone = parse_zone(&from_zone)?;
+    let to_zone = parse_zone(&to_zone)?;
+    let (epoch_millis, ambiguous) = match time {
+        TimeInput::EpochMillis(millis) => (millis, false),
+        TimeInput::Iso(text) => resolve(text.trim(), from_zone)?,
+    };
+
+    let local_in = |zone: Tz| {
+        LocalComponents::at(epoch_millis, zone)
+            .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {epoch_millis}")))
+    };
+    Ok(ZoneConversion {
+        epoch_millis,
+        from: local_in(from_zone)?,
+        to: local_in(to_zone)?,
+        ambiguous,
+    })
+}
+
+/// Unix milliseconds of `text` and whether its wall-clock reading was
+/// ambiguous in `zone`.
+pub(super) fn resolve(text: &str, zone: Tz) -> Result<(i64, bool), ZoneError> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(text) {
+        return Ok((instant.timestamp_millis(), false));
+    }
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .ok_or_else(|| ZoneError::InvalidTime(format!("unrecognized time: {text}")))?;
+
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(local) => Ok((local.timestamp_millis(), false)),
+        LocalResult::Ambiguous(earlier, _) => Ok((earlier.timestamp_millis(), true)),
+        LocalResult::None => Err(ZoneError::InvalidTime(format!(
+            "{text} does not exist in {} because clocks went forward",
+            zone.name()
+        ))),
+    }
+}
@@ -0,0 +1,257 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    Offset, TimeZone, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::sync::state::TimeState;
+
+use super::{LocalComponents, ZoneError, convert, default_zone_name, info, parse_zone};
+
+const MILLIS_PER_SECOND: u64 = 1000;
+const MILLIS_PER_MINUTE: u64 = 60 * MILLIS_PER_SECOND;
+const MILLIS_PER_HOUR: u64 = 60 * MILLIS_PER_MINUTE;
+const MILLIS_PER_DAY: u64 = 24 * MILLIS_PER_HOUR;
+/// Longest run of consecutive holidays `add_business_days` will skip before
+/// giving up on a malformed calendar.
+const MAX_SKIPPED_DAYS: u32 = 366;
+
+/// Calendar parts move the local date and keep the wall-clock time, so one
+/// day after 07:00 is 07:00 even across a DST change. Clock parts add exact
+/// elapsed time. Negative values go backwards.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DurationParts {
+    pub years: i32,
+    pub months: i32,
+    pub weeks: i64,
+    pub days: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64,
+    pub milliseconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZonedInstant {
+    epoch_millis: i64,
+    local: LocalComponents,
+}
+
+/// Adds `duration` to `epoch_millis` (the displayed time by default) as
+/// observed in `time_zone`.
+#[tauri::command]
+pub fn add_duration(
+    state: State<'_, TimeState>,
+    duration: DurationParts,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+) -> Result<ZonedInstant, ZoneError> {
+    let (start, zone) = start(&state, epoch_millis, time_zone)?;
+    let months = i64::from(duration.years) * 12 + i64::from(duration.months);
+    let days = duration
+        .weeks
+        .checked_mul(7)
+        .and_then(|days| days.checked_add(duration.days));
+    let date = shift_months(start.date_naive(), months)
+        .zip(days)
+        .and_then(|(date, days)| shift_days(date, days))
+        .ok_or_else(|| out_of_range(&duration))?;
+    let moved =
+        resolve(zone, date.and_time(start.time())).ok_or_else(|| out_of_range(&duration))?;
+
+    let elapsed = [
+        Duration::try_hours(duration.hours),
+        Duration::try_minutes(duration.minutes),
+        Duration::try_seconds(duration.seconds),
+        Some(Duration::milliseconds(duration.milliseconds)),
+    ]
+    .into_iter()
+    .try_fold(Duration::zero(), |sum, part| sum.checked_add(&part?))
+    .ok_or_else(|| out_of_range(&duration))?;
+    let end = moved
+        .checked_add_signed(elapsed)
+        .ok_or_else(|| out_of_range(&duration))?;
+    Ok(instant(&end))
+}
+
+/// Moves `days` working days from `epoch_millis`, skipping weekends and the
+/// `holidays` (`YYYY-MM-DD`, local dates in `time_zone`). The wall-clock
+/// time is kept.
+#[tauri::command]
+pub fn add_business_days(
+    state: State<'_, TimeState>,
+    days: i64,
+    holidays: Option<Vec<String>>,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+) -> Result<ZonedInstant, ZoneError> {
+    let (start, zone) = start(&state, epoch_millis, time_zone)?;
+    let holidays = holidays
+        .unwrap_or_default()
+        .iter()
+        .map(|text| {
+            NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                .map_err(|_| ZoneError::InvalidTime(format!("invalid holiday date: {text}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let is_business_day = |date: NaiveDate| {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !holidays.contains(&date)
+    };
+
+    let step = days.signum();
+    let mut date = start.date_naive();
+    for _ in 0..days.unsigned_abs() {
+        let mut skipped = 0;
+        loop {
+            date = shift_days(date, step)
+                .ok_or_else(|| ZoneError::InvalidTime("date out of range".to_string()))?;
+            if is_business_day(date) {
+                break;
+            }
+            skipped += 1;
+            if skipped > MAX_SKIPPED_DAYS {
+                return Err(ZoneError::InvalidTime(
+                    "no business day within a year".to_string(),
+                ));
+            }
+        }
+    }
+    let end = resolve(zone, date.and_time(start.time()))
+        .ok_or_else(|| ZoneError::InvalidTime("date out of range".to_string()))?;
+    Ok(instant(&end))
+}
+
+/// The next `weekday` (e.g. "friday" or "fri") at `time` (`HH:MM`, midnight
+/// by default) in `time_zone`, strictly after `epoch_millis`.
+#[tauri::command]
+pub fn next_occurrence_of_weekday(
+    state: State<'_, TimeState>,
+    weekday: String,
+    time: Option<String>,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+) -> Result<ZonedInstant, ZoneError> {
+    let (start, zone) = start(&state, epoch_millis, time_zone)?;
+    let weekday: Weekday = weekday
+        .trim()
+        .parse()
+        .map_err(|_| ZoneError::InvalidTime(format!("unknown weekday: {weekday}")))?;
+    let time = match time {
+        Some(text) => NaiveTime::parse_from_str(text.trim(), "%H:%M")
+            .map_err(|_| ZoneError::InvalidTime(format!("invalid time of day: {text}")))?,
+        None => NaiveTime::MIN,
+    };
+
+    let today = start.date_naive();
+    let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    // Today only counts if the time has not passed yet; otherwise a week on.
+    [ahead, ahead + 7]
+        .into_iter()
+        .filter_map(|days| resolve(zone, shift_days(today, i64::from(days))?.and_time(time)))
+        .find(|candidate| *candidate > start)
+        .map(|next| instant(&next))
+        .ok_or_else(|| ZoneError::InvalidTime("date out of range".to_string()))
+}
+
+/// Time left until a target, split into whole units.
+#[derive(Debug, Clone, Serialize)]
+pub struct Countdown {
+    target_epoch_millis: i64,
+    /// Negative once the target has passed; the parts below are then the
+    /// time since it.
+    total_millis: i64,
+    passed: bool,
+    days: i64,
+    hours: i64,
+    minutes: i64,
+    seconds: i64,
+    milliseconds: i64,
+}
+
+/// Counts down from the displayed time to `target`, an ISO 8601 time read
+/// in `time_zone` unless it carries an offset. Uses elapsed time, so a
+/// countdown across a DST change is an hour shorter or longer than the
+/// wall clocks suggest.
+#[tauri::command]
+pub fn time_until(
+    state: State<'_, TimeState>,
+    target: String,
+    time_zone: Option<String>,
+) -> Result<Countdown, ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let (target_epoch_millis, _) = convert::resolve(target.trim(), zone)?;
+    let total_millis = target_epoch_millis - state.display_now_millis();
+
+    let remaining = total_millis.unsigned_abs();
+    let part = |unit: u64, modulus: u64| ((remaining / unit) % modulus) as i64;
+    Ok(Countdown {
+        target_epoch_millis,
+        total_millis,
+        passed: total_millis < 0,
+        days: (remaining / MILLIS_PER_DAY) as i64,
+        hours: part(MILLIS_PER_HOUR, 24),
+        minutes: part(MILLIS_PER_MINUTE, 60),
+        seconds: part(MILLIS_PER_SECOND, 60),
+        milliseconds: part(1, MILLIS_PER_SECOND),
+    })
+}
+
+fn start(
+    state: &TimeState,
+    epoch_millis: Option<i64>,
+    time_zone: Option<String>,
+) -> Result<(DateTime<Tz>, Tz), ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let epoch_millis = epoch_millis.unwrap_or_else(|| state.display_now_millis());
+    let start = info::at(zone, epoch_millis)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {epoch_millis}")))?;
+    Ok((start, zone))
+}
+
+/// The instant `local` names in `zone`. Ambiguous times take the earlier
+/// instant; times skipped by a DST gap move forward by the gap's length, so
+/// 02:30 on a spring-forward night becomes 03:30.
+pub fn resolve(zone: Tz, local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(resolved) | LocalResult::Ambiguous(resolved, _) => Some(resolved),
+        LocalResult::None => {
+            let day_before = local.checked_sub_signed(Duration::days(1))?;
+            let before = zone.offset_from_utc_datetime(&day_before).fix();
+            let utc =
+                local.checked_sub_signed(Duration::seconds(i64::from(before.local_minus_utc())))?;
+            Some(zone.from_utc_datetime(&utc))
+        }
+    }
+}
+
+fn shift_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let magnitude = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months >= 0 {
+        date.checked_add_months(magnitude)
+    } else {
+        date.checked_sub_months(magnitude)
+    }
+}
+
+pub(super) fn shift_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    let magnitude = Days::new(days.unsigned_abs());
+    if days >= 0 {
+        date.checked_add_days(magnitude)
+    } else {
+        date.checked_sub_days(magnitude)
+    }
+}
+
+fn instant(local: &DateTime<Tz>) -> ZonedInstant {
+    ZonedInstant {
+        epoch_millis: local.timestamp_millis(),
+        local: LocalComponents::from_datetime(local),
+    }
+}
+
+fn out_of_range(duration: &DurationParts) -> ZoneError {
+    ZoneError::InvalidTime(format!("result out of range adding {duration:?}"))
+}
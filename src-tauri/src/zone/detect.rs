@@ -0,0 +1,162 @@
+use std::sync::Mutex;
+
+use chrono::{Offset, Utc};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+use thiserror::Error;
+
+use crate::{
+    net::SharedClient,
+    settings::SettingsStore,
+    sync::{TimeSyncError, clock},
+};
+
+use super::{parse_zone, system_zone_name};
+
+/// Response fields that carry the IANA zone, as used by the common services.
+const ZONE_FIELDS: [&str; 2] = ["timezone", "time_zone"];
+const CITY_FIELDS: [&str; 1] = ["city"];
+const COUNTRY_FIELDS: [&str; 3] = ["country_name", "country", "countryCode"];
+
+#[derive(Debug, Error)]
+pub enum DetectError {
+    #[error("IP geolocation is disabled in settings")]
+    Disabled,
+    #[error(transparent)]
+    Lookup(#[from] TimeSyncError),
+}
+
+impl DetectError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Disabled => "geolocation_disabled",
+            Self::Lookup(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for DetectError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Self::Lookup(err) = self {
+            return err.serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("DetectError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// A zone suggested from the public IP address.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeZoneDetection {
+    time_zone: String,
+    city: Option<String>,
+    country: Option<String>,
+    /// From 0 to 1. IP geolocation is wrong behind VPNs and on some mobile
+    /// networks, so agreement with the OS zone counts for a lot.
+    confidence: f64,
+    /// Whether this came from the cache rather than a fresh lookup.
+    cached: bool,
+}
+
+/// Last successful lookup, reused for `geolocation.cache_secs`.
+#[derive(Default)]
+pub struct DetectionCache {
+    entry: Mutex<Option<(i64, TimeZoneDetection)>>,
+}
+
+impl DetectionCache {
+    fn get(&self, max_age_millis: i64) -> Option<TimeZoneDetection> {
+        let entry = self.entry.lock().ok()?;
+        let (fetched_at, detection) = entry.as_ref()?;
+        (clock::monotonic_millis() - fetched_at <= max_age_millis).then(|| detection.clone())
+    }
+
+    fn set(&self, detection: TimeZoneDetection) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = Some((clock::monotonic_millis(), detection));
+        }
+    }
+}
+
+/// Suggests the local zone from an IP geolocation lookup. Sends the public
+/// address to a third party, so it fails unless `geolocation.enabled` is set.
+#[tauri::command]
+pub async fn detect_timezone(
+    app: AppHandle,
+    cache: State<'_, DetectionCache>,
+    refresh: Option<bool>,
+) -> Result<TimeZoneDetection, DetectError> {
+    let settings = app.state::<SettingsStore>().get().geolocation;
+    if !settings.enabled {
+        return Err(DetectError::Disabled);
+    }
+    let max_age_millis =
+        i64::try_from(settings.cache_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+    let cached = if refresh.unwrap_or(false) {
+        None
+    } else {
+        cache.get(max_age_millis)
+    };
+    if let Some(detection) = cached {
+        return Ok(TimeZoneDetection {
+            cached: true,
+            ..detection
+        });
+    }
+
+    let body = fetch(&app, &settings.url).await?;
+    let name = first_string(&body, &ZONE_FIELDS).ok_or(TimeSyncError::Parse)?;
+    let zone = parse_zone(&name)?;
+
+    let detection = TimeZoneDetection {
+        time_zone: zone.name().to_string(),
+        city: first_string(&body, &CITY_FIELDS),
+        country: first_string(&body, &COUNTRY_FIELDS),
+        confidence: confidence(zone),
+        cached: false,
+    };
+    cache.set(detection.clone());
+    Ok(detection)
+}
+
+async fn fetch(app: &AppHandle, url: &str) -> Result<Value, TimeSyncError> {
+    let client = app.state::<SharedClient>().get();
+    Ok(client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+fn first_string(body: &Value, fields: &[&str]) -> Option<String> {
+    fields.iter().find_map(|field| match body.get(field)? {
+        Value::String(text) if !text.is_empty() => Some(text.clone()),
+        // ipwho.is nests the zone as `{"timezone": {"id": ...}}`.
+        Value::Object(object) => object.get("id")?.as_str().map(str::to_string),
+        _ => None,
+    })
+}
+
+/// High when the OS already uses the detected zone, middling when it merely
+/// shares the current offset, low when the two disagree outright.
+fn confidence(detected: Tz) -> f64 {
+    let Some(system) = system_zone_name().and_then(|name| name.parse::<Tz>().ok()) else {
+        return 0.6;
+    };
+    if system == detected {
+        return 0.95;
+    }
+    let now = Utc::now();
+    let offset = |zone: Tz| now.with_timezone(&zone).offset().fix();
+    if offset(system) == offset(detected) {
+        0.75
+    } else {
+        0.4
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashSet, time::Duration};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    dnd,
+    settings::{DstNoticeSettings, SettingsStore},
+    sync::state::TimeState,
+};
+
+use super::{info, parse_zone, system_zone_name};
+
+pub const EVENT_DST_TRANSITION_UPCOMING: &str = "dst-transition-upcoming";
+
+/// How often watched zones are checked. Short enough that a simulated clock
+/// running many times faster still triggers close to the lead time.
+const CHECK_PERIOD: Duration = Duration::from_secs(60);
+const SECONDS_PER_HOUR: i32 = 3600;
+const SECONDS_PER_MINUTE: i32 = 60;
+
+/// Payload of the `dst-transition-upcoming` event.
+#[derive(Clone, Serialize)]
+struct DstTransitionUpcoming {
+    time_zone: String,
+    transition_epoch_millis: i64,
+    millis_until: i64,
+    offset_before_seconds: i32,
+    offset_after_seconds: i32,
+    /// Positive when clocks go forward.
+    shift_seconds: i32,
+    /// Whether DST is in effect after the transition.
+    dst_active: bool,
+}
+
+/// Warns `dst_notice.lead_secs` ahead of every offset change in the watched
+/// zones, once per zone and transition.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut announced = HashSet::new();
+        loop {
+            let settings = app.state::<SettingsStore>().get().dst_notice;
+            if settings.enabled {
+                check(&app, &settings, &mut announced);
+            }
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    });
+}
+
+fn check(app: &AppHandle, settings: &DstNoticeSettings, announced: &mut HashSet<(String, i64)>) {
+    let zones = if settings.time_zones.is_empty() {
+        system_zone_name().into_iter().collect()
+    } else {
+        settings.time_zones.clone()
+    };
+    let now_millis = app.state::<TimeState>().display_now_millis();
+    let lead_millis = i64::try_from(settings.lead_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+
+    for name in zones {
+        let Ok(zone) = parse_zone(&name) else {
+            eprintln!("ignoring unknown zone {name} in dst_notice.time_zones");
+            continue;
+        };
+        let Some(transition) = info::next_transition(zone, now_millis) else {
+            continue;
+        };
+        let millis_until = transition - now_millis;
+        if millis_until > lead_millis || !announced.insert((name.clone(), transition)) {
+            continue;
+        }
+        let (Some((before, _)), Some((after, dst_active))) = (
+            info::state_at(zone, transition - 1),
+            info::state_at(zone, transition),
+        ) else {
+            continue;
+        };
+
+        let payload = DstTransitionUpcoming {
+            time_zone: name,
+            transition_epoch_millis: transition,
+            millis_until,
+            offset_before_seconds: before,
+            offset_after_seconds: after,
+            shift_seconds: after - before,
+            dst_active,
+        };
+        if settings.notify && !dnd::suppresses(app) {
+            show_notification(app, &payload);
+        }
+        if let Err(err) = app.emit(EVENT_DST_TRANSITION_UPCOMING, payload) {
+            eprintln!("failed to emit {EVENT_DST_TRANSITION_UPCOMING}: {err}");
+        }
+    }
+}
+
+fn show_notification(app: &AppHandle, transition: &DstTransitionUpcoming) {
+    let direction = if transition.shift_seconds > 0 {
+        "forward"
+    } else {
+        "back"
+    };
+    let hours = transition.millis_until as f64 / (f64::from(SECONDS_PER_HOUR) * 1000.0);
+    let body = format!(
+        "Clocks in {} go {direction} {} in {hours:.0} h.",
+        transition.time_zone,
+        describe_shift(transition.shift_seconds.abs()),
+    );
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title("Daylight saving time change")
+        .body(body)
+        .show()
+    {
+        eprintln!("failed to show DST notification: {err}");
+    }
+}
+
+/// "1 h", "30 min" or "1 h 30 min".
+fn describe_shift(seconds: i32) -> String {
+    let hours = seconds / SECONDS_PER_HOUR;
+    let minutes = seconds % SECONDS_PER_HOUR / SECONDS_PER_MINUTE;
+    match (hours, minutes) {
+        (0, minutes) => format!("{minutes} min"),
+        (hours, 0) => format!("{hours} h"),
+        (hours, minutes) => format!("{hours} h {minutes} min"),
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+
+use super::{LocalComponents, ZoneError, convert, default_zone_name, parse_zone};
+
+/// Magnitudes above which a timestamp is assumed to be in the next smaller
+/// unit. Seconds stay below 10^11 until the year 5138, and milliseconds
+/// and microseconds above their thresholds only after 1973.
+const MILLIS_THRESHOLD: u64 = 100_000_000_000;
+const MICROS_THRESHOLD: u64 = 100_000_000_000_000;
+const NANOS_THRESHOLD: u64 = 100_000_000_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl EpochUnit {
+    /// Guesses the unit from the number of digits.
+    fn detect(value: i64) -> Self {
+        match value.unsigned_abs() {
+            magnitude if magnitude >= NANOS_THRESHOLD => Self::Nanoseconds,
+            magnitude if magnitude >= MICROS_THRESHOLD => Self::Microseconds,
+            magnitude if magnitude >= MILLIS_THRESHOLD => Self::Milliseconds,
+            _ => Self::Seconds,
+        }
+    }
+
+    fn to_millis(self, value: i64) -> Option<i64> {
+        match self {
+            Self::Seconds => value.checked_mul(1000),
+            Self::Milliseconds => Some(value),
+            Self::Microseconds => Some(value.div_euclid(1000)),
+            Self::Nanoseconds => Some(value.div_euclid(1_000_000)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochDateTime {
+    /// The unit `value` was read in, detected unless one was given.
+    unit: EpochUnit,
+    epoch_millis: i64,
+    /// RFC 3339 with the zone's offset.
+    iso: String,
+    local: LocalComponents,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochValues {
+    seconds: i64,
+    milliseconds: i64,
+    microseconds: i64,
+    /// The input wall-clock time occurred twice in the zone because clocks
+    /// went back; the earlier instant was used.
+    ambiguous: bool,
+    local: LocalComponents,
+}
+
+/// Reads a Unix timestamp in `unit` (detected from its size by default) and
+/// shows it in `time_zone`.
+#[tauri::command]
+pub fn epoch_to_datetime(
+    value: i64,
+    unit: Option<EpochUnit>,
+    time_zone: Option<String>,
+) -> Result<EpochDateTime, ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let unit = unit.unwrap_or_else(|| EpochUnit::detect(value));
+    let out_of_range = || ZoneError::InvalidTime(format!("out of range: {value}"));
+    let epoch_millis = unit.to_millis(value).ok_or_else(out_of_range)?;
+    let local = DateTime::from_timestamp_millis(epoch_millis)
+        .ok_or_else(out_of_range)?
+        .with_timezone(&zone);
+
+    Ok(EpochDateTime {
+        unit,
+        epoch_millis,
+        iso: local.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+        local: LocalComponents::from_datetime(&local),
+    })
+}
+
+/// Unix timestamps of `iso`, read in `time_zone` unless it carries an
+/// offset.
+#[tauri::command]
+pub fn datetime_to_epoch(iso: String, time_zone: Option<String>) -> Result<EpochValues, ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let (epoch_millis, ambiguous) = convert::resolve(iso.trim(), zone)?;
+    let local = LocalComponents::at(epoch_millis, zone)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {iso}")))?;
+
+    Ok(EpochValues {
+        seconds: epoch_millis.div_euclid(1000),
+        milliseconds: epoch_millis,
+        microseconds: epoch_millis.saturating_mul(1000),
+        ambiguous,
+        local,
+    })
+}
@@ -0,0 +1,74 @@
+use serde::{Serialize, Serializer};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use crate::{settings::SettingsStore, store::StoreError, sync::TimeSyncError};
+
+use super::parse_zone;
+
+pub const EVENT_FAVORITE_ZONES_CHANGED: &str = "favorite-zones-changed";
+
+#[derive(Debug, Error)]
+pub enum FavoritesError {
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl Serialize for FavoritesError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Zone(err) => err.serialize(serializer),
+            Self::Store(err) => err.serialize(serializer),
+        }
+    }
+}
+
+/// The user's favorite zones, in display order.
+#[tauri::command]
+pub fn list_favorite_zones(app: AppHandle) -> Vec<String> {
+    app.state::<SettingsStore>().get().favorite_zones
+}
+
+/// Adds `time_zone` at `position` (the end by default). A zone that is
+/// already a favorite moves to `position` instead, so this also reorders.
+#[tauri::command]
+pub fn add_favorite_zone(
+    app: AppHandle,
+    time_zone: String,
+    position: Option<usize>,
+) -> Result<Vec<String>, FavoritesError> {
+    let name = parse_zone(&time_zone)?.name().to_string();
+    let settings = app.state::<SettingsStore>().update(|settings| {
+        let favorites = &mut settings.favorite_zones;
+        let existing = favorites.iter().position(|zone| *zone == name);
+        if existing.is_some() && position.is_none() {
+            return;
+        }
+        if let Some(index) = existing {
+            favorites.remove(index);
+        }
+        let index = position.unwrap_or(favorites.len()).min(favorites.len());
+        favorites.insert(index, name);
+    })?;
+    Ok(publish(&app, settings.favorite_zones))
+}
+
+#[tauri::command]
+pub fn remove_favorite_zone(app: AppHandle, time_zone: String) -> Result<Vec<String>, StoreError> {
+    let settings = app.state::<SettingsStore>().update(|settings| {
+        settings
+            .favorite_zones
+            .retain(|zone| !zone.eq_ignore_ascii_case(time_zone.trim()));
+    })?;
+    Ok(publish(&app, settings.favorite_zones))
+}
+
+fn publish(app: &AppHandle, favorites: Vec<String>) -> Vec<String> {
+    if let Err(err) = app.emit(EVENT_FAVORITE_ZONES_CHANGED, &favorites) {
+        eprintln!("failed to emit {EVENT_FAVORITE_ZONES_CHANGED}: {err}");
+    }
+    crate::refresh_tray_menu(app);
+    favorites
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+
+use chrono::{
+    DateTime,
+    format::{Item, StrftimeItems},
+};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::State;
+use thiserror::Error;
+
+use crate::sync::{TimeSyncError, state::TimeState};
+
+use super::{default_zone_name, info, parse_zone};
+
+/// Longest specifier chrono accepts, such as `%::z` or `%-3f`.
+const MAX_SPECIFIER_LEN: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("invalid format: {0}")]
+    Invalid(String),
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+}
+
+impl FormatError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid_format",
+            Self::InvalidTime(_) => "invalid_time",
+            Self::Zone(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for FormatError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Self::Zone(err) = self {
+            return err.serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("FormatError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Outcome of checking a user-entered pattern, for inline feedback in the
+/// settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatValidation {
+    valid: bool,
+    /// Byte offset of the first invalid specifier.
+    position: Option<usize>,
+    specifier: Option<String>,
+    message: Option<String>,
+}
+
+/// An invalid specifier and where it starts.
+struct InvalidSpecifier {
+    position: usize,
+    specifier: String,
+}
+
+impl InvalidSpecifier {
+    fn message(&self) -> String {
+        format!(
+            "invalid specifier {} at position {}",
+            self.specifier, self.position
+        )
+    }
+}
+
+/// Checks a strftime-style pattern without rendering it.
+#[tauri::command]
+pub fn validate_format(format: String) -> FormatValidation {
+    match find_invalid(&format) {
+        None => FormatValidation {
+            valid: true,
+            position: None,
+            specifier: None,
+            message: None,
+        },
+        Some(invalid) => FormatValidation {
+            valid: false,
+            position: Some(invalid.position),
+            message: Some(invalid.message()),
+            specifier: Some(invalid.specifier),
+        },
+    }
+}
+
+/// Renders the displayed time in `time_zone` with a strftime-style pattern,
+/// e.g. `%H:%M %Z`.
+#[tauri::command]
+pub fn render_format(
+    state: State<'_, TimeState>,
+    format: String,
+    time_zone: Option<String>,
+) -> Result<String, FormatError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let now_millis = state.display_now_millis();
+    let local = info::at(zone, now_millis)
+        .ok_or_else(|| FormatError::InvalidTime(format!("out of range: {now_millis}")))?;
+    render(&format, &local)
+}
+
+/// Formats `local` with `format`, rejecting patterns chrono cannot render
+/// instead of panicking in `Display`.
+pub fn render(format: &str, local: &DateTime<Tz>) -> Result<String, FormatError> {
+    if let Some(invalid) = find_invalid(format) {
+        return Err(FormatError::Invalid(invalid.message()));
+    }
+    let mut rendered = String::new();
+    write!(
+        rendered,
+        "{}",
+        local.format_with_items(StrftimeItems::new(format))
+    )
+    .map_err(|_| FormatError::Invalid(format!("cannot render {format}")))?;
+    Ok(rendered)
+}
+
+fn find_invalid(format: &str) -> Option<InvalidSpecifier> {
+    if !has_error(format) {
+        return None;
+    }
+    // Find the first `%` that no valid specifier can start at.
+    let position = format
+        .char_indices()
+        .filter(|(_, c)| *c == '%')
+        .map(|(position, _)| position)
+        .find(|&position| {
+            let rest = &format[position..];
+            !(2..=MAX_SPECIFIER_LEN.min(rest.len()))
+                .filter(|len| rest.is_char_boundary(*len))
+                .any(|len| !has_error(&rest[..len]))
+        });
+    Some(match position {
+        Some(position) => InvalidSpecifier {
+            position,
+            specifier: format[position..].chars().take(2).collect(),
+        },
+        None => InvalidSpecifier {
+            position: 0,
+            specifier: format.to_string(),
+        },
+    })
+}
+
+fn has_error(format: &str) -> bool {
+    StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Offset};
+use chrono_tz::{OffsetComponents, Tz};
+use serde::Serialize;
+use tauri::State;
+
+use crate::sync::{TimeSyncError, state::TimeState};
+
+use super::{LocalComponents, ZoneError, parse_zone};
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+/// How far ahead to look for a transition. Zones with DST change at least
+/// once a year, so anything beyond this has no scheduled transition.
+const TRANSITION_HORIZON_DAYS: i64 = 400;
+
+/// Current offset and DST state of a zone, and when they next change.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeZoneInfo {
+    time_zone: String,
+    utc_offset_seconds: i32,
+    dst_active: bool,
+    abbreviation: String,
+    next_transition: Option<ZoneTransition>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneTransition {
+    epoch_millis: i64,
+    millis_until: i64,
+    /// Whether DST is in effect after the transition.
+    dst_active: bool,
+    /// Wall-clock fields right after the transition, including its new
+    /// offset and abbreviation.
+    local: LocalComponents,
+}
+
+/// Describes `time_zone` at the displayed time and finds its next offset or
+/// DST change, for labels such as "DST ends in 12 days".
+#[tauri::command]
+pub fn get_timezone_info(
+    state: State<'_, TimeState>,
+    time_zone: String,
+) -> Result<TimeZoneInfo, TimeSyncError> {
+    let zone = parse_zone(&time_zone)?;
+    let now_millis = state.display_now_millis();
+    let now = at(zone, now_millis).ok_or(TimeSyncError::Parse)?;
+    let offset = now.offset();
+
+    let next_transition = next_transition(zone, now_millis).and_then(|epoch_millis| {
+        let after = at(zone, epoch_millis)?;
+        Some(ZoneTransition {
+            epoch_millis,
+            millis_until: epoch_millis - now_millis,
+            dst_active: is_dst(&after),
+            local: LocalComponents::from_datetime(&after),
+        })
+    });
+
+    Ok(TimeZoneInfo {
+        time_zone: zone.name().to_string(),
+        utc_offset_seconds: offset.fix().local_minus_utc(),
+        dst_active: is_dst(&now),
+        abbreviation: offset.to_string(),
+        next_transition,
+    })
+}
+
+/// A zone's offset at some instant, past or future.
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneOffset {
+    time_zone: String,
+    epoch_millis: i64,
+    utc_offset_seconds: i32,
+    /// Part of `utc_offset_seconds` due to DST.
+    dst_offset_seconds: i32,
+    dst_active: bool,
+    abbreviation: String,
+}
+
+/// Offset of `time_zone` at `epoch_millis` under the rules in force then,
+/// e.g. +09:18:59 LMT for Tokyo before 1888.
+#[tauri::command]
+pub fn zone_offset_at(time_zone: String, epoch_millis: i64) -> Result<ZoneOffset, ZoneError> {
+    let zone = parse_zone(&time_zone)?;
+    let local = at(zone, epoch_millis)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {epoch_millis}")))?;
+    let offset = local.offset();
+    Ok(ZoneOffset {
+        time_zone: zone.name().to_string(),
+        epoch_millis,
+        utc_offset_seconds: offset.fix().local_minus_utc(),
+        dst_offset_seconds: i32::try_from(offset.dst_offset().num_seconds()).unwrap_or_default(),
+        dst_active: is_dst(&local),
+        abbreviation: offset.to_string(),
+    })
+}
+
+/// First instant after `after_millis`, to the millisecond, at which the
+/// zone's offset or DST state changes.
+pub fn next_transition(zone: Tz, after_millis: i64) -> Option<i64> {
+    let start = state_at(zone, after_millis)?;
+    let mut high = (1..=TRANSITION_HORIZON_DAYS)
+        .map(|day| after_millis + day * MILLIS_PER_DAY)
+        .find(|probe| state_at(zone, *probe) != Some(start))?;
+    // Narrow to the exact instant; `low` always has the starting state.
+    let mut low = high - MILLIS_PER_DAY;
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if state_at(zone, middle) == Some(start) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(high)
+}
+
+pub(super) fn at(zone: Tz, epoch_millis: i64) -> Option<DateTime<Tz>> {
+    DateTime::from_timestamp_millis(epoch_millis).map(|utc| utc.with_timezone(&zone))
+}
+
+pub(super) fn is_dst(local: &DateTime<Tz>) -> bool {
+    !local.offset().dst_offset().is_zero()
+}
+
+/// Offset and DST flag in effect at `epoch_millis`.
+pub(super) fn state_at(zone: Tz, epoch_millis: i64) -> Option<(i32, bool)> {
+    let local = at(zone, epoch_millis)?;
+    Some((local.offset().fix().local_minus_utc(), is_dst(&local)))
+}
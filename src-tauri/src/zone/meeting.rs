@@ -0,0 +1,169 @@
+use chrono::{DateTime, Datelike, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::State;
+use thiserror::Error;
+
+use crate::sync::{TimeSyncError, state::TimeState};
+
+use super::{LocalComponents, info, parse_zone};
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+const MILLIS_PER_DAY: i64 = 24 * 60 * MILLIS_PER_MINUTE;
+/// Meetings start on quarter hours.
+const SLOT_MINUTES: i64 = 15;
+const DEFAULT_DAYS: u32 = 7;
+const MAX_DAYS: u32 = 31;
+
+#[derive(Debug, Error)]
+pub enum MeetingError {
+    #[error("invalid meeting request: {0}")]
+    InvalidRequest(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+}
+
+impl MeetingError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidRequest(_) => "invalid_meeting_request",
+            Self::Zone(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for MeetingError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Self::Zone(err) = self {
+            return err.serialize(serializer);
+        }
+        let mut state = serializer.serialize_struct("MeetingError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Local working day shared by every participant, as `HH:MM`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkingHours {
+    pub start: String,
+    pub end: String,
+    /// Count Saturdays and Sundays as working days.
+    #[serde(default)]
+    pub include_weekends: bool,
+}
+
+/// A stretch of time in which a meeting of the requested length can start
+/// at any slot and still end within everyone's working hours.
+#[derive(Debug, Clone, Serialize)]
+pub struct MeetingWindow {
+    start_epoch_millis: i64,
+    /// End of the latest meeting that fits, not its start.
+    end_epoch_millis: i64,
+    /// Each participant's wall clock at `start_epoch_millis`, in the order
+    /// the zones were given.
+    local_starts: Vec<LocalComponents>,
+}
+
+/// Finds the times over the next `days` days at which every zone in `zones`
+/// is inside `working_hours` for `duration_minutes`. Each zone is checked
+/// with the rules in force on that day, so DST changes shift the windows.
+#[tauri::command]
+pub fn find_meeting_windows(
+    state: State<'_, TimeState>,
+    zones: Vec<String>,
+    working_hours: WorkingHours,
+    duration_minutes: u32,
+    days: Option<u32>,
+) -> Result<Vec<MeetingWindow>, MeetingError> {
+    let zones = zones
+        .iter()
+        .map(|name| parse_zone(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (day_start, day_end) = parse_hours(&working_hours)?;
+    if duration_minutes == 0 {
+        return Err(MeetingError::InvalidRequest(
+            "duration must be positive".to_string(),
+        ));
+    }
+
+    let duration = i64::from(duration_minutes) * MILLIS_PER_MINUTE;
+    let slot = SLOT_MINUTES * MILLIS_PER_MINUTE;
+    let now = state.display_now_millis();
+    let first_slot = now.div_euclid(slot) * slot + slot;
+    let horizon = now + i64::from(days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS)) * MILLIS_PER_DAY;
+
+    let fits = |start: i64| {
+        zones.iter().all(|zone| {
+            let (Some(from), Some(to)) =
+                (info::at(*zone, start), info::at(*zone, start + duration))
+            else {
+                return false;
+            };
+            within(
+                &from,
+                &to,
+                day_start,
+                day_end,
+                working_hours.include_weekends,
+            )
+        })
+    };
+
+    let mut windows: Vec<MeetingWindow> = Vec::new();
+    let mut open: Option<(i64, i64)> = None;
+    for start in (first_slot..horizon).step_by(slot as usize) {
+        if fits(start) {
+            let window_start = open.map_or(start, |(window_start, _)| window_start);
+            open = Some((window_start, start + duration));
+        } else if let Some((window_start, window_end)) = open.take() {
+            windows.push(window(&zones, window_start, window_end));
+        }
+    }
+    if let Some((window_start, window_end)) = open {
+        windows.push(window(&zones, window_start, window_end));
+    }
+    Ok(windows)
+}
+
+fn parse_hours(hours: &WorkingHours) -> Result<(NaiveTime, NaiveTime), MeetingError> {
+    let parse = |text: &str| {
+        NaiveTime::parse_from_str(text.trim(), "%H:%M")
+            .map_err(|_| MeetingError::InvalidRequest(format!("invalid time of day: {text}")))
+    };
+    let (start, end) = (parse(&hours.start)?, parse(&hours.end)?);
+    if end <= start {
+        return Err(MeetingError::InvalidRequest(format!(
+            "working hours end at {} before they start at {}",
+            hours.end, hours.start
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Whether a meeting from `from` to `to` stays inside one working day.
+fn within(
+    from: &DateTime<Tz>,
+    to: &DateTime<Tz>,
+    day_start: NaiveTime,
+    day_end: NaiveTime,
+    include_weekends: bool,
+) -> bool {
+    let weekend = matches!(from.weekday(), Weekday::Sat | Weekday::Sun);
+    (include_weekends || !weekend)
+        && from.date_naive() == to.date_naive()
+        && from.time() >= day_start
+        && to.time() <= day_end
+}
+
+fn window(zones: &[Tz], start_epoch_millis: i64, end_epoch_millis: i64) -> MeetingWindow {
+    MeetingWindow {
+        start_epoch_millis,
+        end_epoch_millis,
+        local_starts: zones
+            .iter()
+            .filter_map(|zone| LocalComponents::at(start_epoch_millis, *zone))
+            .collect(),
+    }
+}
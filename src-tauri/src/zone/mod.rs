@@ -0,0 +1,194 @@
+pub mod active;
+mod aliases;
+pub mod calendar;
+mod cities;
+pub mod convert;
+pub mod datemath;
+pub mod detect;
+pub mod dst;
+pub mod epoch;
+pub mod favorites;
+pub mod format;
+pub mod info;
+pub mod meeting;
+pub mod names;
+pub mod natural;
+pub mod search;
+pub mod world;
+
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Offset, Timelike};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use crate::{
+    settings::SettingsStore,
+    store::StoreError,
+    sync::{DEFAULT_TIME_ZONE, TimeSyncError},
+};
+
+pub const EVENT_TIMEZONE_CHANGED: &str = "timezone-changed";
+
+const ZONE_POLL_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum ZoneError {
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl ZoneError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidTime(_) => "invalid_time",
+            Self::Zone(err) => err.code(),
+            Self::Store(err) => err.code(),
+        }
+    }
+}
+
+impl Serialize for ZoneError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Zone(err) => return err.serialize(serializer),
+            Self::Store(err) => return err.serialize(serializer),
+            Self::InvalidTime(_) => {}
+        }
+        let mut state = serializer.serialize_struct("ZoneError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Payload of the `timezone-changed` event.
+#[derive(Clone, Serialize)]
+struct TimeZoneChanged {
+    time_zone: String,
+}
+
+/// Polls the OS zone and emits `timezone-changed` whenever its IANA name
+/// differs from the previous observation. While the main clock follows the
+/// OS zone, the schedules that follow the main clock move with it.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Reading the zone touches the filesystem or the registry, so it
+        // stays off the async workers.
+        let mut current = tauri::async_runtime::spawn_blocking(system_zone_name)
+            .await
+            .ok()
+            .flatten();
+        loop {
+            tokio::time::sleep(ZONE_POLL_PERIOD).await;
+            let Ok(Some(observed)) = tauri::async_runtime::spawn_blocking(system_zone_name).await
+            else {
+                continue;
+            };
+            if current.as_deref() == Some(observed.as_str()) {
+                continue;
+            }
+
+            current = Some(observed.clone());
+            let payload = TimeZoneChanged {
+                time_zone: observed,
+            };
+            if let Err(err) = app.emit(EVENT_TIMEZONE_CHANGED, payload) {
+                eprintln!("failed to emit {EVENT_TIMEZONE_CHANGED}: {err}");
+            }
+            if app.state::<SettingsStore>().get().active_zone.is_none() {
+                active::reschedule(&app);
+            }
+        }
+    });
+}
+
+/// The OS zone, used wherever a command is called without one.
+#[tauri::command]
+pub fn get_system_timezone() -> String {
+    default_zone_name()
+}
+
+/// IANA name of the OS zone, or `DEFAULT_TIME_ZONE` if it cannot be read or
+/// is not a zone chrono-tz knows.
+pub fn default_zone_name() -> String {
+    system_zone_name()
+        .filter(|name| name.parse::<Tz>().is_ok())
+        .unwrap_or_else(|| DEFAULT_TIME_ZONE.to_string())
+}
+
+/// IANA name of the zone the OS is currently configured for.
+fn system_zone_name() -> Option<String> {
+    match iana_time_zone::get_timezone() {
+        Ok(name) => Some(name),
+        Err(err) => {
+            eprintln!("failed to read system time zone: {err}");
+            None
+        }
+    }
+}
+
+/// Number of alternatives offered when a zone name does not resolve.
+const ZONE_SUGGESTIONS: usize = 3;
+
+/// Resolves an IANA zone name such as `Asia/Tokyo`. Unknown names fail with
+/// the closest valid zones as suggestions.
+pub fn parse_zone(name: &str) -> Result<Tz, TimeSyncError> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| TimeSyncError::InvalidTimeZone {
+            name: name.to_string(),
+            suggestions: search::search_timezones(name.to_string(), Some(ZONE_SUGGESTIONS))
+                .into_iter()
+                .map(|found| found.time_zone)
+                .collect(),
+        })
+}
+
+/// Wall-clock fields of an instant as observed in a particular zone.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalComponents {
+    pub time_zone: String,
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+    /// ISO weekday, Monday = 1 through Sunday = 7.
+    pub weekday: u32,
+    pub utc_offset_seconds: i32,
+    pub abbreviation: String,
+}
+
+impl LocalComponents {
+    pub fn at(epoch_millis: i64, zone: Tz) -> Option<Self> {
+        let local = DateTime::from_timestamp_millis(epoch_millis)?.with_timezone(&zone);
+        Some(Self::from_datetime(&local))
+    }
+
+    pub fn from_datetime(local: &DateTime<Tz>) -> Self {
+        let offset = local.offset();
+        Self {
+            time_zone: local.timezone().name().to_string(),
+            year: local.year(),
+            month: local.month(),
+            day: local.day(),
+            hour: local.hour(),
+            minute: local.minute(),
+            second: local.second(),
+            millisecond: local.timestamp_subsec_millis().min(999),
+            weekday: local.weekday().number_from_monday(),
+            utc_offset_seconds: offset.fix().local_minus_utc(),
+            abbreviation: offset.to_string(),
+        }
+    }
+}
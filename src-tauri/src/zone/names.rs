@@ -0,0 +1,111 @@
+use chrono::{Datelike, Offset, Timelike, Utc};
+use chrono_tz::{TZ_VARIANTS, Tz};
+use icu::{
+    calendar::Date,
+    datetime::{
+        NoCalendarFormatter,
+        fieldsets::zone::{ExemplarCity, GenericLong, Location, SpecificLong},
+    },
+    locale::Locale,
+    time::{
+        DateTime, Time, TimeZoneInfo,
+        zone::{IanaParser, IanaParserBorrowed, UtcOffset, models::AtTime},
+    },
+};
+use serde::Serialize;
+
+use crate::locale::LocaleError;
+
+use super::parse_zone;
+
+/// A zone's names in one locale, e.g. for `Asia/Tokyo` in `ja`: "日本標準時",
+/// "日本時間" and "東京".
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneDisplayName {
+    time_zone: String,
+    /// Name that ignores DST, such as "Pacific Time".
+    generic: String,
+    /// Name of the variant in effect now, such as "Pacific Daylight Time".
+    specific: String,
+    /// Name after the country or city, such as "Japan Time".
+    location: String,
+    city: String,
+}
+
+struct Formatters {
+    generic: NoCalendarFormatter<GenericLong>,
+    specific: NoCalendarFormatter<SpecificLong>,
+    location: NoCalendarFormatter<Location>,
+    city: NoCalendarFormatter<ExemplarCity>,
+}
+
+/// Localized names of `zones` (every zone by default) in `locale`, a BCP 47
+/// tag such as `ja-JP`. Locales without data fall back to their parent and
+/// finally to English.
+#[tauri::command]
+pub fn get_zone_display_names(
+    locale: String,
+    zones: Option<Vec<String>>,
+) -> Result<Vec<ZoneDisplayName>, LocaleError> {
+    let locale: Locale = locale
+        .parse()
+        .map_err(|_| LocaleError::Invalid(locale.clone()))?;
+    let formatters = Formatters::new(&locale)?;
+    let zones = match zones {
+        Some(names) => names
+            .iter()
+            .map(|name| parse_zone(name))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => TZ_VARIANTS.to_vec(),
+    };
+
+    let parser = IanaParser::new();
+    Ok(zones
+        .into_iter()
+        .filter_map(|zone| {
+            let info = zone_info(&parser, zone)?;
+            Some(ZoneDisplayName {
+                time_zone: zone.name().to_string(),
+                generic: formatters.generic.format(&info).to_string(),
+                specific: formatters.specific.format(&info).to_string(),
+                location: formatters.location.format(&info).to_string(),
+                city: formatters.city.format(&info).to_string(),
+            })
+        })
+        .collect())
+}
+
+impl Formatters {
+    fn new(locale: &Locale) -> Result<Self, LocaleError> {
+        let invalid = |err: icu::datetime::DateTimeFormatterLoadError| {
+            LocaleError::Invalid(format!("{locale}: {err}"))
+        };
+        Ok(Self {
+            generic: NoCalendarFormatter::try_new(locale.into(), GenericLong).map_err(invalid)?,
+            specific: NoCalendarFormatter::try_new(locale.into(), SpecificLong).map_err(invalid)?,
+            location: NoCalendarFormatter::try_new(locale.into(), Location).map_err(invalid)?,
+            city: NoCalendarFormatter::try_new(locale.into(), ExemplarCity).map_err(invalid)?,
+        })
+    }
+}
+
+/// ICU's view of `zone` right now; the offset and local time select the
+/// metazone and DST variant the names refer to.
+fn zone_info(parser: &IanaParserBorrowed, zone: Tz) -> Option<TimeZoneInfo<AtTime>> {
+    let local = Utc::now().with_timezone(&zone);
+    let offset = UtcOffset::try_from_seconds(local.offset().fix().local_minus_utc()).ok();
+    let date = Date::try_new_iso(local.year(), local.month() as u8, local.day() as u8).ok()?;
+    let time = Time::try_new(
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second().min(59) as u8,
+        0,
+    )
+    .ok()?;
+    Some(
+        parser
+            .parse(zone.name())
+            .with_offset(offset)
+            .at_date_time_iso(DateTime { date, time }),
+    )
+}
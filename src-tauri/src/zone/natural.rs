@@ -0,0 +1,215 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use tauri::State;
+
+use crate::sync::state::TimeState;
+
+use super::{LocalComponents, ZoneError, datemath, default_zone_name, info, parse_zone};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NaturalTime {
+    epoch_millis: i64,
+    local: LocalComponents,
+}
+
+/// Which day a phrase names, before the time of day is applied.
+#[derive(Debug, Clone, Copy)]
+enum DayRef {
+    Offset(i64),
+    /// The coming `Weekday`; `strict` skips today, as in "next friday".
+    Weekday(Weekday, bool),
+    Date(NaiveDate),
+}
+
+/// Resolves phrases such as "in 45 minutes", "tomorrow 9am", "next friday
+/// 14:00" or "noon" against the displayed time in `time_zone`. A bare time
+/// that has already passed today means tomorrow; a bare day means midnight.
+#[tauri::command]
+pub fn parse_natural(
+    state: State<'_, TimeState>,
+    text: String,
+    time_zone: Option<String>,
+) -> Result<NaturalTime, ZoneError> {
+    let zone = parse_zone(&time_zone.unwrap_or_else(default_zone_name))?;
+    let now_millis = state.display_now_millis();
+    let now = info::at(zone, now_millis)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("out of range: {now_millis}")))?;
+    let resolved = resolve(&text, zone, &now)
+        .ok_or_else(|| ZoneError::InvalidTime(format!("could not understand \"{text}\"")))?;
+    Ok(NaturalTime {
+        epoch_millis: resolved.timestamp_millis(),
+        local: LocalComponents::from_datetime(&resolved),
+    })
+}
+
+fn resolve(text: &str, zone: Tz, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let normalized = text.to_lowercase().replace(',', " ");
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    match words.as_slice() {
+        [] => None,
+        ["now"] => Some(*now),
+        ["in", rest @ ..] => now.checked_add_signed(relative(rest)?),
+        [rest @ .., "from", "now"] => now.checked_add_signed(relative(rest)?),
+        [rest @ .., "ago"] => now.checked_sub_signed(relative(rest)?),
+        _ => absolute(&words, zone, now),
+    }
+}
+
+/// Words that join a duration to the rest of a quick-entry phrase, as in
+/// "tea in 3 min" or "laundry for 1h", and are left out of the label.
+const DURATION_CONNECTORS: [&str; 2] = ["in", "for"];
+
+/// Splits quick-entry text such as "pasta 11 min" or "1h 30m laundry" into
+/// the longest positive duration it contains and the remaining words, in
+/// their original case, as a label.
+pub fn split_duration(text: &str) -> Option<(Duration, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let lowered: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    let lowered: Vec<&str> = lowered.iter().map(String::as_str).collect();
+    let (start, end, duration) = (0..words.len())
+        .flat_map(|start| (start + 1..=words.len()).map(move |end| (start, end)))
+        .filter_map(|(start, end)| {
+            let duration = relative(&lowered[start..end])?;
+            (duration > Duration::zero()).then_some((start, end, duration))
+        })
+        .max_by_key(|(start, end, _)| (end - start, std::cmp::Reverse(*start)))?;
+    let before = match lowered[..start] {
+        [.., connector] if DURATION_CONNECTORS.contains(&connector) => start - 1,
+        _ => start,
+    };
+    let label = words[..before]
+        .iter()
+        .chain(&words[end..])
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((duration, label))
+}
+
+/// "45 minutes", "1 hour 30 minutes", "2h and 5m".
+fn relative(words: &[&str]) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut rest = words;
+    while !rest.is_empty() {
+        let (amount, unit, remaining) = match rest {
+            ["and", remaining @ ..] => {
+                rest = remaining;
+                continue;
+            }
+            [amount, unit, remaining @ ..] if amount.parse::<i64>().is_ok() => {
+                (amount.parse().ok()?, *unit, remaining)
+            }
+            // Compact forms such as "45m".
+            [word, remaining @ ..] => {
+                let split = word.find(|c: char| !c.is_ascii_digit())?;
+                (word[..split].parse().ok()?, &word[split..], remaining)
+            }
+            [] => unreachable!(),
+        };
+        total = total.checked_add(&unit_duration(unit, amount)?)?;
+        rest = remaining;
+    }
+    (!words.is_empty()).then_some(total)
+}
+
+fn unit_duration(unit: &str, amount: i64) -> Option<Duration> {
+    match unit.trim_end_matches('s') {
+        "" | "sec" | "second" => Duration::try_seconds(amount),
+        "m" | "min" | "minute" => Duration::try_minutes(amount),
+        "h" | "hr" | "hour" => Duration::try_hours(amount),
+        "d" | "day" => Duration::try_days(amount),
+        "w" | "week" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// A day, a time of day, or both, in either order and with an optional
+/// "at" or "on".
+fn absolute(words: &[&str], zone: Tz, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let mut day = None;
+    let mut time = None;
+    let mut index = 0;
+    while index < words.len() {
+        let word = words[index];
+        let next = words.get(index + 1).copied();
+        index += 1;
+        match word {
+            "at" | "on" => continue,
+            "today" | "tonight" => day = Some(DayRef::Offset(0)),
+            "tomorrow" => day = Some(DayRef::Offset(1)),
+            "yesterday" => day = Some(DayRef::Offset(-1)),
+            "next" | "this" => {
+                let weekday = next?.parse::<Weekday>().ok()?;
+                day = Some(DayRef::Weekday(weekday, word == "next"));
+                index += 1;
+            }
+            _ => {
+                if let Ok(weekday) = word.parse::<Weekday>() {
+                    day = Some(DayRef::Weekday(weekday, false));
+                } else if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                    day = Some(DayRef::Date(date));
+                } else if let Some(parsed) = next
+                    .filter(|suffix| matches!(*suffix, "am" | "pm"))
+                    .and_then(|suffix| time_of_day(&format!("{word}{suffix}")))
+                {
+                    time = Some(parsed);
+                    index += 1;
+                } else {
+                    time = Some(time_of_day(word)?);
+                }
+            }
+        }
+    }
+
+    let today = now.date_naive();
+    let time = time.unwrap_or(NaiveTime::MIN);
+    let at = |date: NaiveDate| datemath::resolve(zone, date.and_time(time));
+    match day {
+        Some(DayRef::Offset(days)) => at(datemath::shift_days(today, days)?),
+        Some(DayRef::Date(date)) => at(date),
+        Some(DayRef::Weekday(weekday, strict)) => {
+            let ahead =
+                (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+            let ahead = if strict && ahead == 0 { 7 } else { ahead };
+            at(datemath::shift_days(today, i64::from(ahead))?)
+        }
+        None => {
+            let candidate = at(today)?;
+            if candidate > *now {
+                Some(candidate)
+            } else {
+                at(datemath::shift_days(today, 1)?)
+            }
+        }
+    }
+}
+
+/// "14:00", "9am", "9:30pm", "noon", "midnight".
+fn time_of_day(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, pm) = if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true))
+    } else if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false))
+    } else {
+        (word, None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm; "9" alone is ambiguous.
+        None if pm.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
@@ -0,0 +1,182 @@
+use chrono_tz::TZ_VARIANTS;
+use serde::Serialize;
+
+use super::{
+    aliases::{CITY_ALIASES, COUNTRY_ZONES},
+    cities::CITIES,
+};
+
+const DEFAULT_LIMIT: usize = 20;
+/// One typo is tolerated per this many characters of the query; shorter
+/// queries must match exactly.
+const CHARS_PER_EDIT: usize = 4;
+
+/// What part of a zone's description the query matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The IANA name or its city part, e.g. `Asia/Tokyo` or `Tokyo`.
+    Zone,
+    /// A city or former name observing the zone, e.g. `Osaka`.
+    Alias,
+    Country,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneMatch {
+    pub time_zone: String,
+    /// The name the query matched, as it should be shown in the picker.
+    pub matched: String,
+    pub kind: MatchKind,
+}
+
+/// Zones whose name, city, aliases, or country resemble `query`, best match
+/// first. Small typos are forgiven, so "tokio" still finds `Asia/Tokyo`.
+#[tauri::command]
+pub fn search_timezones(query: String, limit: Option<usize>) -> Vec<ZoneMatch> {
+    let query = normalize(&query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let zones = TZ_VARIANTS.iter().flat_map(|zone| {
+        let name = zone.name();
+        let city = name.rsplit('/').next().unwrap_or(name);
+        [(name, name), (city, name)].map(|(term, zone)| (term, zone, MatchKind::Zone))
+    });
+    let aliases = CITY_ALIASES
+        .iter()
+        .map(|(term, zone)| (*term, *zone, MatchKind::Alias));
+    let countries = COUNTRY_ZONES
+        .iter()
+        .map(|(term, zone)| (*term, *zone, MatchKind::Country));
+
+    let mut best: Vec<(usize, ZoneMatch)> = Vec::new();
+    for (term, zone, kind) in zones.chain(aliases).chain(countries) {
+        let Some(score) = score(&query, &normalize(term)) else {
+            continue;
+        };
+        let candidate = ZoneMatch {
+            time_zone: zone.to_string(),
+            matched: term.replace('_', " "),
+            kind,
+        };
+        match best.iter_mut().find(|(_, found)| found.time_zone == zone) {
+            Some(existing) if (score, kind) < (existing.0, existing.1.kind) => {
+                *existing = (score, candidate);
+            }
+            Some(_) => {}
+            None => best.push((score, candidate)),
+        }
+    }
+
+    best.sort_by(|(a_score, a), (b_score, b)| {
+        (a_score, a.kind, &a.time_zone).cmp(&(b_score, b.kind, &b.time_zone))
+    });
+    best.into_iter()
+        .take(limit.unwrap_or(DEFAULT_LIMIT))
+        .map(|(_, found)| found)
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CityMatch {
+    pub name: String,
+    pub country: String,
+    pub airport: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub time_zone: String,
+}
+
+/// Cities from the bundled database whose name, country, or airport code
+/// resemble `query`, best match first. Works offline.
+#[tauri::command]
+pub fn search_cities(query: String, limit: Option<usize>) -> Vec<CityMatch> {
+    let query = normalize(&query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut found: Vec<(usize, &_)> = CITIES
+        .iter()
+        .filter_map(|city| {
+            let by_airport = city
+                .airport
+                .filter(|code| code.eq_ignore_ascii_case(&query))
+                .map(|_| 0);
+            // Country matches rank below any name match of the same quality.
+            let by_country = score(&query, &normalize(city.country)).map(|score| score + 1);
+            [score(&query, &normalize(city.name)), by_airport, by_country]
+                .into_iter()
+                .flatten()
+                .min()
+                .map(|score| (score, city))
+        })
+        .collect();
+    found.sort_by(|(a_score, a), (b_score, b)| (a_score, a.name).cmp(&(b_score, b.name)));
+    found
+        .into_iter()
+        .take(limit.unwrap_or(DEFAULT_LIMIT))
+        .map(|(_, city)| CityMatch {
+            name: city.name.to_string(),
+            country: city.country.to_string(),
+            airport: city.airport.map(str::to_string),
+            latitude: city.latitude,
+            longitude: city.longitude,
+            time_zone: city.time_zone.to_string(),
+        })
+        .collect()
+}
+
+/// Lower is better; `None` when `term` is too different to be shown.
+fn score(query: &str, term: &str) -> Option<usize> {
+    if term == query {
+        return Some(0);
+    }
+    if term.starts_with(query) {
+        return Some(1);
+    }
+    if term.split(' ').any(|word| word.starts_with(query)) {
+        return Some(2);
+    }
+    if term.contains(query) {
+        return Some(3);
+    }
+
+    let max_edits = query.chars().count() / CHARS_PER_EDIT;
+    if max_edits == 0 {
+        return None;
+    }
+    let distance = std::iter::once(term)
+        .chain(term.split(' '))
+        .map(|candidate| edit_distance(query, candidate))
+        .min()?;
+    (distance <= max_edits).then_some(4 + distance)
+}
+
+/// Lowercase with separators collapsed to single spaces, so `America/New_York`
+/// and "new york" compare equal word by word.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| c == '/' || c == '_' || c == '-' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Offset};
+use chrono_tz::Tz;
+use serde::Serialize;
+use tauri::State;
+
+use crate::sync::{TimeSyncError, state::TimeState};
+
+use super::{LocalComponents, default_zone_name, info, parse_zone};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldClock {
+    epoch_millis: i64,
+    home_zone: String,
+    clocks: Vec<ZoneClock>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneClock {
+    utc_offset_seconds: i32,
+    dst_active: bool,
+    /// Calendar days ahead of the home zone: -1 is yesterday, 1 tomorrow.
+    day_offset: i64,
+    local: LocalComponents,
+}
+
+/// Reads every zone in `zones` at the same corrected instant, in one call for
+/// multi-city views. Days are compared against `home_zone`, which defaults to
+/// the OS zone.
+#[tauri::command]
+pub fn world_clock(
+    state: State<'_, TimeState>,
+    zones: Vec<String>,
+    home_zone: Option<String>,
+) -> Result<WorldClock, TimeSyncError> {
+    let home = parse_zone(&home_zone.unwrap_or_else(default_zone_name))?;
+    let zones = zones
+        .iter()
+        .map(|name| parse_zone(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let epoch_millis = state.display_now_millis();
+    let home_now = info::at(home, epoch_millis).ok_or(TimeSyncError::Parse)?;
+    let clocks = zones
+        .into_iter()
+        .map(|zone| {
+            let now = info::at(zone, epoch_millis).ok_or(TimeSyncError::Parse)?;
+            Ok(zone_clock(&now, &home_now))
+        })
+        .collect::<Result<_, TimeSyncError>>()?;
+
+    Ok(WorldClock {
+        epoch_millis,
+        home_zone: home.name().to_string(),
+        clocks,
+    })
+}
+
+fn zone_clock(now: &DateTime<Tz>, home_now: &DateTime<Tz>) -> ZoneClock {
+    ZoneClock {
+        utc_offset_seconds: now.offset().fix().local_minus_utc(),
+        dst_active: info::is_dst(now),
+        day_offset: (now.date_naive() - home_now.date_naive()).num_days(),
+        local: LocalComponents::from_datetime(now),
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, Position, WebviewUrl, WebviewWindowBuilder, WindowEvent,
+};
+use thiserror::Error;
+
+use crate::{
+    store::{self, StoreError},
+    sync::TimeSyncError,
+    zone::parse_zone,
+};
+
+const ZONE_WINDOWS_FILE_NAME: &str = "zone-windows.json";
+/// Labels of zone windows start with this; the capability in
+/// `capabilities/main.json` grants them the main window's permissions.
+const LABEL_PREFIX: &str = "zone-";
+const WINDOW_SIZE: f64 = 240.0;
+
+#[derive(Debug, Error)]
+pub enum ZoneWindowError {
+    #[error(transparent)]
+    Zone(#[from] TimeSyncError),
+    #[error("failed to create window: {0}")]
+    Window(#[from] tauri::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+impl Serialize for ZoneWindowError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Zone(err) => err.serialize(serializer),
+            Self::Store(err) => err.serialize(serializer),
+            Self::Window(_) => {
+                let mut state = serializer.serialize_struct("ZoneWindowError", 2)?;
+                state.serialize_field("code", "window")?;
+                state.serialize_field("message", &self.to_string())?;
+                state.end()
+            }
+        }
+    }
+}
+
+/// An extra clock window pinned to a zone, restored on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneWindow {
+    pub window_label: String,
+    pub time_zone: String,
+    /// Caption shown on the clock face, e.g. "Tokyo office".
+    pub label: String,
+    /// Last position of the window's top-left corner; `None` until moved.
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+}
+
+/// Open zone windows, saved to `zone-windows.json` in the app data
+/// directory whenever one opens, moves, or closes.
+pub struct ZoneWindowStore {
+    windows: Mutex<Vec<ZoneWindow>>,
+    path: Option<PathBuf>,
+}
+
+impl ZoneWindowStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = store::data_file(app, ZONE_WINDOWS_FILE_NAME);
+        let windows = path
+            .as_deref()
+            .and_then(store::read_json)
+            .unwrap_or_default();
+        Self {
+            windows: Mutex::new(windows),
+            path,
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Vec<ZoneWindow>)) -> Result<(), StoreError> {
+        let path = self.path.as_deref().ok_or(StoreError::NoDirectory)?;
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        change(&mut windows);
+        store::write_json(path, &*windows)
+    }
+
+    fn list(&self) -> Vec<ZoneWindow> {
+        self.windows
+            .lock()
+            .map(|windows| windows.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Opens a frameless, always-on-top clock showing `time_zone`, or focuses
+/// the one already open for it. Returns the window label.
+#[tauri::command]
+pub fn open_zone_window(
+    app: AppHandle,
+    time_zone: String,
+    label: Option<String>,
+) -> Result<String, ZoneWindowError> {
+    let zone = parse_zone(&time_zone)?;
+    let window_label = format!(
+        "{LABEL_PREFIX}{}",
+        zone.name().replace('/', "-").to_lowercase()
+    );
+    if let Some(window) = app.get_webview_window(&window_label) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(window_label);
+    }
+
+    let city = zone.name().rsplit('/').next().unwrap_or(zone.name());
+    let entry = ZoneWindow {
+        window_label: window_label.clone(),
+        time_zone: zone.name().to_string(),
+        label: label.unwrap_or_else(|| city.replace('_', " ")),
+        x: None,
+        y: None,
+    };
+    build(&app, &entry)?;
+    app.state::<ZoneWindowStore>().update(|windows| {
+        windows.retain(|window| window.window_label != entry.window_label);
+        windows.push(entry);
+    })?;
+    Ok(window_label)
+}
+
+/// Reopens the zone windows that were open when the app last quit.
+pub fn restore(app: &AppHandle) {
+    for entry in app.state::<ZoneWindowStore>().list() {
+        if let Err(err) = build(app, &entry) {
+            eprintln!("failed to restore window for {}: {err}", entry.time_zone);
+        }
+    }
+}
+
+fn build(app: &AppHandle, entry: &ZoneWindow) -> Result<(), tauri::Error> {
+    let url = format!(
+        "index.html?zone={}&label={}",
+        urlencoding::encode(&entry.time_zone),
+        urlencoding::encode(&entry.label)
+    );
+    let window = WebviewWindowBuilder::new(app, &entry.window_label, WebviewUrl::App(url.into()))
+        .title(&entry.label)
+        .inner_size(WINDOW_SIZE, WINDOW_SIZE)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible_on_all_workspaces(true)
+        .build()?;
+    if let (Some(x), Some(y)) = (entry.x, entry.y) {
+        window.set_position(Position::Physical(PhysicalPosition { x, y }))?;
+    }
+
+    let app = app.clone();
+    let window_label = entry.window_label.clone();
+    window.on_window_event(move |event| {
+        let store = app.state::<ZoneWindowStore>();
+        let result = match event {
+            WindowEvent::Moved(position) => store.update(|windows| {
+                if let Some(window) = windows
+                    .iter_mut()
+                    .find(|window| window.window_label == window_label)
+                {
+                    window.x = Some(position.x);
+                    window.y = Some(position.y);
+                }
+            }),
+            // Closing a window unpins it; quitting the app keeps the list.
+            WindowEvent::CloseRequested { .. } => store.update(|windows| {
+                windows.retain(|window| window.window_label != window_label);
+            }),
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            eprintln!("failed to save zone windows: {err}");
+        }
+    });
+    Ok(())
+}